@@ -0,0 +1,179 @@
+//! The Backlight, Uplight and Glare (BUG) rating of outdoor luminaires, following IES TM-15-11 Addendum A.
+//!
+//! The front of the luminaire is the C0-C180 half-space containing the C90 plane, which points towards the street side
+//! of road lighting luminaires, and the back is the half-space containing the C270 plane.
+
+use crate::{
+    ops::tilt_photometric_web,
+    photweb::PhotometricWeb,
+    util::geom::degrees_to_radians,
+};
+use std::f64::consts::PI;
+
+/// The angular step (in degrees) used when integrating the lumens in each zone.
+const INTEGRATION_STEP: f64 = 0.5;
+
+/// The maximum lumens in the (BH, BM, BL) zones for each of the backlight ratings B0 to B4.
+pub const BACKLIGHT_LIMITS: [(f64, f64, f64); 5] = [
+    (110.0, 220.0, 110.0),
+    (500.0, 1000.0, 500.0),
+    (1000.0, 2500.0, 1000.0),
+    (2500.0, 5000.0, 2500.0),
+    (5000.0, 8500.0, 5000.0),
+];
+
+/// The maximum lumens in the (UH, UL) zones for each of the uplight ratings U0 to U4.
+pub const UPLIGHT_LIMITS: [(f64, f64); 5] = [(0.0, 0.0), (10.0, 10.0), (50.0, 50.0), (500.0, 500.0), (1000.0, 1000.0)];
+
+/// The maximum lumens in the (FVH, BVH, FH, BH) zones for each of the glare ratings G0 to G4.
+pub const GLARE_LIMITS: [(f64, f64, f64, f64); 5] = [
+    (10.0, 10.0, 660.0, 110.0),
+    (100.0, 100.0, 1800.0, 500.0),
+    (225.0, 225.0, 5000.0, 1000.0),
+    (350.0, 350.0, 7500.0, 2500.0),
+    (450.0, 450.0, 12000.0, 5000.0),
+];
+
+/// The lumens emitted into each of the secondary solid angles of TM-15-11.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BugZoneLumens {
+    /// Back low, gamma 0 -> 30 degrees.
+    pub bl: f64,
+    /// Back medium, gamma 30 -> 60 degrees.
+    pub bm: f64,
+    /// Back high, gamma 60 -> 80 degrees.
+    pub bh: f64,
+    /// Back very high, gamma 80 -> 90 degrees.
+    pub bvh: f64,
+    /// Front low, gamma 0 -> 30 degrees.
+    pub fl: f64,
+    /// Front medium, gamma 30 -> 60 degrees.
+    pub fm: f64,
+    /// Front high, gamma 60 -> 80 degrees.
+    pub fh: f64,
+    /// Front very high, gamma 80 -> 90 degrees.
+    pub fvh: f64,
+    /// Uplight low, gamma 90 -> 100 degrees.
+    pub ul: f64,
+    /// Uplight high, gamma 100 -> 180 degrees.
+    pub uh: f64,
+}
+
+impl BugZoneLumens {
+    /// Integrates the lumens in each zone of a Type C web, with intensities in candela.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        let step = degrees_to_radians(INTEGRATION_STEP);
+        let n_c = (2.0 * PI / step).round() as usize;
+        let n_gamma = (PI / step).round() as usize;
+        let mut zones = Self::default();
+        for ic in 0..n_c {
+            let c_angle = (ic as f64 + 0.5) * step;
+            let front = c_angle < PI;
+            for ig in 0..n_gamma {
+                let gamma = (ig as f64 + 0.5) * step;
+                let flux = web.intensity_at(c_angle, gamma) * gamma.sin() * step * step;
+                let gamma_deg = gamma.to_degrees();
+                let zone = match (gamma_deg, front) {
+                    (g, true) if g < 30.0 => &mut zones.fl,
+                    (g, true) if g < 60.0 => &mut zones.fm,
+                    (g, true) if g < 80.0 => &mut zones.fh,
+                    (g, true) if g < 90.0 => &mut zones.fvh,
+                    (g, false) if g < 30.0 => &mut zones.bl,
+                    (g, false) if g < 60.0 => &mut zones.bm,
+                    (g, false) if g < 80.0 => &mut zones.bh,
+                    (g, false) if g < 90.0 => &mut zones.bvh,
+                    (g, _) if g < 100.0 => &mut zones.ul,
+                    _ => &mut zones.uh,
+                };
+                *zone += flux;
+            }
+        }
+        zones
+    }
+}
+
+/// The BUG rating of a luminaire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BugRating {
+    /// The lumens in each of the zones.
+    pub zones: BugZoneLumens,
+    /// The backlight rating, from 0 to 5.
+    pub backlight: u8,
+    /// The uplight rating, from 0 to 5.
+    pub uplight: u8,
+    /// The glare rating, from 0 to 5.
+    pub glare: u8,
+}
+
+impl BugRating {
+    /// Computes the BUG rating of a Type C web, with intensities in candela, for the luminaire installed level.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        let zones = BugZoneLumens::new(web);
+        let rating = |within: &dyn Fn(usize) -> bool| (0..5).find(|i| within(*i)).unwrap_or(5) as u8;
+        Self {
+            backlight: rating(&|i| {
+                let (bh, bm, bl) = BACKLIGHT_LIMITS[i];
+                zones.bh <= bh && zones.bm <= bm && zones.bl <= bl
+            }),
+            uplight: rating(&|i| {
+                let (uh, ul) = UPLIGHT_LIMITS[i];
+                zones.uh <= uh && zones.ul <= ul
+            }),
+            glare: rating(&|i| {
+                let (fvh, bvh, fh, bh) = GLARE_LIMITS[i];
+                zones.fvh <= fvh && zones.bvh <= bvh && zones.fh <= fh && zones.bh <= bh
+            }),
+            zones,
+        }
+    }
+
+    /// Computes the BUG rating of a Type C web for the luminaire installed with a tilt (in degrees) towards the front.
+    pub fn with_tilt(web: &PhotometricWeb, tilt: f64) -> Self {
+        Self::new(&tilt_photometric_web(web, degrees_to_radians(tilt)))
+    }
+}
+
+impl std::fmt::Display for BugRating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "B{}-U{}-G{}", self.backlight, self.uplight, self.glare)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::rotational_web;
+    use approx::assert_abs_diff_eq;
+
+    /// For an isotropic source of intensity I, each front or back zone between gamma_1 and gamma_2 receives
+    /// pi I (cos(gamma_1) - cos(gamma_2)) lumens, and each uplight zone twice that.
+    #[test]
+    fn test_isotropic_zones() {
+        let zones = BugZoneLumens::new(&rotational_web(0.25, |_| 10.0));
+        let zone = |lower: f64, upper: f64| 10.0 * PI * (lower.to_radians().cos() - upper.to_radians().cos());
+        assert_abs_diff_eq!(zones.fl, zone(0.0, 30.0), epsilon = 1E-3);
+        assert_abs_diff_eq!(zones.bm, zone(30.0, 60.0), epsilon = 1E-3);
+        assert_abs_diff_eq!(zones.fh, zone(60.0, 80.0), epsilon = 1E-3);
+        assert_abs_diff_eq!(zones.bvh, zone(80.0, 90.0), epsilon = 1E-3);
+        assert_abs_diff_eq!(zones.ul, 2.0 * zone(90.0, 100.0), epsilon = 1E-3);
+        assert_abs_diff_eq!(zones.uh, 2.0 * zone(100.0, 180.0), epsilon = 1E-3);
+
+        // The uplight of 10.9 lm (UL) and 51.9 lm (UH) gives U3, while 5.5 lm in each very high zone is within G0.
+        let rating = BugRating::new(&rotational_web(0.25, |_| 10.0));
+        assert_eq!(rating.to_string(), "B0-U3-G0");
+    }
+
+    /// A full cut-off downlight has no uplight, and tilting it upwards moves its light forwards and into the uplight zones.
+    #[test]
+    fn test_tilt() {
+        let web = rotational_web(0.25, |gamma| 2000.0 * gamma.cos().max(0.0).powi(4));
+        let level = BugRating::new(&web);
+        assert_eq!(level.uplight, 0);
+        assert_abs_diff_eq!(level.zones.fl, level.zones.bl, epsilon = 1E-6);
+
+        let tilted = BugRating::with_tilt(&web, 30.0);
+        assert!(tilted.zones.fm > level.zones.fm);
+        assert!(tilted.zones.bl < level.zones.bl);
+        assert!(tilted.uplight > 0);
+    }
+}
@@ -0,0 +1,287 @@
+//! The CIE flux code (CIE 52) and intensity distribution class of a luminaire, and the direct ratios stored in
+//! EULUMDAT files.
+
+use super::LightOutputRatios;
+use crate::{io::eulumdat::EulumdatFile, photweb::PhotometricWeb};
+
+/// The room indices for which the direct ratios are stored in EULUMDAT files.
+pub const STANDARD_ROOM_INDICES: [f64; 10] = [0.6, 0.8, 1.0, 1.25, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0];
+
+/// The coefficients (t1, t2, t3, t4) giving the direct ratio for each of the standard room indices from the cumulative
+/// zonal flux of the CIE flux code, as DR = t1 N1 + t2 N2 + t3 N3 + t4, with N1 -> N3 as fractions of the downward flux.
+/// The coefficients are not copied from the tables of CIE 52, but derived for this crate with the zone model that those
+/// tables are built on. The coefficients for each zone are the direct ratios of a square room (room index k = a / 2h)
+/// whose ceiling is uniformly covered with luminaires of constant intensity within that zone and none outside of it,
+/// where a ray leaving a luminaire with a horizontal offset (dx, dy) lands on the working plane with probability
+/// (1 - |dx| / a)(1 - |dy| / a). Differences between successive zones express them in terms of the cumulative flux.
+/// The tests reproduce the coefficients from this model, and check the direct ratios of a Lambertian luminaire against
+/// the configuration factor between parallel squares (Howell, A Catalog of Radiation Heat Transfer Configuration
+/// Factors, C-11), to which the four zones are accurate to about 0.01.
+pub const DIRECT_RATIO_COEFFICIENTS: [[f64; 4]; 10] = [
+    [0.4387, 0.0666, 0.0000, 0.0000],
+    [0.4158, 0.1976, 0.0017, 0.0000],
+    [0.3601, 0.3013, 0.0241, 0.0000],
+    [0.3051, 0.3511, 0.0880, 0.0000],
+    [0.2636, 0.3545, 0.1662, 0.0000],
+    [0.2066, 0.3106, 0.3143, 0.0047],
+    [0.1695, 0.2654, 0.4027, 0.0302],
+    [0.1436, 0.2306, 0.4481, 0.0669],
+    [0.1099, 0.1818, 0.4795, 0.1451],
+    [0.0890, 0.1497, 0.4782, 0.2159],
+];
+
+/// The CIE flux code of a luminaire. Each value is a percentage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CieFluxCode {
+    /// The percentage of the downward flux emitted within the cone of pi / 2 sr (gamma 0 -> 41.4 degrees).
+    pub n1: f64,
+    /// The percentage of the downward flux emitted within the cone of pi sr (gamma 0 -> 60 degrees).
+    pub n2: f64,
+    /// The percentage of the downward flux emitted within the cone of 3 pi / 2 sr (gamma 0 -> 75.5 degrees).
+    pub n3: f64,
+    /// The downward flux fraction, the percentage of the luminaire flux emitted into the lower hemisphere.
+    pub n4: f64,
+    /// The light output ratio, the percentage of the lamp flux emitted by the luminaire.
+    pub n5: f64,
+}
+
+impl CieFluxCode {
+    /// Computes the CIE flux code of a Type C web, given the total flux of the lamps in the same units as the web.
+    pub fn new(web: &PhotometricWeb, lamp_flux: f64) -> Self {
+        let ratios = LightOutputRatios::new(web, lamp_flux);
+        // The cones of pi / 2, pi and 3 pi / 2 steradians have half angles of acos(3 / 4), acos(1 / 2) and acos(1 / 4).
+        let cone_percent = |cos_half_angle: f64| {
+            if ratios.downward_flux > 0.0 {
+                100.0 * web.integrate_intensity_between(f64::NEG_INFINITY, cos_half_angle.acos()) / ratios.downward_flux
+            } else {
+                0.0
+            }
+        };
+
+        Self {
+            n1: cone_percent(0.75),
+            n2: cone_percent(0.5),
+            n3: cone_percent(0.25),
+            n4: ratios.dff,
+            n5: ratios.lor,
+        }
+    }
+}
+
+impl std::fmt::Display for CieFluxCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0} {:.0} {:.0} {:.0} {:.0}",
+            self.n1, self.n2, self.n3, self.n4, self.n5
+        )
+    }
+}
+
+/// The CIE classification of luminaires by the fraction of their flux emitted downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CieDistributionClass {
+    /// 90 -> 100 % of the flux emitted downwards.
+    Direct,
+    /// 60 -> 90 % of the flux emitted downwards.
+    SemiDirect,
+    /// 40 -> 60 % of the flux emitted downwards.
+    GeneralDiffuse,
+    /// 10 -> 40 % of the flux emitted downwards.
+    SemiIndirect,
+    /// 0 -> 10 % of the flux emitted downwards.
+    Indirect,
+}
+
+impl CieDistributionClass {
+    /// Classifies a Type C web by its downward flux fraction.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        let ratios = LightOutputRatios::new(web, 0.0);
+        Self::from_downward_fraction(ratios.dff)
+    }
+
+    /// Classifies a luminaire by its downward flux fraction, in percent.
+    pub fn from_downward_fraction(dff: f64) -> Self {
+        match dff {
+            d if d >= 90.0 => Self::Direct,
+            d if d >= 60.0 => Self::SemiDirect,
+            d if d >= 40.0 => Self::GeneralDiffuse,
+            d if d >= 10.0 => Self::SemiIndirect,
+            _ => Self::Indirect,
+        }
+    }
+}
+
+impl std::fmt::Display for CieDistributionClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Direct => "direct",
+            Self::SemiDirect => "semi-direct",
+            Self::GeneralDiffuse => "general diffuse",
+            Self::SemiIndirect => "semi-indirect",
+            Self::Indirect => "indirect",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Computes the direct ratio for a given room index from the CIE flux code, as a fraction of the downward flux of the
+/// luminaires that falls directly onto the working plane. Room indices between the standard ones are interpolated
+/// linearly, and those outside of the range are clamped to it.
+pub fn direct_ratio(code: &CieFluxCode, room_index: f64) -> f64 {
+    let k = room_index.clamp(STANDARD_ROOM_INDICES[0], STANDARD_ROOM_INDICES[STANDARD_ROOM_INDICES.len() - 1]);
+    let upper = STANDARD_ROOM_INDICES
+        .partition_point(|index| *index < k)
+        .clamp(1, STANDARD_ROOM_INDICES.len() - 1);
+    let frac = (k - STANDARD_ROOM_INDICES[upper - 1]) / (STANDARD_ROOM_INDICES[upper] - STANDARD_ROOM_INDICES[upper - 1]);
+    let ratio = |i: usize| {
+        let [t1, t2, t3, t4] = DIRECT_RATIO_COEFFICIENTS[i];
+        (t1 * code.n1 + t2 * code.n2 + t3 * code.n3) / 100.0 + t4
+    };
+    (1.0 - frac) * ratio(upper - 1) + frac * ratio(upper)
+}
+
+/// Computes the direct ratios of a Type C web for the standard room indices stored in EULUMDAT files.
+pub fn direct_ratios(web: &PhotometricWeb) -> Vec<f64> {
+    let code = CieFluxCode::new(web, 0.0);
+    STANDARD_ROOM_INDICES
+        .iter()
+        .map(|room_index| direct_ratio(&code, *room_index))
+        .collect()
+}
+
+/// Computes the direct ratios of a EULUMDAT file from its intensities, and stores them in the file.
+pub fn fill_eulumdat_direct_ratios(ldt: &mut EulumdatFile) {
+    let web: PhotometricWeb = ldt.clone().into();
+    ldt.set_direct_ratios(direct_ratios(&web));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::rotational_web;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    /// For a Lambertian source, the flux within a cone of half angle theta is proportional to sin^2(theta),
+    /// giving 1 - cos^2(theta) for each of the cones.
+    #[test]
+    fn test_flux_code() {
+        let web = rotational_web(0.25, |gamma| gamma.cos().max(0.0));
+        let code = CieFluxCode::new(&web, 4.0 * PI);
+        assert_abs_diff_eq!(code.n1, 43.75, epsilon = 0.1);
+        assert_abs_diff_eq!(code.n2, 75.0, epsilon = 0.1);
+        assert_abs_diff_eq!(code.n3, 93.75, epsilon = 0.1);
+        assert_abs_diff_eq!(code.n4, 100.0, epsilon = 0.1);
+        assert_abs_diff_eq!(code.n5, 25.0, epsilon = 0.1);
+
+        let code = CieFluxCode { n1: 43.2, n2: 75.0, n3: 93.9, n4: 100.0, n5: 24.6 };
+        assert_eq!(code.to_string(), "43 75 94 100 25");
+    }
+
+    #[test]
+    fn test_distribution_class() {
+        let class = |func: fn(f64) -> f64| CieDistributionClass::new(&rotational_web(0.25, func));
+        assert_eq!(class(|gamma| gamma.cos().max(0.0)), CieDistributionClass::Direct);
+        assert_eq!(class(|_| 1.0), CieDistributionClass::GeneralDiffuse);
+        assert_eq!(class(|gamma| (-gamma.cos()).max(0.0)), CieDistributionClass::Indirect);
+        assert_eq!(CieDistributionClass::from_downward_fraction(75.0).to_string(), "semi-direct");
+        assert_eq!(CieDistributionClass::from_downward_fraction(10.0), CieDistributionClass::SemiIndirect);
+    }
+
+    /// The flux codes of the BZ reference distributions I = cos^n(gamma) (BZ1 -> BZ5 for n = 4, 3, 2, 1.5 and 1) and of
+    /// a constant downward intensity, for which the fraction of the flux within a cone of half angle theta is
+    /// 1 - cos^(n + 1)(theta).
+    #[test]
+    fn test_flux_code_reference_distributions() {
+        for n in [4.0, 3.0, 2.0, 1.5, 1.0, 0.0] {
+            let web = rotational_web(0.25, move |gamma| if gamma <= FRAC_PI_2 { gamma.cos().powf(n) } else { 0.0 });
+            let code = CieFluxCode::new(&web, 1.0);
+            assert_abs_diff_eq!(code.n1, 100.0 * (1.0 - 0.75_f64.powf(n + 1.0)), epsilon = 0.1);
+            assert_abs_diff_eq!(code.n2, 100.0 * (1.0 - 0.5_f64.powf(n + 1.0)), epsilon = 0.1);
+            assert_abs_diff_eq!(code.n3, 100.0 * (1.0 - 0.25_f64.powf(n + 1.0)), epsilon = 0.1);
+            assert_abs_diff_eq!(code.n4, 100.0, epsilon = 0.5);
+        }
+    }
+
+    /// The direct ratios are linear in the cumulative zonal flux, so a luminaire emitting only into the first zone has
+    /// the direct ratio of that zone, and one emitting only into the last zone has the constant term.
+    #[test]
+    fn test_direct_ratio_from_flux_code() {
+        let code = |n1: f64, n2: f64, n3: f64| CieFluxCode { n1, n2, n3, n4: 100.0, n5: 100.0 };
+        assert_abs_diff_eq!(direct_ratio(&code(100.0, 100.0, 100.0), 0.6), 0.5053, epsilon = 1E-9);
+        assert_abs_diff_eq!(direct_ratio(&code(0.0, 0.0, 0.0), 5.0), 0.2159, epsilon = 1E-9);
+        // A constant downward intensity puts a quarter of its flux into each zone.
+        assert_abs_diff_eq!(
+            direct_ratio(&code(25.0, 50.0, 75.0), 2.0),
+            0.25 * 0.2066 + 0.5 * 0.3106 + 0.75 * 0.3143 + 0.0047,
+            epsilon = 1E-9
+        );
+
+        // Room indices between the standard ones are interpolated, and those outside are clamped.
+        let lambertian = code(43.75, 75.0, 93.75);
+        let (lower, upper) = (direct_ratio(&lambertian, 0.6), direct_ratio(&lambertian, 0.8));
+        assert_abs_diff_eq!(direct_ratio(&lambertian, 0.7), 0.5 * (lower + upper), epsilon = 1E-9);
+        assert_eq!(direct_ratio(&lambertian, 0.4), lower);
+        assert_eq!(direct_ratio(&lambertian, 8.0), direct_ratio(&lambertian, 5.0));
+    }
+
+    /// The direct ratio of a square room of room index k whose ceiling is uniformly covered with luminaires of constant
+    /// intensity between the gamma angles (in radians), and none outside of them, integrated over a quadrant of C-planes.
+    fn zone_direct_ratio(room_index: f64, lower: f64, upper: f64) -> f64 {
+        let step = (0.25_f64).to_radians();
+        let n = (FRAC_PI_2 / step).round() as usize;
+        let (direct, downward) = (0..n)
+            .flat_map(|ic| (0..n).map(move |ig| ((ic as f64 + 0.5) * step, (ig as f64 + 0.5) * step)))
+            .filter(|(_, gamma)| *gamma >= lower && *gamma < upper)
+            .fold((0.0, 0.0), |(direct, downward), (c_angle, gamma)| {
+                let offset = gamma.tan() / (2.0 * room_index);
+                let probability = (1.0 - offset * c_angle.cos()).max(0.0) * (1.0 - offset * c_angle.sin()).max(0.0);
+                (direct + gamma.sin() * probability, downward + gamma.sin())
+            });
+        direct / downward
+    }
+
+    /// The tabulated coefficients are reproduced by integrating the zone model for each of the four zones, to within
+    /// the error of binning the zone edges onto the integration grid.
+    #[test]
+    fn test_direct_ratio_coefficients() {
+        let edges = [0.0, 0.75_f64.acos(), 0.5_f64.acos(), 0.25_f64.acos(), FRAC_PI_2];
+        for (room_index, coefficients) in STANDARD_ROOM_INDICES.iter().zip(DIRECT_RATIO_COEFFICIENTS) {
+            let zones: Vec<f64> = edges.windows(2).map(|zone| zone_direct_ratio(*room_index, zone[0], zone[1])).collect();
+            let expected = [zones[0] - zones[1], zones[1] - zones[2], zones[2] - zones[3], zones[3]];
+            for (coefficient, expected) in coefficients.iter().zip(expected) {
+                assert_abs_diff_eq!(*coefficient, expected, epsilon = 1.5E-3);
+            }
+        }
+    }
+
+    /// A ceiling of uniformly distributed Lambertian luminaires behaves as a Lambertian area source, so the direct ratio
+    /// is the configuration factor between two directly opposed parallel squares of side a, separated by h, where
+    /// a / h = 2k. This is independent of the zone model, which only keeps the flux in each of the four zones and so
+    /// falls short of it by about 0.01.
+    #[test]
+    fn test_direct_ratios_lambertian() {
+        let view_factor = |x: f64| {
+            let root = (1.0 + x * x).sqrt();
+            2.0 / (PI * x * x)
+                * (((1.0 + x * x).powi(2) / (1.0 + 2.0 * x * x)).ln() / 2.0 + 2.0 * x * root * (x / root).atan()
+                    - 2.0 * x * x.atan())
+        };
+
+        let lambertian = direct_ratios(&rotational_web(0.25, |gamma| gamma.cos().max(0.0)));
+        for (room_index, ratio) in STANDARD_ROOM_INDICES.iter().zip(lambertian) {
+            assert_abs_diff_eq!(ratio, view_factor(2.0 * room_index), epsilon = 0.015);
+        }
+    }
+
+    /// The direct ratios increase with the room index, and more concentrated distributions have higher direct ratios.
+    #[test]
+    fn test_direct_ratios() {
+        let lambertian = direct_ratios(&rotational_web(0.25, |gamma| gamma.cos().max(0.0)));
+        let concentrated = direct_ratios(&rotational_web(0.25, |gamma| gamma.cos().max(0.0).powi(4)));
+        assert_eq!(lambertian.len(), STANDARD_ROOM_INDICES.len());
+        assert!(lambertian.windows(2).all(|vals| vals[1] > vals[0]));
+        assert!(concentrated.iter().zip(&lambertian).all(|(conc, lamb)| conc > lamb));
+    }
+}
@@ -0,0 +1,167 @@
+//! The cone diagram of a spotlight: the diameter of the beam, and the illuminance at its centre and on average across it,
+//! at a series of throw distances along the axis of the beam.
+
+use super::BeamMetrics;
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+
+/// The throw distances (in metres) of a metric cone diagram.
+pub const STANDARD_CONE_DISTANCES_METRIC: [f64; 5] = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+/// The throw distances (in feet) of an imperial cone diagram.
+pub const STANDARD_CONE_DISTANCES_IMPERIAL: [f64; 5] = [3.0, 6.0, 9.0, 12.0, 15.0];
+
+/// The system of units of a cone diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConeUnits {
+    /// Distances in metres, and illuminances in lux.
+    Metric,
+    /// Distances in feet, and illuminances in footcandles.
+    Imperial,
+}
+
+impl ConeUnits {
+    /// The symbol of the unit of length.
+    pub fn length_symbol(&self) -> &'static str {
+        match self {
+            Self::Metric => "m",
+            Self::Imperial => "ft",
+        }
+    }
+
+    /// The symbol of the unit of illuminance.
+    pub fn illuminance_symbol(&self) -> &'static str {
+        match self {
+            Self::Metric => "lx",
+            Self::Imperial => "fc",
+        }
+    }
+
+    /// The standard throw distances of the units.
+    pub fn standard_distances(&self) -> &'static [f64] {
+        match self {
+            Self::Metric => &STANDARD_CONE_DISTANCES_METRIC,
+            Self::Imperial => &STANDARD_CONE_DISTANCES_IMPERIAL,
+        }
+    }
+}
+
+/// A row of a cone diagram, for one throw distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConeDiagramRow {
+    /// The distance along the axis of the beam.
+    pub distance: f64,
+    /// The diameter of the beam at the distance.
+    pub diameter: f64,
+    /// The illuminance at the centre of the beam.
+    pub centre_illuminance: f64,
+    /// The average illuminance across the beam.
+    pub average_illuminance: f64,
+}
+
+/// The cone diagram of a spotlight aimed along the nadir of its web.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConeDiagram {
+    /// The units of the distances and illuminances.
+    pub units: ConeUnits,
+    /// The beam angle, in degrees.
+    pub beam_angle: f64,
+    /// The axial (centre-beam) intensity.
+    pub axial_intensity: f64,
+    /// The flux emitted within the beam angle.
+    pub beam_flux: f64,
+    /// The rows of the diagram, in order of distance.
+    pub rows: Vec<ConeDiagramRow>,
+}
+
+impl ConeDiagram {
+    /// Computes the cone diagram of a Type C web at the standard distances of the units.
+    pub fn new(web: &PhotometricWeb, units: ConeUnits) -> Self {
+        Self::with_distances(web, units, units.standard_distances())
+    }
+
+    /// Computes the cone diagram of a Type C web at the given distances, in the length unit of the units.
+    /// The beam diameter follows from the beam angle, and the centre illuminance from the axial intensity by the inverse
+    /// square law. The average illuminance is the flux emitted within the beam angle spread over the area of the beam.
+    /// Intensities in candela give illuminances in lux or footcandles, and intensities in cd/klm give values per 1000 lm.
+    pub fn with_distances(web: &PhotometricWeb, units: ConeUnits, distances: &[f64]) -> Self {
+        let metrics = BeamMetrics::new(web);
+        let half_angle = degrees_to_radians(metrics.beam_angle / 2.0);
+        let axial_intensity = metrics.centre_beam_intensity;
+        let beam_flux = web.integrate_intensity_between(f64::NEG_INFINITY, half_angle);
+        let rows = distances
+            .iter()
+            .map(|distance| {
+                let radius = distance * half_angle.tan();
+                let area = std::f64::consts::PI * radius * radius;
+                ConeDiagramRow {
+                    distance: *distance,
+                    diameter: 2.0 * radius,
+                    centre_illuminance: axial_intensity / (distance * distance),
+                    average_illuminance: if area > 0.0 { beam_flux / area } else { 0.0 },
+                }
+            })
+            .collect();
+        Self {
+            units,
+            beam_angle: metrics.beam_angle,
+            axial_intensity,
+            beam_flux,
+            rows,
+        }
+    }
+}
+
+impl std::fmt::Display for ConeDiagram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (length, illuminance) = (self.units.length_symbol(), self.units.illuminance_symbol());
+        writeln!(f, "Beam angle {:.1} deg, axial intensity {:.0} cd", self.beam_angle, self.axial_intensity)?;
+        writeln!(
+            f,
+            "{:>10} {:>10} {:>10} {:>10}",
+            format!("d ({})", length),
+            format!("D ({})", length),
+            format!("E ({})", illuminance),
+            format!("Eav ({})", illuminance)
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>10.1} {:>10.2} {:>10.0} {:>10.0}",
+                row.distance, row.diameter, row.centre_illuminance, row.average_illuminance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::rotational_web;
+    use approx::assert_abs_diff_eq;
+
+    /// A cone of uniform intensity, with a sharp edge, so that the beam angle is twice the half angle of the cone.
+    fn cone_web(intensity: f64, half_angle: f64) -> PhotometricWeb {
+        rotational_web(0.1, |gamma| if gamma.to_degrees() <= half_angle { intensity } else { 0.0 })
+    }
+
+    /// For a uniform cone of half angle theta, the flux in the beam is 2 pi I (1 - cos(theta)), spread over an area of
+    /// pi d^2 tan^2(theta) at a distance d.
+    #[test]
+    fn test_uniform_cone() {
+        let diagram = ConeDiagram::new(&cone_web(1000.0, 15.0), ConeUnits::Metric);
+        assert_abs_diff_eq!(diagram.beam_angle, 30.0, epsilon = 0.1);
+        assert_eq!(diagram.rows.len(), 5);
+
+        let row = &diagram.rows[1];
+        let theta = 15_f64.to_radians();
+        assert_abs_diff_eq!(row.diameter, 4.0 * theta.tan(), epsilon = 0.01);
+        assert_abs_diff_eq!(row.centre_illuminance, 250.0, epsilon = 1E-9);
+        let average = 1000.0 * 2.0 * (1.0 - theta.cos()) / (4.0 * theta.tan().powi(2));
+        assert_abs_diff_eq!(row.average_illuminance, average, epsilon = 0.01 * average);
+
+        let imperial = ConeDiagram::new(&cone_web(1000.0, 15.0), ConeUnits::Imperial);
+        assert_abs_diff_eq!(imperial.rows[0].centre_illuminance, 1000.0 / 9.0, epsilon = 1E-9);
+        assert!(imperial.to_string().contains("E (fc)"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Coefficient of utilization (CU) tables, computed with the IES zonal cavity method.
+//!
+//! The room cavity is modelled as an enclosure of three surfaces: the effective ceiling cavity (in the plane of the luminaires),
+//! the walls and the effective floor cavity (in the plane of the work plane). The direct flux from the luminaire onto each
+//! surface is found from the zonal lumens and the standard zonal multipliers, and the interreflections between the surfaces
+//! are then solved exactly using the form factors of the cavity.
+
+use super::ZonalLumenSummary;
+use crate::photweb::PhotometricWeb;
+
+/// The standard room cavity ratios of a CU table.
+pub const STANDARD_ROOM_CAVITY_RATIOS: [f64; 11] = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+
+/// The standard (ceiling cavity, wall, floor cavity) reflectances of a CU table.
+pub const STANDARD_REFLECTANCES: [(f64, f64, f64); 18] = [
+    (0.8, 0.7, 0.2),
+    (0.8, 0.5, 0.2),
+    (0.8, 0.3, 0.2),
+    (0.8, 0.1, 0.2),
+    (0.7, 0.7, 0.2),
+    (0.7, 0.5, 0.2),
+    (0.7, 0.3, 0.2),
+    (0.7, 0.1, 0.2),
+    (0.5, 0.5, 0.2),
+    (0.5, 0.3, 0.2),
+    (0.5, 0.1, 0.2),
+    (0.3, 0.5, 0.2),
+    (0.3, 0.3, 0.2),
+    (0.3, 0.1, 0.2),
+    (0.1, 0.5, 0.2),
+    (0.1, 0.3, 0.2),
+    (0.1, 0.1, 0.2),
+    (0.0, 0.0, 0.0),
+];
+
+/// The (A, B) coefficients of the zonal multipliers Kz = 1 - exp(-A RCR^B), for the 10 degree zones in the lower hemisphere.
+/// The zonal multiplier gives the fraction of the flux in a zone that falls onto the walls rather than the floor cavity.
+const ZONAL_MULTIPLIER_COEFFICIENTS: [(f64, f64); 9] = [
+    (0.000, 0.00),
+    (0.041, 0.98),
+    (0.070, 1.05),
+    (0.100, 1.12),
+    (0.136, 1.16),
+    (0.190, 1.25),
+    (0.315, 1.25),
+    (0.640, 1.25),
+    (2.100, 0.80),
+];
+
+/// The fractions of the lamp flux emitted downwards, in each 10 degree zone, and upwards into the ceiling cavity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CavityFlux {
+    /// The fraction of the lamp flux emitted in each of the nine 10 degree zones from 0 -> 90 degrees.
+    pub downward_zones: [f64; 9],
+    /// The fraction of the lamp flux emitted into the upper hemisphere.
+    pub upward: f64,
+}
+
+impl CavityFlux {
+    /// Extracts the cavity fluxes from a zonal lumen summary computed with the standard 10 degree zones.
+    pub fn from_zonal_summary(summary: &ZonalLumenSummary, lamp_flux: f64) -> Self {
+        let fraction = |flux: f64| if lamp_flux > 0.0 { flux / lamp_flux } else { 0.0 };
+        let mut downward_zones = [0.0; 9];
+        let mut upward = 0.0;
+        for zone in &summary.zones {
+            let izone = (zone.lower / 10.0).round() as usize;
+            if zone.upper <= 90.0 && izone < 9 {
+                downward_zones[izone] += fraction(zone.flux);
+            } else {
+                upward += fraction(zone.flux);
+            }
+        }
+        Self { downward_zones, upward }
+    }
+
+    /// The fraction of the lamp flux emitted downwards.
+    pub fn downward(&self) -> f64 {
+        self.downward_zones.iter().sum()
+    }
+
+    /// The fraction of the downward flux that falls directly onto the floor cavity, for a given room cavity ratio.
+    pub fn direct_ratio(&self, rcr: f64) -> f64 {
+        let downward = self.downward();
+        if downward <= 0.0 {
+            return 0.0;
+        }
+        let to_walls: f64 = self
+            .downward_zones
+            .iter()
+            .zip(ZONAL_MULTIPLIER_COEFFICIENTS)
+            .map(|(flux, (a, b))| flux * (1.0 - (-a * rcr.powf(b)).exp()))
+            .sum();
+        1.0 - to_walls / downward
+    }
+
+    /// Computes the coefficient of utilization for a room cavity ratio and the (ceiling cavity, wall, floor cavity) reflectances.
+    /// This is the fraction of the lamp flux that falls onto the work plane, directly and after interreflection.
+    pub fn coefficient_of_utilization(&self, rcr: f64, reflectances: (f64, f64, f64)) -> f64 {
+        let (rho_c, rho_w, rho_f) = reflectances;
+        let downward = self.downward();
+        let direct_ratio = self.direct_ratio(rcr);
+
+        // The form factors between the ceiling and floor cavities, and from the walls to each of them.
+        // The ratio of the wall area to the floor area is RCR / 2.5.
+        let f2 = if rcr > 0.0 {
+            0.026 + 0.503 * (-0.270 * rcr).exp() + 0.470 * (-0.119 * rcr).exp()
+        } else {
+            1.0
+        };
+        let f_wall = if rcr > 0.0 { (1.0 - f2) * 2.5 / rcr } else { 0.0 };
+
+        // Solve for the flux leaving each surface (ceiling, walls, floor), B = rho (D + F^T B),
+        // where D is the direct flux onto each surface and F the form factors between them.
+        let direct = [self.upward, downward * (1.0 - direct_ratio), downward * direct_ratio];
+        let rho = [rho_c, rho_w, rho_f];
+        let form_factors = [
+            [0.0, 1.0 - f2, f2],
+            [f_wall, 1.0 - 2.0 * f_wall, f_wall],
+            [f2, 1.0 - f2, 0.0],
+        ];
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, val) in row.iter_mut().enumerate() {
+                *val = if i == j { 1.0 } else { 0.0 } - rho[i] * form_factors[j][i];
+            }
+        }
+        let rhs = [rho[0] * direct[0], rho[1] * direct[1], rho[2] * direct[2]];
+        let exitance = solve3(matrix, rhs);
+
+        // The flux onto the work plane is the direct flux plus that arriving from the ceiling and walls.
+        direct[2] + form_factors[0][2] * exitance[0] + form_factors[1][2] * exitance[1]
+    }
+}
+
+/// Solves a 3x3 linear system with Gaussian elimination and partial pivoting.
+pub(crate) fn solve3(mut matrix: [[f64; 3]; 3], mut rhs: [f64; 3]) -> [f64; 3] {
+    for col in 0..3 {
+        let pivot = (col..3).fold(col, |best, row| {
+            if matrix[row][col].abs() > matrix[best][col].abs() { row } else { best }
+        });
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+        if matrix[col][col] == 0.0 {
+            continue;
+        }
+        let pivot_row = matrix[col];
+        for row in col + 1..3 {
+            let factor = matrix[row][col] / pivot_row[col];
+            for (val, pivot_val) in matrix[row].iter_mut().zip(pivot_row).skip(col) {
+                *val -= factor * pivot_val;
+            }
+            rhs[row] -= factor * rhs[col];
+        }
+    }
+
+    let mut solution = [0.0; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = if matrix[row][row] != 0.0 { (rhs[row] - sum) / matrix[row][row] } else { 0.0 };
+    }
+    solution
+}
+
+/// A table of coefficients of utilization, for a set of room cavity ratios and reflectance combinations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuTable {
+    /// The room cavity ratios, one for each row of the table.
+    pub room_cavity_ratios: Vec<f64>,
+    /// The (ceiling cavity, wall, floor cavity) reflectances, one for each column of the table.
+    pub reflectances: Vec<(f64, f64, f64)>,
+    /// The coefficients of utilization, indexed by [room cavity ratio][reflectances].
+    pub values: Vec<Vec<f64>>,
+}
+
+impl CuTable {
+    /// Computes the standard CU table of a Type C web, given the total flux of the lamps in the same units as the web.
+    pub fn new(web: &PhotometricWeb, lamp_flux: f64) -> Self {
+        Self::with_parameters(web, lamp_flux, &STANDARD_ROOM_CAVITY_RATIOS, &STANDARD_REFLECTANCES)
+    }
+
+    /// Computes a CU table of a Type C web for the given room cavity ratios and (ceiling cavity, wall, floor cavity) reflectances.
+    pub fn with_parameters(
+        web: &PhotometricWeb,
+        lamp_flux: f64,
+        room_cavity_ratios: &[f64],
+        reflectances: &[(f64, f64, f64)],
+    ) -> Self {
+        let cavity_flux = CavityFlux::from_zonal_summary(&ZonalLumenSummary::new(web, Some(lamp_flux)), lamp_flux);
+        Self {
+            room_cavity_ratios: room_cavity_ratios.to_vec(),
+            reflectances: reflectances.to_vec(),
+            values: room_cavity_ratios
+                .iter()
+                .map(|rcr| {
+                    reflectances
+                        .iter()
+                        .map(|refl| cavity_flux.coefficient_of_utilization(*rcr, *refl))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for CuTable {
+    /// Formats the table as text, with the reflectances (in percent) as column headers and the CU values multiplied by 100,
+    /// as is conventional in photometric reports.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = |f: &mut std::fmt::Formatter<'_>, label: &str, func: fn(&(f64, f64, f64)) -> f64| {
+            write!(f, "{:>5}", label)?;
+            for refl in &self.reflectances {
+                write!(f, "{:>4.0}", 100.0 * func(refl))?;
+            }
+            writeln!(f)
+        };
+        header(f, "RC", |refl| refl.0)?;
+        header(f, "RW", |refl| refl.1)?;
+        header(f, "RF", |refl| refl.2)?;
+        for (rcr, row) in self.room_cavity_ratios.iter().zip(&self.values) {
+            write!(f, "{:>5}", rcr)?;
+            for val in row {
+                write!(f, "{:>4.0}", 100.0 * val)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::rotational_web;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    /// A rotationally symmetric Lambertian web, emitting pi lumens downwards and a tenth of that upwards.
+    fn web() -> PhotometricWeb {
+        rotational_web(1.0, |gamma| if gamma <= PI / 2.0 { gamma.cos() } else { -0.1 * gamma.cos() })
+    }
+
+    /// With no reflections, only the direct flux onto the work plane is utilized.
+    #[test]
+    fn test_black_room() {
+        let lamp_flux = 2.0 * PI;
+        let cavity_flux = CavityFlux::from_zonal_summary(&ZonalLumenSummary::new(&web(), None), lamp_flux);
+        assert_abs_diff_eq!(cavity_flux.downward(), 0.5, epsilon = 1E-3);
+        assert_abs_diff_eq!(cavity_flux.upward, 0.05, epsilon = 1E-3);
+        assert_abs_diff_eq!(cavity_flux.direct_ratio(0.0), 1.0, epsilon = 1E-9);
+
+        for rcr in [0.0, 1.0, 5.0, 10.0] {
+            assert_abs_diff_eq!(
+                cavity_flux.coefficient_of_utilization(rcr, (0.0, 0.0, 0.0)),
+                cavity_flux.downward() * cavity_flux.direct_ratio(rcr),
+                epsilon = 1E-9
+            );
+        }
+    }
+
+    /// With no walls, the ceiling and floor cavities reflect light back and forth between them, such that
+    /// the flux onto the floor E satisfies E = down + rho_c (up + rho_f E).
+    #[test]
+    fn test_no_walls() {
+        let cavity_flux = CavityFlux::from_zonal_summary(&ZonalLumenSummary::new(&web(), None), 2.0 * PI);
+        let (down, up) = (cavity_flux.downward(), cavity_flux.upward);
+        let (rho_c, rho_f) = (0.8, 0.2);
+        assert_abs_diff_eq!(
+            cavity_flux.coefficient_of_utilization(0.0, (rho_c, 0.5, rho_f)),
+            (down + rho_c * up) / (1.0 - rho_c * rho_f),
+            epsilon = 1E-9
+        );
+    }
+
+    /// The CU should decrease as the room becomes narrower, and increase with the reflectance of the walls.
+    #[test]
+    fn test_cu_table() {
+        let table = CuTable::new(&web(), 2.0 * PI);
+        assert_eq!(table.values.len(), 11);
+        assert_eq!(table.values[0].len(), 18);
+        for row in table.values.windows(2) {
+            assert!(row[0].iter().zip(&row[1]).all(|(wide, narrow)| narrow <= wide));
+        }
+        assert!(table.values[5][0] > table.values[5][1]);
+
+        let text = table.to_string();
+        assert_eq!(text.lines().count(), 14);
+        assert!(text.lines().next().unwrap().starts_with("   RC  80  80"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Floodlight metrics: the beam and field spreads in the horizontal and vertical directions, the NEMA beam types and the
+//! beam efficiency.
+//!
+//! Directions are described with Type B (V, H) angles, see `Vector3::from_type_b`, so that Type B webs are analysed
+//! directly and Type C webs are analysed as if converted to the V-H system. The spreads are measured through the
+//! direction of the peak intensity, which is taken as the beam axis.
+
+use super::metrics::full_width;
+use crate::{
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, radians_to_degrees, Vector3},
+};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// The fraction of the peak intensity that defines the edge of the beam spread.
+const BEAM_SPREAD_FRACTION: f64 = 0.5;
+/// The fraction of the peak intensity that defines the edge of the field spread, used for the NEMA beam type.
+const FIELD_SPREAD_FRACTION: f64 = 0.1;
+/// The angular step (in degrees) used when searching and integrating the web.
+const SEARCH_STEP: f64 = 0.5;
+
+/// The upper limits (in degrees) of the field spread for the NEMA beam types 1 to 6. Wider beams are type 7.
+pub const NEMA_BEAM_LIMITS: [f64; 6] = [18.0, 29.0, 46.0, 70.0, 100.0, 130.0];
+
+/// Returns the NEMA beam type (1 to 7) for a field spread in degrees.
+pub fn nema_beam_type(field_spread: f64) -> u8 {
+    NEMA_BEAM_LIMITS
+        .iter()
+        .position(|limit| field_spread < *limit)
+        .unwrap_or(NEMA_BEAM_LIMITS.len()) as u8
+        + 1
+}
+
+/// The spreads of a floodlight in one direction. Angles are full angles, in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloodlightSpread {
+    /// The full angle over which the intensity is at least 50% of the peak intensity.
+    pub beam_spread: f64,
+    /// The full angle over which the intensity is at least 10% of the peak intensity.
+    pub field_spread: f64,
+    /// The NEMA beam type, from the field spread.
+    pub nema_type: u8,
+}
+
+impl FloodlightSpread {
+    /// Finds the spreads of a curve of intensities against angle (in radians).
+    fn from_curve(angles: &[f64], intensities: &[f64], peak: f64) -> Self {
+        let field_spread = full_width(angles, intensities, FIELD_SPREAD_FRACTION * peak);
+        Self {
+            beam_spread: full_width(angles, intensities, BEAM_SPREAD_FRACTION * peak),
+            field_spread,
+            nema_type: nema_beam_type(field_spread),
+        }
+    }
+}
+
+/// The floodlight metrics of a web.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloodlightMetrics {
+    /// The peak intensity.
+    pub peak_intensity: f64,
+    /// The direction of the peak intensity, as a (V, H) pair in degrees.
+    pub peak_direction: (f64, f64),
+    /// The spreads in the horizontal direction, varying H through the peak.
+    pub horizontal: FloodlightSpread,
+    /// The spreads in the vertical direction, varying V through the peak.
+    pub vertical: FloodlightSpread,
+    /// The beam lumens: the flux emitted in directions where the intensity is at least 10% of the peak intensity.
+    pub beam_lumens: f64,
+    /// The beam efficiency, the percentage of the lamp flux emitted as beam lumens, if the lamp flux is known.
+    pub beam_efficiency: Option<f64>,
+}
+
+impl FloodlightMetrics {
+    /// Computes the floodlight metrics of a web, given the total flux of the lamps if known.
+    pub fn new(web: &PhotometricWeb, lamp_flux: Option<f64>) -> Self {
+        let step = degrees_to_radians(SEARCH_STEP);
+        let n = (PI / step).round() as usize;
+        let angles: Vec<f64> = (0..=n).map(|i| -FRAC_PI_2 + i as f64 * step).collect();
+        let intensity = |v: f64, h: f64| web.intensity_towards(&Vector3::from_type_b(v, h));
+
+        let (peak_intensity, (peak_v, peak_h)) = angles
+            .iter()
+            .flat_map(|v| angles.iter().map(move |h| (*v, *h)))
+            .map(|(v, h)| (intensity(v, h), (v, h)))
+            .fold((0.0, (0.0, 0.0)), |max, val| if val.0 > max.0 { val } else { max });
+
+        // H is limited to -pi / 2 -> pi / 2, while V runs all the way around, so the vertical curve is centred on the peak.
+        let horizontal: Vec<f64> = angles.iter().map(|h| intensity(peak_v, *h)).collect();
+        let vertical_angles: Vec<f64> = angles.iter().map(|v| peak_v + v).collect();
+        let vertical: Vec<f64> = vertical_angles.iter().map(|v| intensity(*v, peak_h)).collect();
+
+        let beam_lumens = flux_above(web, FIELD_SPREAD_FRACTION * peak_intensity);
+        Self {
+            peak_intensity,
+            peak_direction: (radians_to_degrees(peak_v), radians_to_degrees(peak_h)),
+            horizontal: FloodlightSpread::from_curve(&angles, &horizontal, peak_intensity),
+            vertical: FloodlightSpread::from_curve(&vertical_angles, &vertical, peak_intensity),
+            beam_lumens,
+            beam_efficiency: lamp_flux.filter(|flux| *flux > 0.0).map(|flux| 100.0 * beam_lumens / flux),
+        }
+    }
+
+    /// The NEMA designation of the floodlight, such as "6H x 4V".
+    pub fn nema_designation(&self) -> String {
+        format!("{}H x {}V", self.horizontal.nema_type, self.vertical.nema_type)
+    }
+}
+
+/// Integrates the flux emitted in the directions where the intensity is at least the threshold, over the whole sphere.
+fn flux_above(web: &PhotometricWeb, threshold: f64) -> f64 {
+    let step = degrees_to_radians(SEARCH_STEP);
+    let n_c = (2.0 * PI / step).round() as usize;
+    let n_gamma = (PI / step).round() as usize;
+    (0..n_c)
+        .flat_map(|ic| (0..n_gamma).map(move |ig| (ic, ig)))
+        .map(|(ic, ig)| {
+            let (c_angle, gamma) = ((ic as f64 + 0.5) * step, (ig as f64 + 0.5) * step);
+            let intensity = web.intensity_towards(&Vector3::from_c_gamma(c_angle, gamma));
+            if intensity >= threshold {
+                intensity * gamma.sin() * step * step
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ops::convert_photometric_web,
+        photweb::{test_webs::planar_web, PlaneOrientation},
+    };
+    use approx::assert_abs_diff_eq;
+
+    /// A Type C web with a gaussian beam around the nadir, with widths sigma_v and sigma_h (in degrees) in the V and H angles.
+    fn gaussian_web(sigma_v: f64, sigma_h: f64) -> PhotometricWeb {
+        planar_web(5.0, 1.0, |c_angle, gamma| {
+            let (v, h) = Vector3::from_c_gamma(c_angle, gamma).to_type_b();
+            let (v, h) = (radians_to_degrees(v), radians_to_degrees(h));
+            1000.0 * (-0.5 * (v / sigma_v).powi(2) - 0.5 * (h / sigma_h).powi(2)).exp()
+        })
+    }
+
+    #[test]
+    fn test_nema_types() {
+        assert_eq!(nema_beam_type(5.0), 1);
+        assert_eq!(nema_beam_type(18.0), 2);
+        assert_eq!(nema_beam_type(69.9), 4);
+        assert_eq!(nema_beam_type(150.0), 7);
+    }
+
+    /// The full width of a gaussian at 10% of its peak is 2 sigma sqrt(2 ln 10) = 4.29 sigma.
+    #[test]
+    fn test_floodlight() {
+        let web = gaussian_web(20.0, 30.0);
+        let metrics = FloodlightMetrics::new(&web, Some(10000.0));
+        assert_abs_diff_eq!(metrics.vertical.field_spread, 4.29 * 20.0, epsilon = 1.0);
+        assert_abs_diff_eq!(metrics.horizontal.field_spread, 4.29 * 30.0, epsilon = 1.5);
+        assert_abs_diff_eq!(metrics.horizontal.beam_spread, 2.355 * 30.0, epsilon = 1.0);
+        assert_eq!(metrics.nema_designation(), "6H x 5V");
+        assert!(metrics.beam_efficiency.unwrap() > 0.0);
+
+        // The same beam as a Type B web gives the same designation.
+        let type_b = convert_photometric_web(&web, PlaneOrientation::Horizontal);
+        let metrics_b = FloodlightMetrics::new(&type_b, None);
+        assert_eq!(metrics_b.nema_designation(), "6H x 5V");
+        assert_abs_diff_eq!(metrics_b.vertical.field_spread, metrics.vertical.field_spread, epsilon = 1.0);
+        assert_eq!(metrics_b.beam_efficiency, None);
+    }
+}
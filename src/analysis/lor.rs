@@ -0,0 +1,253 @@
+//! Light output ratios of a luminaire, and checks of these against the values stated in the headers of photometric files.
+
+use crate::{
+    io::{eulumdat::EulumdatFile, ies::IesFile},
+    ops::scale_photometric_web,
+    photweb::PhotometricWeb,
+};
+use std::f64::consts::FRAC_PI_2;
+
+/// The flux of the lamps assumed by EULUMDAT files, whose intensities are given in cd/klm.
+pub const EULUMDAT_LAMP_FLUX: f64 = 1000.0;
+
+/// The light output ratio (in percent) below which the flux of an IES file is considered implausibly low compared with
+/// its rated lamp flux, which usually indicates that the candela multiplying factor or the lamp lumens are incorrect.
+pub const MIN_PLAUSIBLE_LOR: f64 = 20.0;
+
+/// The light output ratios of a luminaire, derived from the integrated intensities of the web and the lamp flux.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightOutputRatios {
+    /// The total flux of the lamps.
+    pub lamp_flux: f64,
+    /// The total flux emitted by the luminaire.
+    pub luminaire_flux: f64,
+    /// The flux emitted into the lower hemisphere (gamma 0 -> 90 degrees).
+    pub downward_flux: f64,
+    /// The flux emitted into the upper hemisphere (gamma 90 -> 180 degrees).
+    pub upward_flux: f64,
+    /// The light output ratio of the luminaire (LOR), in percent.
+    pub lor: f64,
+    /// The downward light output ratio (DLOR), in percent.
+    pub dlor: f64,
+    /// The upward light output ratio (ULOR), in percent.
+    pub ulor: f64,
+    /// The downward flux fraction (DFF), the percentage of the luminaire flux that is emitted downwards.
+    pub dff: f64,
+}
+
+impl LightOutputRatios {
+    /// Computes the light output ratios of a Type C web, given the total flux of the lamps in the same units as the web.
+    pub fn new(web: &PhotometricWeb, lamp_flux: f64) -> Self {
+        let downward_flux = web.integrate_intensity_between(f64::NEG_INFINITY, FRAC_PI_2);
+        let upward_flux = web.integrate_intensity_between(FRAC_PI_2, f64::INFINITY);
+        let luminaire_flux = downward_flux + upward_flux;
+        let percent = |num: f64, denom: f64| if denom > 0.0 { 100.0 * num / denom } else { 0.0 };
+
+        Self {
+            lamp_flux,
+            luminaire_flux,
+            downward_flux,
+            upward_flux,
+            lor: percent(luminaire_flux, lamp_flux),
+            dlor: percent(downward_flux, lamp_flux),
+            ulor: percent(upward_flux, lamp_flux),
+            dff: percent(downward_flux, luminaire_flux),
+        }
+    }
+}
+
+/// A value stated in the header of a file that disagrees with the value computed from its intensities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderDiscrepancy {
+    /// The name of the header value.
+    pub field: String,
+    /// The value stated in the header of the file.
+    pub header: f64,
+    /// The name of the value computed from the intensities that the header value is compared against.
+    pub compared_with: String,
+    /// The value computed from the intensities of the file.
+    pub computed: f64,
+}
+
+impl std::fmt::Display for HeaderDiscrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is stated as {:.2} in the header, but the {} computed from the intensities is {:.2}.",
+            self.field, self.header, self.compared_with, self.computed
+        )
+    }
+}
+
+/// Computes the light output ratios of a EULUMDAT file, whose intensities are relative to 1000 lm of lamp flux.
+pub fn eulumdat_light_output_ratios(ldt: &EulumdatFile) -> LightOutputRatios {
+    let web: PhotometricWeb = ldt.clone().into();
+    LightOutputRatios::new(&web, EULUMDAT_LAMP_FLUX)
+}
+
+/// Checks the light output ratio (LORL) and downward flux fraction (DFF) in the header of a EULUMDAT file against
+/// the values computed from its intensities, returning any that differ by more than the tolerance (in percentage points).
+pub fn check_eulumdat_light_output_ratios(ldt: &EulumdatFile, tolerance: f64) -> Vec<HeaderDiscrepancy> {
+    let ratios = eulumdat_light_output_ratios(ldt);
+    [
+        ("Light output ratio (LORL)", ldt.light_output_ratio_luminaire(), "light output ratio", ratios.lor),
+        ("Downward flux fraction (DFF)", ldt.downward_flux_fraction(), "downward flux fraction", ratios.dff),
+    ]
+    .into_iter()
+    .filter(|(_, header, _, computed)| (header - computed).abs() > tolerance)
+    .map(|(field, header, compared_with, computed)| HeaderDiscrepancy {
+        field: field.to_string(),
+        header,
+        compared_with: compared_with.to_string(),
+        computed,
+    })
+    .collect()
+}
+
+/// Returns the ballast factor of an IES file, taking a missing (non-positive) value as 1.
+fn ies_ballast_factor(ies: &IesFile) -> f64 {
+    if ies.ballast_factor() > 0.0 {
+        ies.ballast_factor()
+    } else {
+        1.0
+    }
+}
+
+/// Computes the light output ratios of an IES file, taking into account the candela multiplying factor and the ballast
+/// factor, which scales both the lamp flux and the intensities.
+/// Returns `None` for absolute photometry (where the lumens per lamp are given as -1), as there is no lamp flux to compare against.
+pub fn ies_light_output_ratios(ies: &IesFile) -> Option<LightOutputRatios> {
+    let ballast_factor = ies_ballast_factor(ies);
+    let lamp_flux = ies.n_lamps() as f64 * ies.lumens_per_lamp() * ballast_factor;
+    if lamp_flux <= 0.0 {
+        return None;
+    }
+
+    let web: PhotometricWeb = ies.clone().into();
+    let web = scale_photometric_web(&web, ies.candela_multiplying_factor() * ballast_factor);
+    Some(LightOutputRatios::new(&web, lamp_flux))
+}
+
+/// Checks the rated lamp flux in the header of an IES file (the number of lamps times the lumens per lamp, times the
+/// ballast factor) against the flux integrated from its intensities. IES files do not state a light output ratio, so
+/// a discrepancy is returned if the implied ratio exceeds 100% by more than the tolerance (in percentage points), or
+/// falls below `MIN_PLAUSIBLE_LOR`. Either almost always indicates that the candela multiplying factor or the lamp
+/// lumens are incorrect.
+pub fn check_ies_light_output_ratios(ies: &IesFile, tolerance: f64) -> Vec<HeaderDiscrepancy> {
+    ies_light_output_ratios(ies)
+        .filter(|ratios| ratios.lor > 100.0 + tolerance || ratios.lor < MIN_PLAUSIBLE_LOR)
+        .map(|ratios| HeaderDiscrepancy {
+            field: "Lamp flux (lamps x lumens per lamp x ballast factor)".to_string(),
+            header: ratios.lamp_flux,
+            compared_with: format!("luminaire flux (a light output ratio of {:.1}%)", ratios.lor),
+            computed: ratios.luminaire_flux,
+        })
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::eulumdat::EulumdatSymmetry;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    /// A rotationally symmetric EULUMDAT file with a constant intensity (in cd/klm) in the lower hemisphere,
+    /// and half of that intensity in the upper hemisphere. The sample on the horizon takes the average of the two.
+    fn eulumdat_file(intensity: f64) -> EulumdatFile {
+        let mut ldt = EulumdatFile::new();
+        ldt.set_n_cplanes(1_usize);
+        ldt.set_c_angles(vec![0.0]);
+        ldt.set_g_angles((0..=180).map(|ang| ang as f64).collect::<Vec<f64>>());
+        ldt.set_n_luminous_intensities_per_cplane(181_usize);
+        ldt.set_intensities(
+            (0..=180)
+                .map(|ang| match ang {
+                    0..=89 => intensity,
+                    90 => 0.75 * intensity,
+                    _ => intensity / 2.0,
+                })
+                .collect::<Vec<f64>>(),
+        );
+        ldt.set_symmetry(EulumdatSymmetry::AboutVerticalAxis);
+        ldt
+    }
+
+    #[test]
+    fn test_light_output_ratios() {
+        // 100 cd/klm over the lower hemisphere is 200 pi lm per klm, and 50 cd/klm above is 100 pi lm per klm.
+        let ratios = eulumdat_light_output_ratios(&eulumdat_file(100.0));
+        assert_abs_diff_eq!(ratios.dlor, 20.0 * PI, epsilon = 0.2);
+        assert_abs_diff_eq!(ratios.ulor, 10.0 * PI, epsilon = 0.2);
+        assert_abs_diff_eq!(ratios.lor, 30.0 * PI, epsilon = 0.2);
+        assert_abs_diff_eq!(ratios.dff, 200.0 / 3.0, epsilon = 0.2);
+    }
+
+    #[test]
+    fn test_check_eulumdat_header() {
+        let mut ldt = eulumdat_file(100.0);
+        ldt.set_light_output_ratio_luminaire(30.0 * PI);
+        ldt.set_downward_flux_fraction(200.0 / 3.0);
+        assert!(check_eulumdat_light_output_ratios(&ldt, 0.5).is_empty());
+
+        // A header that was not updated after the intensities were scaled.
+        ldt.set_light_output_ratio_luminaire(80.0);
+        let issues = check_eulumdat_light_output_ratios(&ldt, 0.5);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].header, 80.0);
+    }
+
+    /// An IES file for a single 1000 lm lamp, with a constant 100 cd (before the candela multiplying factor) over the
+    /// lower hemisphere, which is a luminaire flux of about 200 pi lm.
+    fn ies_file(multiplier: f64, ballast_factor: f64) -> IesFile {
+        let angles: Vec<String> = (0..=18).map(|i| (5 * i).to_string()).collect();
+        let ies_string = format!(
+            "IESNA:LM-63-2002\nTILT=NONE\n1 1000 {} 19 1 1 2 0.0 0.0 0.0\n{} 1 100\n{}\n0.0\n{}\n",
+            multiplier,
+            ballast_factor,
+            angles.join(" "),
+            vec!["100.0"; 19].join(" ")
+        );
+        let mut ies = IesFile::new();
+        ies.parse(&ies_string).unwrap();
+        ies
+    }
+
+    #[test]
+    fn test_ies_light_output_ratios() {
+        let ratios = ies_light_output_ratios(&ies_file(1.0, 1.0)).unwrap();
+        assert_eq!(ratios.lamp_flux, 1000.0);
+        // With 5 degree steps, the quadrature error of the integral of sin over the hemisphere is about h^2 / 12.
+        assert_abs_diff_eq!(ratios.luminaire_flux, 200.0 * PI, epsilon = 1E-3 * 200.0 * PI);
+
+        // The file stops at 90 degrees, so none of its light may be attributed to the upper hemisphere.
+        assert_eq!(ratios.upward_flux, 0.0);
+        assert_eq!(ratios.ulor, 0.0);
+        assert_eq!(ratios.dff, 100.0);
+
+        // The ballast factor scales both the lamp flux and the intensities, leaving the ratios unchanged.
+        let with_ballast = ies_light_output_ratios(&ies_file(1.0, 0.9)).unwrap();
+        assert_abs_diff_eq!(with_ballast.lamp_flux, 900.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(with_ballast.luminaire_flux, 0.9 * ratios.luminaire_flux, epsilon = 1E-9);
+        assert_abs_diff_eq!(with_ballast.lor, ratios.lor, epsilon = 1E-9);
+    }
+
+    #[test]
+    fn test_check_ies_header() {
+        assert!(check_ies_light_output_ratios(&ies_file(1.0, 0.9), 1.0).is_empty());
+
+        // A candela multiplying factor that makes the luminaire emit more than its lamps.
+        let issues = check_ies_light_output_ratios(&ies_file(2.0, 0.9), 1.0);
+        assert_eq!(issues.len(), 1);
+        assert_abs_diff_eq!(issues[0].header, 900.0, epsilon = 1E-9);
+        let ratios = ies_light_output_ratios(&ies_file(1.0, 0.9)).unwrap();
+        assert_abs_diff_eq!(issues[0].computed, 2.0 * ratios.luminaire_flux, epsilon = 1E-9);
+
+        // One that makes the luminaire implausibly inefficient.
+        let issues = check_ies_light_output_ratios(&ies_file(0.2, 1.0), 1.0);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].to_string().starts_with("Lamp flux"));
+        assert!(issues[0].computed < 0.2 * issues[0].header);
+    }
+}
@@ -0,0 +1,354 @@
+//! The luminous area of a luminaire as seen from a given direction, and the luminance distribution that follows from it.
+//!
+//! Dimensions are in metres. The length of a luminous area is measured along the C0-C180 (x) axis, and the width along the
+//! C90-C270 (y) axis, following the photometric horizontal of IES files.
+
+use crate::{
+    io::{
+        eulumdat::EulumdatFile,
+        ies::{lum_opening::IesLuminousOpening, IesFile, LuminousOpeningUnits},
+    },
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, Vector3},
+};
+use std::f64::consts::PI;
+
+/// The number of metres in a foot, used to convert IES luminous openings given in feet.
+const METRES_PER_FOOT: f64 = 0.3048;
+
+/// The gamma angles (in degrees) of the average luminance table used for glare limits.
+pub const STANDARD_LUMINANCE_GAMMAS: [f64; 9] = [45.0, 50.0, 55.0, 60.0, 65.0, 70.0, 75.0, 80.0, 85.0];
+
+/// The C-planes (in degrees) of the average luminance table used for glare limits.
+pub const STANDARD_LUMINANCE_CPLANES: [f64; 8] = [0.0, 45.0, 90.0, 135.0, 180.0, 225.0, 270.0, 315.0];
+
+/// The projected area of a luminous area, as seen from a given direction.
+pub trait ProjectedArea {
+    /// Returns the area (in square metres) of the luminous area projected onto a plane perpendicular to the direction,
+    /// where the direction points from the luminaire towards the observer in the luminaire coordinate system.
+    fn projected_area(&self, direction: &Vector3) -> f64;
+}
+
+/// A horizontal luminous area facing downwards, either rectangular or elliptical, as described by EULUMDAT files and
+/// the footprint of IES luminous openings. The area may also have luminous sides facing the C0, C90, C180 and C270
+/// planes, as given in EULUMDAT files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuminousArea {
+    /// The length of the area along the C0-C180 axis, in metres.
+    pub length: f64,
+    /// The width of the area along the C90-C270 axis, in metres.
+    pub width: f64,
+    /// Whether the area is elliptical (or circular), rather than rectangular.
+    pub elliptical: bool,
+    /// The heights of the luminous sides facing the C0, C90, C180 and C270 planes, in metres, if the sides are luminous.
+    pub side_heights: Option<[f64; 4]>,
+}
+
+impl LuminousArea {
+    /// Creates a flat luminous area, without luminous sides.
+    pub fn flat(length: f64, width: f64, elliptical: bool) -> Self {
+        Self {
+            length,
+            width,
+            elliptical,
+            side_heights: None,
+        }
+    }
+
+    /// The area of the downward facing luminous surface, in square metres.
+    pub fn area(&self) -> f64 {
+        if self.elliptical {
+            PI / 4.0 * self.length * self.width
+        } else {
+            self.length * self.width
+        }
+    }
+
+    /// The height of the luminous side facing the given C-plane (in radians), interpolated between the four given heights.
+    fn height_at(heights: &[f64; 4], c_angle: f64) -> f64 {
+        let quadrant = c_angle.rem_euclid(2.0 * PI) / (PI / 2.0);
+        let lower = (quadrant.floor() as usize).min(3);
+        let frac = quadrant - lower as f64;
+        (1.0 - frac) * heights[lower] + frac * heights[(lower + 1) % 4]
+    }
+}
+
+impl ProjectedArea for LuminousArea {
+    fn projected_area(&self, direction: &Vector3) -> f64 {
+        let unit = direction.normalise();
+        let base = self.area() * (-unit.z).max(0.0);
+        let sides = match self.side_heights {
+            None => 0.0,
+            Some(heights) if self.elliptical => {
+                let silhouette = ellipse_silhouette(self.length / 2.0, self.width / 2.0, unit.x, unit.y);
+                if silhouette > 0.0 {
+                    silhouette * Self::height_at(&heights, unit.y.atan2(unit.x))
+                } else {
+                    0.0
+                }
+            }
+            Some(heights) => {
+                self.width * (heights[0] * unit.x.max(0.0) + heights[2] * (-unit.x).max(0.0))
+                    + self.length * (heights[1] * unit.y.max(0.0) + heights[3] * (-unit.y).max(0.0))
+            }
+        };
+        base + sides
+    }
+}
+
+impl From<&EulumdatFile> for LuminousArea {
+    /// Takes the luminous area and the heights of its sides from a EULUMDAT file, given in mm.
+    /// A width of zero indicates a circular area, whose diameter is given as the length.
+    fn from(ldt: &EulumdatFile) -> Self {
+        let length = ldt.luminous_area_length() / 1000.0;
+        let width = ldt.luminous_area_width() / 1000.0;
+        let heights = [
+            ldt.luminous_area_height_c0() / 1000.0,
+            ldt.luminous_area_height_c90() / 1000.0,
+            ldt.luminous_area_height_c180() / 1000.0,
+            ldt.luminous_area_height_c270() / 1000.0,
+        ];
+        let side_heights = heights.iter().any(|height| *height > 0.0).then_some(heights);
+        if width == 0.0 {
+            Self { length, width: length, elliptical: true, side_heights }
+        } else {
+            Self { length, width, elliptical: false, side_heights }
+        }
+    }
+}
+
+impl From<&IesFile> for LuminousArea {
+    /// Takes the horizontal footprint of the luminous opening of an IES file.
+    fn from(ies: &IesFile) -> Self {
+        let (length, width, elliptical) = match ies_luminous_opening(ies) {
+            IesLuminousOpening::Point => (0.0, 0.0, false),
+            IesLuminousOpening::Rectangular { width, length }
+            | IesLuminousOpening::RectanguarLuminousSides { width, length, .. }
+            | IesLuminousOpening::HorizontalEllipsoidalCylinderAlong { width, length, .. }
+            | IesLuminousOpening::HorizontalEllipsoidalCylinderPerpendicular { width, length, .. } => {
+                (length, width, false)
+            }
+            IesLuminousOpening::HorizontalCylinderAlong { diameter, length } => (length, diameter, false),
+            IesLuminousOpening::HorizontalCylinderPerpendicular { width, diameter } => (diameter, width, false),
+            IesLuminousOpening::Ellipse { width, length }
+            | IesLuminousOpening::VerticalEllipsoidalCylinder { width, length, .. }
+            | IesLuminousOpening::EllipsoidalSpheroid { width, length, .. } => (length, width, true),
+            IesLuminousOpening::Circular { diameter }
+            | IesLuminousOpening::VerticalCylinder { diameter, .. }
+            | IesLuminousOpening::Sphere { diameter } => (diameter, diameter, true),
+            IesLuminousOpening::VerticalCircle { .. } | IesLuminousOpening::VerticalEllipse { .. } => (0.0, 0.0, false),
+        };
+        Self::flat(length, width, elliptical)
+    }
+}
+
+/// Returns the luminous opening of an IES file, with its dimensions converted to metres.
+pub fn ies_luminous_opening(ies: &IesFile) -> IesLuminousOpening {
+    let scale = match ies.luminous_opening_units() {
+        LuminousOpeningUnits::Feet => METRES_PER_FOOT,
+        LuminousOpeningUnits::Meters => 1.0,
+    };
+    ies.get_luminous_opening().scaled(scale)
+}
+
+/// The projected length of an ellipse with semi-axes a and b, seen along a direction with components u and v
+/// along the axes of a and b respectively.
+fn ellipse_silhouette(a: f64, b: f64, u: f64, v: f64) -> f64 {
+    2.0 * (a * a * v * v + b * b * u * u).sqrt()
+}
+
+impl ProjectedArea for IesLuminousOpening {
+    /// Solid openings (cylinders, spheres and ellipsoids) are treated as closed luminous bodies, seen as their silhouette.
+    /// Flat openings emit from one face only: downwards for horizontal openings and towards C0 for vertical openings.
+    fn projected_area(&self, direction: &Vector3) -> f64 {
+        let Vector3 { x, y, z } = direction.normalise();
+        match *self {
+            Self::Point => 0.0,
+            Self::Rectangular { width, length } => width * length * (-z).max(0.0),
+            Self::RectanguarLuminousSides { width, length, height } => {
+                width * length * (-z).max(0.0) + width * height * x.abs() + length * height * y.abs()
+            }
+            Self::Circular { diameter } => PI / 4.0 * diameter * diameter * (-z).max(0.0),
+            Self::Ellipse { width, length } => PI / 4.0 * width * length * (-z).max(0.0),
+            Self::VerticalCylinder { diameter, height } => {
+                PI / 4.0 * diameter * diameter * z.abs() + ellipse_silhouette(diameter / 2.0, diameter / 2.0, x, y) * height
+            }
+            Self::VerticalEllipsoidalCylinder { width, length, height } => {
+                PI / 4.0 * width * length * z.abs() + ellipse_silhouette(length / 2.0, width / 2.0, x, y) * height
+            }
+            Self::Sphere { diameter } => PI / 4.0 * diameter * diameter,
+            Self::EllipsoidalSpheroid { width, length, height } => {
+                let (a, b, c) = (length / 2.0, width / 2.0, height / 2.0);
+                PI * ((b * c * x).powi(2) + (a * c * y).powi(2) + (a * b * z).powi(2)).sqrt()
+            }
+            Self::HorizontalCylinderAlong { diameter, length } => {
+                PI / 4.0 * diameter * diameter * x.abs() + ellipse_silhouette(diameter / 2.0, diameter / 2.0, y, z) * length
+            }
+            Self::HorizontalEllipsoidalCylinderAlong { width, length, height } => {
+                PI / 4.0 * width * height * x.abs() + ellipse_silhouette(width / 2.0, height / 2.0, y, z) * length
+            }
+            Self::HorizontalCylinderPerpendicular { width, diameter } => {
+                PI / 4.0 * diameter * diameter * y.abs() + ellipse_silhouette(diameter / 2.0, diameter / 2.0, x, z) * width
+            }
+            Self::HorizontalEllipsoidalCylinderPerpendicular { width, length, height } => {
+                PI / 4.0 * length * height * y.abs() + ellipse_silhouette(length / 2.0, height / 2.0, x, z) * width
+            }
+            Self::VerticalCircle { diameter } => PI / 4.0 * diameter * diameter * x.max(0.0),
+            Self::VerticalEllipse { width, height } => PI / 4.0 * width * height * x.max(0.0),
+        }
+    }
+}
+
+/// Computes the average luminance (in cd/m2) of the luminous area seen from the given C-plane and gamma angles (in radians),
+/// the intensity divided by the projected area. Returns zero where the luminous area is not visible.
+pub fn luminance(web: &PhotometricWeb, area: &dyn ProjectedArea, c_angle: f64, gamma: f64) -> f64 {
+    let direction = Vector3::from_c_gamma(c_angle, gamma);
+    let projected_area = area.projected_area(&direction);
+    if projected_area > 0.0 {
+        web.intensity_towards(&direction) / projected_area
+    } else {
+        0.0
+    }
+}
+
+/// A table of the average luminance of a luminaire over a range of C-planes and gamma angles, as used for glare limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuminanceTable {
+    /// The C-planes of the table, in degrees.
+    pub c_planes: Vec<f64>,
+    /// The gamma angles of the table, in degrees.
+    pub gammas: Vec<f64>,
+    /// The luminances (in cd/m2), indexed by [C-plane][gamma].
+    pub values: Vec<Vec<f64>>,
+}
+
+impl LuminanceTable {
+    /// Computes the luminance table at the standard angles, gamma 45 -> 85 degrees in C-planes every 45 degrees.
+    pub fn new(web: &PhotometricWeb, area: &dyn ProjectedArea) -> Self {
+        Self::with_angles(web, area, &STANDARD_LUMINANCE_CPLANES, &STANDARD_LUMINANCE_GAMMAS)
+    }
+
+    /// Computes the luminance table at the given C-planes and gamma angles, in degrees.
+    pub fn with_angles(web: &PhotometricWeb, area: &dyn ProjectedArea, c_planes: &[f64], gammas: &[f64]) -> Self {
+        Self {
+            c_planes: c_planes.to_vec(),
+            gammas: gammas.to_vec(),
+            values: c_planes
+                .iter()
+                .map(|c_angle| {
+                    gammas
+                        .iter()
+                        .map(|gamma| luminance(web, area, degrees_to_radians(*c_angle), degrees_to_radians(*gamma)))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// The largest luminance in the table.
+    pub fn max(&self) -> f64 {
+        self.values.iter().flatten().fold(0.0, |max, val| max.max(*val))
+    }
+}
+
+impl std::fmt::Display for LuminanceTable {
+    /// Formats the table as text, with a row for each gamma angle and a column for each C-plane.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>8}", "gamma")?;
+        for c_angle in &self.c_planes {
+            write!(f, "{:>10}", format!("C{}", c_angle))?;
+        }
+        writeln!(f)?;
+        for (igamma, gamma) in self.gammas.iter().enumerate() {
+            write!(f, "{:>8}", gamma)?;
+            for row in &self.values {
+                write!(f, "{:>10.0}", row[igamma])?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::lambertian_web;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_flat_opening() {
+        let opening = IesLuminousOpening::Rectangular { width: 0.5, length: 2.0 };
+        assert_abs_diff_eq!(opening.projected_area(&Vector3::new(0.0, 0.0, -1.0)), 1.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(opening.projected_area(&Vector3::from_c_gamma(0.0, PI / 3.0)), 0.5, epsilon = 1E-12);
+        assert_eq!(opening.projected_area(&Vector3::new(0.0, 0.0, 1.0)), 0.0);
+
+        let opening = IesLuminousOpening::VerticalCircle { diameter: 1.0 };
+        assert_abs_diff_eq!(opening.projected_area(&Vector3::new(1.0, 0.0, 0.0)), PI / 4.0, epsilon = 1E-12);
+        assert_eq!(opening.projected_area(&Vector3::new(-1.0, 0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_solid_openings() {
+        // A sphere looks the same from every direction, and an ellipsoid with equal axes is a sphere.
+        let sphere = IesLuminousOpening::Sphere { diameter: 2.0 };
+        let spheroid = IesLuminousOpening::EllipsoidalSpheroid { width: 2.0, length: 2.0, height: 2.0 };
+        for direction in [Vector3::new(0.0, 0.0, -1.0), Vector3::new(1.0, 2.0, 3.0)] {
+            assert_abs_diff_eq!(sphere.projected_area(&direction), PI, epsilon = 1E-12);
+            assert_abs_diff_eq!(spheroid.projected_area(&direction), PI, epsilon = 1E-12);
+        }
+
+        // A vertical cylinder shows its end from below and a rectangle from the side.
+        let cylinder = IesLuminousOpening::VerticalCylinder { diameter: 1.0, height: 2.0 };
+        assert_abs_diff_eq!(cylinder.projected_area(&Vector3::new(0.0, 0.0, -1.0)), PI / 4.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(cylinder.projected_area(&Vector3::new(0.0, 1.0, 0.0)), 2.0, epsilon = 1E-12);
+
+        // A horizontal cylinder along C0 shows its end from C0 and a rectangle from below and from C90.
+        let cylinder = IesLuminousOpening::HorizontalCylinderAlong { diameter: 1.0, length: 3.0 };
+        assert_abs_diff_eq!(cylinder.projected_area(&Vector3::new(1.0, 0.0, 0.0)), PI / 4.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(cylinder.projected_area(&Vector3::new(0.0, 0.0, -1.0)), 3.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(cylinder.projected_area(&Vector3::new(0.0, 1.0, 0.0)), 3.0, epsilon = 1E-12);
+
+        let scaled = cylinder.scaled(2.0);
+        assert_abs_diff_eq!(scaled.projected_area(&Vector3::new(0.0, 0.0, -1.0)), 12.0, epsilon = 1E-12);
+    }
+
+    #[test]
+    fn test_eulumdat_area() {
+        let area = LuminousArea {
+            side_heights: Some([0.1, 0.2, 0.0, 0.0]),
+            ..LuminousArea::flat(1.0, 0.5, false)
+        };
+        assert_abs_diff_eq!(area.projected_area(&Vector3::new(0.0, 0.0, -1.0)), 0.5, epsilon = 1E-12);
+        assert_abs_diff_eq!(area.projected_area(&Vector3::new(1.0, 0.0, 0.0)), 0.05, epsilon = 1E-12);
+        assert_abs_diff_eq!(area.projected_area(&Vector3::new(0.0, 1.0, 0.0)), 0.2, epsilon = 1E-12);
+        assert_eq!(area.projected_area(&Vector3::new(-1.0, 0.0, 0.0)), 0.0);
+
+        let circular = LuminousArea {
+            side_heights: Some([0.2, 0.2, 0.2, 0.2]),
+            ..LuminousArea::flat(1.0, 1.0, true)
+        };
+        assert_abs_diff_eq!(circular.projected_area(&Vector3::new(0.0, 0.0, -1.0)), PI / 4.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(circular.projected_area(&Vector3::new(1.0, 1.0, 0.0)), 0.2, epsilon = 1E-12);
+
+        // Without luminous sides, the area is only visible from below.
+        let flat = LuminousArea::flat(1.0, 0.5, false);
+        assert_abs_diff_eq!(flat.projected_area(&Vector3::from_c_gamma(0.0, PI / 3.0)), 0.25, epsilon = 1E-12);
+        assert_eq!(flat.projected_area(&Vector3::new(0.0, 1.0, 0.0)), 0.0);
+    }
+
+    /// A Lambertian flat panel has the same luminance in every direction below it.
+    #[test]
+    fn test_luminance_table() {
+        let web = lambertian_web(1000.0);
+        let area = IesLuminousOpening::Rectangular { width: 0.5, length: 0.5 };
+
+        let table = LuminanceTable::new(&web, &area);
+        for val in table.values.iter().flatten() {
+            assert_abs_diff_eq!(*val, 4000.0, epsilon = 1E-6);
+        }
+        assert_abs_diff_eq!(table.max(), 4000.0, epsilon = 1E-6);
+        assert_eq!(luminance(&web, &area, 0.0, 2.0), 0.0);
+        assert_eq!(table.to_string().lines().count(), 10);
+    }
+}
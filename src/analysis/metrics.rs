@@ -0,0 +1,284 @@
+//! Beam metrics of spotlights and downlights, such as the beam and field angles, peak and centre-beam intensities and beam lumens.
+
+use crate::{
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, radians_to_degrees},
+};
+use std::f64::consts::PI;
+
+/// The fraction of the peak intensity that defines the edge of the beam.
+pub const BEAM_FRACTION: f64 = 0.5;
+/// The fraction of the peak intensity that defines the edge of the field.
+pub const FIELD_FRACTION: f64 = 0.1;
+/// The fraction of the peak intensity below which the luminaire is considered to be cut off.
+pub const CUTOFF_FRACTION: f64 = 0.01;
+/// The C-plane pairs (in degrees) over which the beam widths are averaged by default.
+pub const STANDARD_CPLANE_PAIRS: [(f64, f64); 2] = [(0.0, 180.0), (90.0, 270.0)];
+/// The angular step (in degrees) used when integrating the beam and field lumens.
+const INTEGRATION_STEP: f64 = 0.5;
+
+/// The widths of the beam within a pair of opposing C-planes. All angles are full angles, in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeamWidths {
+    /// The pair of C-planes, in degrees.
+    pub cplanes: (f64, f64),
+    /// The full angle over which the intensity is at least 50% of the peak intensity.
+    pub beam_angle: f64,
+    /// The full angle over which the intensity is at least 10% of the peak intensity.
+    pub field_angle: f64,
+    /// The full angle over which the intensity is at least 1% of the peak intensity.
+    pub cutoff_angle: f64,
+}
+
+/// The beam metrics of a Type C web. Angles are given in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeamMetrics {
+    /// The peak intensity of the web.
+    pub peak_intensity: f64,
+    /// The direction of the peak intensity, as a (C, gamma) pair in degrees.
+    pub peak_direction: (f64, f64),
+    /// The centre-beam intensity (centre-beam candlepower), the intensity at the nadir.
+    pub centre_beam_intensity: f64,
+    /// The beam widths in each of the C-plane pairs.
+    pub cplane_pairs: Vec<BeamWidths>,
+    /// The beam angle averaged over the C-plane pairs.
+    pub beam_angle: f64,
+    /// The field angle averaged over the C-plane pairs.
+    pub field_angle: f64,
+    /// The cut-off angle averaged over the C-plane pairs.
+    pub cutoff_angle: f64,
+    /// The flux emitted in directions where the intensity is at least 50% of the peak intensity.
+    pub beam_lumens: f64,
+    /// The flux emitted in directions where the intensity is at least 10% of the peak intensity.
+    pub field_lumens: f64,
+}
+
+impl BeamMetrics {
+    /// Computes the beam metrics of a Type C web, with the beam widths averaged over the C0-C180 and C90-C270 planes.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        Self::with_cplane_pairs(web, &STANDARD_CPLANE_PAIRS)
+    }
+
+    /// Computes the beam metrics of a Type C web, with the beam widths averaged over the given C-plane pairs (in degrees).
+    /// The edges of the beam are found relative to the peak intensity of the whole web, so that the widths of
+    /// asymmetric beams can be compared between the pairs.
+    pub fn with_cplane_pairs(web: &PhotometricWeb, pairs: &[(f64, f64)]) -> Self {
+        let (peak_intensity, peak_direction) = peak_intensity(web);
+        let cplane_pairs: Vec<BeamWidths> = pairs
+            .iter()
+            .map(|(lower, upper)| {
+                let (angles, intensities) = cplane_pair_curve(web, *lower, *upper);
+                let width = |fraction: f64| full_width(&angles, &intensities, fraction * peak_intensity);
+                BeamWidths {
+                    cplanes: (*lower, *upper),
+                    beam_angle: width(BEAM_FRACTION),
+                    field_angle: width(FIELD_FRACTION),
+                    cutoff_angle: width(CUTOFF_FRACTION),
+                }
+            })
+            .collect();
+
+        let average = |func: fn(&BeamWidths) -> f64| {
+            if cplane_pairs.is_empty() {
+                0.0
+            } else {
+                cplane_pairs.iter().map(func).sum::<f64>() / cplane_pairs.len() as f64
+            }
+        };
+
+        Self {
+            peak_intensity,
+            peak_direction,
+            centre_beam_intensity: web.intensity_at(0.0, 0.0),
+            beam_angle: average(|widths| widths.beam_angle),
+            field_angle: average(|widths| widths.field_angle),
+            cutoff_angle: average(|widths| widths.cutoff_angle),
+            cplane_pairs,
+            beam_lumens: flux_above(web, BEAM_FRACTION * peak_intensity),
+            field_lumens: flux_above(web, FIELD_FRACTION * peak_intensity),
+        }
+    }
+}
+
+/// Finds the peak intensity of the web, and its direction as a (C, gamma) pair in degrees.
+fn peak_intensity(web: &PhotometricWeb) -> (f64, (f64, f64)) {
+    web.planes()
+        .iter()
+        .flat_map(|pl| {
+            pl.angles()
+                .iter()
+                .zip(pl.intensities())
+                .map(move |(ang, int)| (*int, (pl.angle_deg(), radians_to_degrees(*ang))))
+        })
+        .fold((0.0, (0.0, 0.0)), |max, val| if val.0 > max.0 { val } else { max })
+}
+
+/// Returns the intensity curve through a pair of C-planes as (angles, intensities), with the angles in radians
+/// running from -pi (in the upper plane) through 0 (the nadir) to pi (in the lower plane).
+/// Each gamma angle of the upper plane is mapped to -gamma, so the zenith of the upper plane is at -pi.
+/// The planes are taken from the web where they are present, and interpolated otherwise.
+fn cplane_pair_curve(web: &PhotometricWeb, lower: f64, upper: f64) -> (Vec<f64>, Vec<f64>) {
+    let find_plane = |angle: f64| web.planes().iter().find(|pl| pl.angle_deg() == angle);
+    match (find_plane(lower), find_plane(upper)) {
+        (Some(lower_plane), Some(upper_plane)) => (
+            upper_plane
+                .angles()
+                .iter()
+                .rev()
+                .map(|ang| -ang)
+                .chain(lower_plane.angles().iter().cloned())
+                .collect(),
+            upper_plane
+                .intensities()
+                .iter()
+                .rev()
+                .chain(lower_plane.intensities())
+                .cloned()
+                .collect(),
+        ),
+        _ => {
+            let gammas: Vec<f64> = web
+                .planes()
+                .first()
+                .map(|pl| pl.angles().to_vec())
+                .unwrap_or_default();
+            let (lower, upper) = (degrees_to_radians(lower), degrees_to_radians(upper));
+            let angles = gammas.iter().rev().map(|ang| -ang).chain(gammas.iter().cloned()).collect();
+            let intensities = gammas
+                .iter()
+                .rev()
+                .map(|ang| web.intensity_at(upper, *ang))
+                .chain(gammas.iter().map(|ang| web.intensity_at(lower, *ang)))
+                .collect();
+            (angles, intensities)
+        }
+    }
+}
+
+/// Finds the full width (in degrees) of the region around the peak of a curve where the intensity is at least the threshold.
+/// The edges are linearly interpolated between the samples either side of the threshold.
+pub(super) fn full_width(angles: &[f64], intensities: &[f64], threshold: f64) -> f64 {
+    let n = angles.len().min(intensities.len());
+    if n == 0 {
+        return 0.0;
+    }
+    let ipeak = (0..n).fold(0, |imax, i| if intensities[i] > intensities[imax] { i } else { imax });
+    if intensities[ipeak] < threshold {
+        return 0.0;
+    }
+
+    let crossing = |inside: usize, outside: usize| {
+        let (int_in, int_out) = (intensities[inside], intensities[outside]);
+        let frac = if int_in > int_out { (int_in - threshold) / (int_in - int_out) } else { 0.0 };
+        angles[inside] + frac * (angles[outside] - angles[inside])
+    };
+    let left = (0..ipeak)
+        .rev()
+        .find(|i| intensities[*i] < threshold)
+        .map_or(angles[0], |i| crossing(i + 1, i));
+    let right = (ipeak + 1..n)
+        .find(|i| intensities[*i] < threshold)
+        .map_or(angles[n - 1], |i| crossing(i - 1, i));
+    radians_to_degrees(right - left)
+}
+
+/// Integrates the flux emitted in the directions where the intensity is at least the threshold.
+fn flux_above(web: &PhotometricWeb, threshold: f64) -> f64 {
+    let step = degrees_to_radians(INTEGRATION_STEP);
+    let n_c = (2.0 * PI / step).round() as usize;
+    let n_gamma = (PI / step).round() as usize;
+    (0..n_c)
+        .flat_map(|ic| (0..n_gamma).map(move |ig| (ic, ig)))
+        .map(|(ic, ig)| {
+            let (c_angle, gamma) = ((ic as f64 + 0.5) * step, (ig as f64 + 0.5) * step);
+            let intensity = web.intensity_at(c_angle, gamma);
+            if intensity >= threshold {
+                intensity * gamma.sin() * step * step
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::{isotropic_web, planar_web};
+    use approx::assert_abs_diff_eq;
+
+    /// A web with a gaussian beam, with a different width in the C0-C180 and C90-C270 planes.
+    /// The width in each plane is given by the standard deviation in degrees.
+    fn gaussian_web(sigma_c0: f64, sigma_c90: f64) -> PhotometricWeb {
+        planar_web(15.0, 1.0, |c_angle, gamma| {
+            let sigma = (sigma_c0 * c_angle.cos()).hypot(sigma_c90 * c_angle.sin());
+            1000.0 * (-0.5 * (gamma.to_degrees() / sigma).powi(2)).exp()
+        })
+    }
+
+    /// The full width of a gaussian at a fraction f of its peak is 2 sigma sqrt(-2 ln f).
+    fn gaussian_width(sigma: f64, fraction: f64) -> f64 {
+        2.0 * sigma * (-2.0 * fraction.ln()).sqrt()
+    }
+
+    #[test]
+    fn test_symmetric_beam() {
+        let metrics = BeamMetrics::new(&gaussian_web(10.0, 10.0));
+        assert_eq!(metrics.peak_intensity, 1000.0);
+        assert_eq!(metrics.peak_direction.1, 0.0);
+        assert_eq!(metrics.centre_beam_intensity, 1000.0);
+        assert_abs_diff_eq!(metrics.beam_angle, gaussian_width(10.0, 0.5), epsilon = 0.1);
+        assert_abs_diff_eq!(metrics.field_angle, gaussian_width(10.0, 0.1), epsilon = 0.2);
+        assert_abs_diff_eq!(metrics.cutoff_angle, gaussian_width(10.0, 0.01), epsilon = 0.3);
+
+        // The flux of a narrow gaussian beam above a fraction f of the peak is approximately 2 pi I sigma^2 (1 - f).
+        let sigma = degrees_to_radians(10.0);
+        assert_abs_diff_eq!(metrics.beam_lumens, 2.0 * PI * 1000.0 * sigma.powi(2) * 0.5, epsilon = 5.0);
+        assert!(metrics.field_lumens > metrics.beam_lumens);
+    }
+
+    #[test]
+    fn test_asymmetric_beam() {
+        let metrics = BeamMetrics::new(&gaussian_web(10.0, 20.0));
+        assert_abs_diff_eq!(metrics.cplane_pairs[0].beam_angle, gaussian_width(10.0, 0.5), epsilon = 0.1);
+        assert_abs_diff_eq!(metrics.cplane_pairs[1].beam_angle, gaussian_width(20.0, 0.5), epsilon = 0.1);
+        assert_abs_diff_eq!(metrics.beam_angle, gaussian_width(15.0, 0.5), epsilon = 0.1);
+
+        // A pair of planes between those measured is interpolated.
+        let metrics = BeamMetrics::with_cplane_pairs(&gaussian_web(10.0, 20.0), &[(40.0, 220.0)]);
+        assert!(metrics.beam_angle > gaussian_width(10.0, 0.5));
+        assert!(metrics.beam_angle < gaussian_width(20.0, 0.5));
+    }
+
+    /// An isotropic source never falls below any fraction of its peak, so its beam, field and cut-off angles all
+    /// cover the full circle through each pair of planes, whether the planes are measured or interpolated.
+    #[test]
+    fn test_isotropic() {
+        for web in [planar_web(15.0, 1.0, |_, _| 100.0), isotropic_web(100.0)] {
+            let metrics = BeamMetrics::new(&web);
+            assert_abs_diff_eq!(metrics.beam_angle, 360.0, epsilon = 1E-9);
+            assert_abs_diff_eq!(metrics.field_angle, 360.0, epsilon = 1E-9);
+            assert_abs_diff_eq!(metrics.cutoff_angle, 360.0, epsilon = 1E-9);
+        }
+    }
+
+    /// A pendant with a cosine downlight, a cosine uplight of a fifth of its peak and 50 cd of diffuse light in all
+    /// directions. The zenith of the upper plane must sit at -pi, so the curve never drops below 1% of the peak.
+    #[test]
+    fn test_pendant_with_uplight() {
+        let web = planar_web(15.0, 1.0, |_, gamma| 50.0 + f64::max(1000.0 * gamma.cos(), -200.0 * gamma.cos()));
+
+        let (angles, intensities) = cplane_pair_curve(&web, 0.0, 180.0);
+        assert_eq!(angles[0], -PI);
+        assert_eq!(angles[angles.len() - 1], PI);
+        assert!(angles.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_abs_diff_eq!(intensities[0], 250.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(intensities[intensities.len() - 1], 250.0, epsilon = 1E-9);
+
+        // The field edge is where 1000 cos(gamma) + 50 falls to 105 cd, and the cut-off (10.5 cd) is never reached.
+        let metrics = BeamMetrics::new(&web);
+        assert_eq!(metrics.peak_intensity, 1050.0);
+        assert_abs_diff_eq!(metrics.field_angle, 2.0 * 0.055_f64.acos().to_degrees(), epsilon = 0.1);
+        assert_abs_diff_eq!(metrics.cutoff_angle, 360.0, epsilon = 1E-9);
+    }
+}
@@ -0,0 +1,18 @@
+//! Analyses of photometric webs, producing the summary values and tables used in photometric reports.
+
+pub mod bug;
+pub mod cie;
+pub mod cone;
+pub mod cu;
+pub mod floodlight;
+pub mod lor;
+pub mod luminance;
+pub mod metrics;
+pub mod outdoor;
+pub mod roadway;
+pub mod ugr;
+pub mod zonal;
+pub use self::{
+    bug::*, cie::*, cone::*, cu::*, floodlight::*, lor::*, luminance::*, metrics::*, outdoor::*, roadway::*, ugr::*,
+    zonal::*,
+};
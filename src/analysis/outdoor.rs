@@ -0,0 +1,241 @@
+//! Classifications of outdoor luminaires limiting obtrusive light and glare: the upward light ratio (ULR), and the
+//! luminous intensity classes G*1 to G*6 and glare index classes D0 to D6 of EN 13201-2 Annex A.
+//!
+//! All of these apply to the luminaire in its installed position. The webs are expected level, and are tilted towards
+//! the front (the C90 plane) by the installed tilt where one is given.
+
+use super::ProjectedArea;
+use crate::{
+    ops::tilt_photometric_web,
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, Vector3},
+};
+
+/// The angular step (in degrees) used when searching for the maximum intensities.
+const SEARCH_STEP: f64 = 0.5;
+
+/// The maximum intensities (in cd/klm) at gamma 70, 80 and 90 degrees and above for each of the classes G*1 to G*6,
+/// and whether the intensities above 95 degrees must be zero.
+pub const INTENSITY_CLASS_LIMITS: [(f64, f64, f64, bool); 6] = [
+    (f64::INFINITY, 200.0, 50.0, false),
+    (f64::INFINITY, 150.0, 30.0, false),
+    (f64::INFINITY, 100.0, 20.0, false),
+    (500.0, 100.0, 10.0, true),
+    (350.0, 100.0, 10.0, true),
+    (350.0, 100.0, 0.0, true),
+];
+
+/// The maximum glare index for each of the classes D1 to D6. Class D0 has no limit.
+pub const GLARE_INDEX_LIMITS: [f64; 6] = [7000.0, 5500.0, 4000.0, 2000.0, 1000.0, 500.0];
+
+/// Computes the upward light ratio (ULR) of a Type C web installed with a tilt (in degrees) towards the front: the
+/// percentage of the luminaire flux emitted above the horizontal.
+/// A level luminaire is integrated on its own grid, as resampling it would spread the light at the horizon upwards.
+pub fn upward_light_ratio(web: &PhotometricWeb, tilt: f64) -> f64 {
+    let tilted;
+    let installed = if tilt == 0.0 {
+        web
+    } else {
+        tilted = tilt_photometric_web(web, degrees_to_radians(tilt));
+        &tilted
+    };
+    let upward = installed.integrate_intensity_between(std::f64::consts::FRAC_PI_2, f64::INFINITY);
+    let total = installed.integrate_intensity_between(f64::NEG_INFINITY, f64::INFINITY);
+    if total > 0.0 {
+        100.0 * upward / total
+    } else {
+        0.0
+    }
+}
+
+/// Returns the maximum intensity at or above the gamma angle (in degrees), over all C-planes.
+fn max_intensity_above(web: &PhotometricWeb, lower: f64) -> f64 {
+    let n_c = (360.0 / SEARCH_STEP).round() as usize;
+    let n_gamma = ((180.0 - lower) / SEARCH_STEP).round() as usize;
+    (0..n_c)
+        .flat_map(|ic| (0..=n_gamma).map(move |ig| (ic, ig)))
+        .map(|(ic, ig)| {
+            let c_angle = degrees_to_radians(ic as f64 * SEARCH_STEP);
+            let gamma = degrees_to_radians(lower + ig as f64 * SEARCH_STEP);
+            web.intensity_at(c_angle, gamma)
+        })
+        .fold(0.0, f64::max)
+}
+
+/// The luminous intensity class (G*) of a luminaire, with the intensities used to reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntensityClassification {
+    /// The maximum intensity at gamma 70 degrees and above, in candela per 1000 luminaire lumens.
+    pub above_70: f64,
+    /// The maximum intensity at gamma 80 degrees and above, in candela per 1000 luminaire lumens.
+    pub above_80: f64,
+    /// The maximum intensity at gamma 90 degrees and above, in candela per 1000 luminaire lumens.
+    pub above_90: f64,
+    /// The maximum intensity above gamma 95 degrees, in candela per 1000 luminaire lumens.
+    pub above_95: f64,
+    /// The class, from 1 to 6 for G*1 to G*6, or `None` if the luminaire does not meet G*1.
+    pub class: Option<u8>,
+}
+
+impl IntensityClassification {
+    /// Classifies a Type C web for the luminaire installed level.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        let flux = web.integrate_intensity_between(f64::NEG_INFINITY, f64::INFINITY);
+        let scale = if flux > 0.0 { 1000.0 / flux } else { 0.0 };
+        let above_70 = scale * max_intensity_above(web, 70.0);
+        let above_80 = scale * max_intensity_above(web, 80.0);
+        let above_90 = scale * max_intensity_above(web, 90.0);
+        let above_95 = scale * max_intensity_above(web, 95.0 + SEARCH_STEP);
+        let class = (0..6)
+            .rev()
+            .find(|i| {
+                let (at_70, at_80, at_90, zero_above_95) = INTENSITY_CLASS_LIMITS[*i];
+                above_70 <= at_70 && above_80 <= at_80 && above_90 <= at_90 && (!zero_above_95 || above_95 <= 0.0)
+            })
+            .map(|i| i as u8 + 1);
+        Self {
+            above_70,
+            above_80,
+            above_90,
+            above_95,
+            class,
+        }
+    }
+
+    /// Classifies a Type C web for the luminaire installed with a tilt (in degrees) towards the front.
+    pub fn with_tilt(web: &PhotometricWeb, tilt: f64) -> Self {
+        Self::new(&tilt_photometric_web(web, degrees_to_radians(tilt)))
+    }
+}
+
+impl std::fmt::Display for IntensityClassification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.class {
+            Some(class) => write!(f, "G*{}", class),
+            None => write!(f, "none"),
+        }
+    }
+}
+
+/// The glare index class (D) of a luminaire, with the values used to reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlareIndexClassification {
+    /// The maximum intensity at gamma 85 degrees, in candela.
+    pub intensity: f64,
+    /// The C-plane (in degrees) of the maximum intensity.
+    pub c_angle: f64,
+    /// The projected luminous area perpendicular to the direction of the maximum intensity, in square metres.
+    pub projected_area: f64,
+    /// The glare index, I / sqrt(A).
+    pub glare_index: f64,
+    /// The class, from 0 to 6 for D0 to D6.
+    pub class: u8,
+}
+
+impl GlareIndexClassification {
+    /// Classifies a Type C web, with intensities in candela, for the luminaire installed level.
+    pub fn new(web: &PhotometricWeb, area: &dyn ProjectedArea) -> Self {
+        let gamma = degrees_to_radians(85.0);
+        let n_c = (360.0 / SEARCH_STEP).round() as usize;
+        let (c_angle, intensity) = (0..n_c)
+            .map(|ic| {
+                let c_angle = ic as f64 * SEARCH_STEP;
+                (c_angle, web.intensity_at(degrees_to_radians(c_angle), gamma))
+            })
+            .fold((0.0, 0.0), |max, val| if val.1 > max.1 { val } else { max });
+        let projected_area = area.projected_area(&Vector3::from_c_gamma(degrees_to_radians(c_angle), gamma));
+        let glare_index = if projected_area > 0.0 {
+            intensity / projected_area.sqrt()
+        } else {
+            f64::INFINITY
+        };
+        let class = (0..6)
+            .rev()
+            .find(|i| glare_index <= GLARE_INDEX_LIMITS[*i])
+            .map_or(0, |i| i as u8 + 1);
+        Self {
+            intensity,
+            c_angle,
+            projected_area,
+            glare_index,
+            class,
+        }
+    }
+
+    /// Classifies a Type C web, with intensities in candela, for the luminaire installed with a tilt (in degrees)
+    /// towards the front. The luminous area is given for the luminaire level, and is not tilted.
+    pub fn with_tilt(web: &PhotometricWeb, area: &dyn ProjectedArea, tilt: f64) -> Self {
+        Self::new(&tilt_photometric_web(web, degrees_to_radians(tilt)), area)
+    }
+}
+
+impl std::fmt::Display for GlareIndexClassification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "D{}", self.class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::LuminousArea, io::ies::IesFile, photweb::test_webs::rotational_web};
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_upward_light_ratio() {
+        assert_abs_diff_eq!(upward_light_ratio(&rotational_web(0.25, |_| 10.0), 0.0), 50.0, epsilon = 0.1);
+
+        let downlight = rotational_web(0.25, |gamma| gamma.cos().max(0.0));
+        assert_abs_diff_eq!(upward_light_ratio(&downlight, 0.0), 0.0, epsilon = 1E-6);
+        assert!(upward_light_ratio(&downlight, 20.0) > 1.0);
+    }
+
+    /// An IES file for a downlight is only measured from 0 to 90 degrees, and must have no upward light when level.
+    #[test]
+    fn test_upward_light_ratio_ies_downlight() {
+        let angles: Vec<String> = (0..=18).map(|i| (5 * i).to_string()).collect();
+        let ies_string = format!(
+            "IESNA:LM-63-2002\nTILT=NONE\n1 1000 1.0 19 1 1 2 0.0 0.0 0.0\n1.0 1 100\n{}\n0.0\n{}\n",
+            angles.join(" "),
+            vec!["100.0"; 19].join(" ")
+        );
+        let mut ies = IesFile::new();
+        ies.parse(&ies_string).unwrap();
+        let web: PhotometricWeb = ies.into();
+
+        assert_eq!(upward_light_ratio(&web, 0.0), 0.0);
+        assert!(upward_light_ratio(&web, 10.0) > 0.0);
+    }
+
+    /// A Lambertian downlight of flux pi I0 has 1000 cos(gamma) / pi cd/klm, giving 108.9 cd/klm at 70 degrees and
+    /// 55.3 cd/klm at 80 degrees, while a uniform lower hemisphere has 159.2 cd/klm up to the horizontal.
+    #[test]
+    fn test_intensity_class() {
+        let lambertian = rotational_web(0.25, |gamma| if gamma < PI / 2.0 { gamma.cos() } else { 0.0 });
+        let classification = IntensityClassification::new(&lambertian);
+        assert_abs_diff_eq!(classification.above_70, 1000.0 * 70_f64.to_radians().cos() / PI, epsilon = 0.5);
+        assert_abs_diff_eq!(classification.above_80, 1000.0 * 80_f64.to_radians().cos() / PI, epsilon = 0.5);
+        assert_eq!(classification.to_string(), "G*6");
+
+        let hemisphere = rotational_web(0.25, |gamma| if gamma < PI / 2.0 { 1.0 } else { 0.0 });
+        let classification = IntensityClassification::new(&hemisphere);
+        assert_abs_diff_eq!(classification.above_80, 1000.0 / (2.0 * PI), epsilon = 0.5);
+        assert_eq!(classification.class, Some(1));
+
+        assert_eq!(IntensityClassification::new(&rotational_web(0.25, |_| 1.0)).class, None);
+        assert_eq!(IntensityClassification::with_tilt(&lambertian, 20.0).class, None);
+    }
+
+    /// A flat area of 0.1 x 0.1 m seen at 85 degrees has a projected area of 0.01 cos(85) = 8.72E-4 m2, so a uniform
+    /// 100 cd gives a glare index of 3387.
+    #[test]
+    fn test_glare_index_class() {
+        let area = LuminousArea::flat(0.1, 0.1, false);
+        let classification = GlareIndexClassification::new(&rotational_web(0.25, |_| 100.0), &area);
+        assert_abs_diff_eq!(classification.glare_index, 3386.9, epsilon = 0.5);
+        assert_eq!(classification.to_string(), "D3");
+        assert_eq!(GlareIndexClassification::new(&rotational_web(0.25, |_| 10.0), &area).class, 6);
+        assert_eq!(GlareIndexClassification::new(&rotational_web(0.25, |_| 1000.0), &area).class, 0);
+    }
+}
@@ -0,0 +1,332 @@
+//! The IES roadway classifications of a luminaire (IES RP-8): the lateral distribution type, the vertical throw and the
+//! legacy cutoff classes.
+//!
+//! As in the BUG rating, the street side of the luminaire is the half-space containing the C90 plane, and the C0 plane
+//! runs along the road. Positions on the ground are given in multiples of the mounting height (MH), with x along the road
+//! and y across it towards the street side.
+
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+
+/// The upper bounds (in MH) of the longitudinal distance of the maximum intensity for the short, medium and long throws.
+pub const VERTICAL_THROW_LIMITS: [f64; 3] = [2.25, 3.75, 6.0];
+
+/// The lower bound (in MH) of the longitudinal range of the short throw, within which the half-maximum trace is examined.
+const SHORT_THROW_LOWER: f64 = 1.0;
+
+/// The upper bounds (in MH) of the street side extent of the half-maximum trace for Types I, II and III.
+pub const LATERAL_TYPE_LIMITS: [f64; 3] = [1.0, 1.75, 2.75];
+
+/// The smallest ratio of the minimum to the maximum intensity around the luminaire for the distribution to be taken as
+/// circular (Type V) or square (Type VS).
+const SYMMETRY_RATIO: f64 = 0.9;
+
+/// The angular step (in degrees) used when searching the web.
+const SEARCH_STEP: f64 = 0.5;
+
+/// The vertical throw of a roadway luminaire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalThrow {
+    Short,
+    Medium,
+    Long,
+    /// The maximum intensity lies beyond the long throw range.
+    VeryLong,
+}
+
+/// The lateral distribution type of a roadway luminaire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LateralType {
+    I,
+    II,
+    III,
+    IV,
+    /// Circular symmetry.
+    V,
+    /// Square symmetry.
+    VS,
+}
+
+/// The roadway classification of a luminaire, with the intermediate data used to reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoadwayClassification {
+    /// The maximum intensity in the lower hemisphere.
+    pub max_intensity: f64,
+    /// The direction of the maximum intensity, as a (C, gamma) pair in degrees.
+    pub max_direction: (f64, f64),
+    /// The position on the ground of the maximum intensity, (x, y) in MH.
+    pub max_point: (f64, f64),
+    /// The half-maximum isocandela trace on the ground, (x, y) in MH, taking the outermost crossing in each C-plane.
+    pub half_max_trace: Vec<(f64, f64)>,
+    /// The longitudinal range (in MH) of the vertical throw, within which the street side extent is found.
+    pub longitudinal_range: (f64, f64),
+    /// The furthest street side extent (in MH) of the half-maximum trace within the longitudinal range.
+    pub street_side_extent: f64,
+    /// The largest gamma angle (in degrees) of the half-maximum trace, which bounds the band of gamma angles over which
+    /// the symmetry of the distribution is examined.
+    pub half_max_gamma: f64,
+    /// The smallest ratio of the minimum to the maximum intensity around the luminaire, over the gamma angles up to the
+    /// half-maximum gamma.
+    pub circular_ratio: f64,
+    /// The smallest ratio of the intensities in C-planes 90 degrees apart, over the gamma angles up to the half-maximum
+    /// gamma.
+    pub square_ratio: f64,
+    /// The vertical throw.
+    pub vertical: VerticalThrow,
+    /// The lateral distribution type.
+    pub lateral: LateralType,
+}
+
+impl RoadwayClassification {
+    /// Classifies a Type C web.
+    pub fn new(web: &PhotometricWeb) -> Self {
+        let step = degrees_to_radians(SEARCH_STEP);
+        let n_c = (360.0 / SEARCH_STEP).round() as usize;
+        let n_gamma = (90.0 / SEARCH_STEP).round() as usize;
+        let c_angles: Vec<f64> = (0..n_c).map(|i| i as f64 * step).collect();
+        let gammas: Vec<f64> = (0..n_gamma).map(|i| i as f64 * step).collect();
+        let ground = |c_angle: f64, gamma: f64| (gamma.tan() * c_angle.cos(), gamma.tan() * c_angle.sin());
+
+        let (max_intensity, (max_c, max_gamma)) = c_angles
+            .iter()
+            .flat_map(|c| gammas.iter().map(move |g| (*c, *g)))
+            .map(|(c, g)| (web.intensity_at(c, g), (c, g)))
+            .fold((0.0, (0.0, 0.0)), |max, val| if val.0 > max.0 { val } else { max });
+        let max_point = ground(max_c, max_gamma);
+
+        let half_max_crossings: Vec<(f64, f64)> = c_angles
+            .iter()
+            .filter_map(|c| {
+                gammas
+                    .iter()
+                    .rev()
+                    .find(|g| web.intensity_at(*c, **g) >= 0.5 * max_intensity)
+                    .map(|g| (*c, *g))
+            })
+            .collect();
+        let half_max_trace: Vec<(f64, f64)> = half_max_crossings.iter().map(|(c, g)| ground(*c, *g)).collect();
+        let half_max_gamma = half_max_crossings.iter().map(|(_, g)| *g).fold(max_gamma, f64::max);
+
+        let vertical = match max_point.0.abs() {
+            x if x < VERTICAL_THROW_LIMITS[0] => VerticalThrow::Short,
+            x if x < VERTICAL_THROW_LIMITS[1] => VerticalThrow::Medium,
+            x if x < VERTICAL_THROW_LIMITS[2] => VerticalThrow::Long,
+            _ => VerticalThrow::VeryLong,
+        };
+        let longitudinal_range = match vertical {
+            VerticalThrow::Short => (SHORT_THROW_LOWER, VERTICAL_THROW_LIMITS[0]),
+            VerticalThrow::Medium => (VERTICAL_THROW_LIMITS[0], VERTICAL_THROW_LIMITS[1]),
+            VerticalThrow::Long => (VERTICAL_THROW_LIMITS[1], VERTICAL_THROW_LIMITS[2]),
+            VerticalThrow::VeryLong => (VERTICAL_THROW_LIMITS[2], f64::INFINITY),
+        };
+        // Where the trace does not reach the longitudinal range, as for narrow distributions, the whole trace is used.
+        let extent = |points: &mut dyn Iterator<Item = &(f64, f64)>| points.map(|pt| pt.1).fold(f64::NEG_INFINITY, f64::max);
+        let street_side_extent = match extent(&mut half_max_trace.iter().filter(|pt| {
+            (longitudinal_range.0..=longitudinal_range.1).contains(&pt.0.abs())
+        })) {
+            val if val.is_finite() => val,
+            _ => extent(&mut half_max_trace.iter()).max(0.0),
+        };
+
+        // The ratios are taken over the whole footprint of the half-maximum trace, so that a distribution peaking at the
+        // nadir is only taken as symmetric if it also spreads symmetrically.
+        let ratio = |min: f64, max: f64| if max > 0.0 { min / max } else { 1.0 };
+        let circular_ratio = gammas
+            .iter()
+            .filter(|g| **g <= half_max_gamma)
+            .map(|g| {
+                let (min, max) = c_angles
+                    .iter()
+                    .map(|c| web.intensity_at(*c, *g))
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), val| (min.min(val), max.max(val)));
+                ratio(min, max)
+            })
+            .fold(1.0, f64::min);
+        let square_ratio = gammas
+            .iter()
+            .filter(|g| **g <= half_max_gamma)
+            .flat_map(|g| c_angles.iter().map(move |c| (*c, *g)))
+            .map(|(c, g)| {
+                let (lower, upper) = (web.intensity_at(c, g), web.intensity_at(c + degrees_to_radians(90.0), g));
+                ratio(lower.min(upper), lower.max(upper))
+            })
+            .fold(1.0, f64::min);
+
+        let lateral = if circular_ratio >= SYMMETRY_RATIO {
+            LateralType::V
+        } else if square_ratio >= SYMMETRY_RATIO {
+            LateralType::VS
+        } else {
+            match street_side_extent {
+                y if y < LATERAL_TYPE_LIMITS[0] => LateralType::I,
+                y if y < LATERAL_TYPE_LIMITS[1] => LateralType::II,
+                y if y < LATERAL_TYPE_LIMITS[2] => LateralType::III,
+                _ => LateralType::IV,
+            }
+        };
+
+        Self {
+            max_intensity,
+            max_direction: (max_c.to_degrees(), max_gamma.to_degrees()),
+            max_point,
+            half_max_trace,
+            longitudinal_range,
+            street_side_extent,
+            half_max_gamma: half_max_gamma.to_degrees(),
+            circular_ratio,
+            square_ratio,
+            vertical,
+            lateral,
+        }
+    }
+}
+
+/// The legacy IES cutoff classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CutoffClass {
+    FullCutoff,
+    Cutoff,
+    SemiCutoff,
+    NonCutoff,
+}
+
+/// The cutoff classification of a luminaire, with the intensities used to reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutoffClassification {
+    /// The maximum intensity at or above the horizontal, in candela per 1000 lamp lumens.
+    pub above_horizontal: f64,
+    /// The maximum intensity between gamma 80 and 90 degrees, in candela per 1000 lamp lumens.
+    pub above_80: f64,
+    /// The cutoff class.
+    pub class: CutoffClass,
+}
+
+impl CutoffClassification {
+    /// Classifies a Type C web, given the total flux of the lamps in the same units as the web. The limits are, per
+    /// 1000 lamp lumens: full cutoff, 0 cd at or above the horizontal and 100 cd above 80 degrees; cutoff, 25 cd and
+    /// 100 cd; semi-cutoff, 50 cd and 200 cd.
+    pub fn new(web: &PhotometricWeb, lamp_flux: f64) -> Self {
+        let scale = if lamp_flux > 0.0 { 1000.0 / lamp_flux } else { 0.0 };
+        let max_between = |lower: f64, upper: f64| {
+            let n_c = (360.0 / SEARCH_STEP).round() as usize;
+            let n_gamma = ((upper - lower) / SEARCH_STEP).round() as usize;
+            (0..n_c)
+                .flat_map(|ic| (0..=n_gamma).map(move |ig| (ic, ig)))
+                .map(|(ic, ig)| {
+                    let c_angle = degrees_to_radians(ic as f64 * SEARCH_STEP);
+                    let gamma = degrees_to_radians(lower + ig as f64 * SEARCH_STEP);
+                    scale * web.intensity_at(c_angle, gamma)
+                })
+                .fold(0.0, f64::max)
+        };
+        let above_horizontal = max_between(90.0, 180.0);
+        let above_80 = max_between(80.0, 90.0);
+        let class = if above_horizontal <= 0.0 && above_80 <= 100.0 {
+            CutoffClass::FullCutoff
+        } else if above_horizontal <= 25.0 && above_80 <= 100.0 {
+            CutoffClass::Cutoff
+        } else if above_horizontal <= 50.0 && above_80 <= 200.0 {
+            CutoffClass::SemiCutoff
+        } else {
+            CutoffClass::NonCutoff
+        };
+        Self {
+            above_horizontal,
+            above_80,
+            class,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        photweb::test_webs::{isotropic_web, lambertian_web, planar_web, rotational_web},
+        util::geom::Vector3,
+    };
+
+    /// A web with a gaussian lobe of 1000 cd around the direction (C, gamma) in degrees, with a width sigma in degrees.
+    fn lobe_web(c_peak: f64, gamma_peak: f64, sigma: f64) -> PhotometricWeb {
+        let peak = Vector3::from_c_gamma(degrees_to_radians(c_peak), degrees_to_radians(gamma_peak));
+        planar_web(5.0, 1.0, |c_angle, gamma| {
+            let theta = Vector3::from_c_gamma(c_angle, gamma).dot(&peak).clamp(-1.0, 1.0).acos().to_degrees();
+            1000.0 * (-0.5 * (theta / sigma).powi(2)).exp()
+        })
+    }
+
+    /// A lobe of width sigma reaches half of its peak 1.18 sigma away, so the street side extent is tan(gamma + 1.18 sigma).
+    #[test]
+    fn test_lateral_types() {
+        let class = RoadwayClassification::new(&lobe_web(90.0, 50.0, 5.0));
+        assert_eq!(class.vertical, VerticalThrow::Short);
+        assert_eq!(class.lateral, LateralType::II);
+        assert!((class.max_direction.1 - 50.0).abs() <= 1.0);
+
+        assert_eq!(RoadwayClassification::new(&lobe_web(90.0, 60.0, 4.0)).lateral, LateralType::III);
+        assert_eq!(RoadwayClassification::new(&lobe_web(90.0, 70.0, 3.0)).lateral, LateralType::IV);
+        assert_eq!(RoadwayClassification::new(&lobe_web(90.0, 30.0, 3.0)).lateral, LateralType::I);
+    }
+
+    /// The maximum at gamma 70 degrees in the C30 plane lands 2.38 MH along the road, giving a medium throw.
+    #[test]
+    fn test_vertical_throw() {
+        let class = RoadwayClassification::new(&lobe_web(30.0, 70.0, 3.0));
+        assert_eq!(class.vertical, VerticalThrow::Medium);
+        assert_eq!(class.longitudinal_range, (2.25, 3.75));
+        assert!(!class.half_max_trace.is_empty());
+    }
+
+    #[test]
+    fn test_symmetric_types() {
+        assert_eq!(RoadwayClassification::new(&lambertian_web(1000.0)).lateral, LateralType::V);
+
+        // Four lobes at 90 degree intervals have square symmetry.
+        let lobes = [0.0, 90.0, 180.0, 270.0].map(|c| lobe_web(c, 45.0, 10.0));
+        let web = crate::ops::add_photometric_webs(
+            &crate::ops::add_photometric_webs(&lobes[0], &lobes[1]).unwrap(),
+            &crate::ops::add_photometric_webs(&lobes[2], &lobes[3]).unwrap(),
+        )
+        .unwrap();
+        let class = RoadwayClassification::new(&web);
+        assert!(class.circular_ratio < SYMMETRY_RATIO);
+        assert_eq!(class.lateral, LateralType::VS);
+    }
+
+    #[test]
+    fn test_cutoff() {
+        let web = rotational_web(1.0, |gamma| {
+            if gamma < degrees_to_radians(90.0) {
+                1000.0 * gamma.cos().powi(4)
+            } else {
+                0.0
+            }
+        });
+        let cutoff = CutoffClassification::new(&web, 1000.0);
+        assert_eq!(cutoff.class, CutoffClass::FullCutoff);
+        assert_eq!(cutoff.above_horizontal, 0.0);
+
+        let web = isotropic_web(30.0);
+        assert_eq!(CutoffClassification::new(&web, 1000.0).class, CutoffClass::SemiCutoff);
+        assert_eq!(CutoffClassification::new(&web, 500.0).class, CutoffClass::NonCutoff);
+    }
+
+    /// A distribution peaking at the nadir, narrow on the house side and wide on the street side, is not symmetric even
+    /// though its maximum is on the axis.
+    #[test]
+    fn test_asymmetric_nadir_peak() {
+        let web = planar_web(5.0, 1.0, |c_angle, gamma| {
+            let sigma = if c_angle < degrees_to_radians(180.0) { 60.0 } else { 10.0 };
+            if gamma < degrees_to_radians(90.0) {
+                1000.0 * (-0.5 * (gamma.to_degrees() / sigma).powi(2)).exp()
+            } else {
+                0.0
+            }
+        });
+        let class = RoadwayClassification::new(&web);
+        assert_eq!(class.max_direction.1, 0.0);
+        assert!(class.half_max_gamma > 60.0);
+        assert!(class.circular_ratio < SYMMETRY_RATIO && class.square_ratio < SYMMETRY_RATIO);
+        assert!(class.street_side_extent > LATERAL_TYPE_LIMITS[1]);
+        assert_eq!(class.lateral, LateralType::III);
+    }
+}
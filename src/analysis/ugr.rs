@@ -0,0 +1,402 @@
+//! The Unified Glare Rating (UGR) of a luminaire, using the tabular method of CIE 117 and CIE 190.
+//!
+//! The standard table gives the UGR for rooms of X by Y, in multiples of the height H of the luminaires above the eye,
+//! with the luminaires arranged in a regular array at a spacing of 0.25 H. The observer sits at the middle of one wall,
+//! looking horizontally along the Y dimension of the room. When viewed crosswise the line of sight is along the C0-C180
+//! axis of the luminaires, and when viewed endwise it is along the C90-C270 axis.
+//!
+//! The background luminance is found from the indirect illuminance of the walls, solving the interreflections between the
+//! ceiling, walls and floor of the room. As both the direct glare and the background luminance scale in the same way with
+//! the room size, the table is independent of the absolute value of H.
+
+use super::{cu::solve3, ProjectedArea};
+use crate::{photweb::PhotometricWeb, util::geom::Vector3};
+use std::f64::consts::PI;
+
+/// The room dimensions (X, Y) of the standard UGR table, in multiples of H.
+pub const STANDARD_ROOM_DIMENSIONS: [(f64, f64); 19] = [
+    (2.0, 2.0),
+    (2.0, 3.0),
+    (2.0, 4.0),
+    (2.0, 6.0),
+    (2.0, 8.0),
+    (2.0, 12.0),
+    (4.0, 2.0),
+    (4.0, 3.0),
+    (4.0, 4.0),
+    (4.0, 6.0),
+    (4.0, 8.0),
+    (4.0, 12.0),
+    (8.0, 4.0),
+    (8.0, 6.0),
+    (8.0, 8.0),
+    (8.0, 12.0),
+    (12.0, 4.0),
+    (12.0, 6.0),
+    (12.0, 8.0),
+];
+
+/// The (ceiling, wall, floor) reflectances of the standard UGR table.
+pub const STANDARD_UGR_REFLECTANCES: [(f64, f64, f64); 5] = [
+    (0.7, 0.5, 0.2),
+    (0.7, 0.3, 0.2),
+    (0.5, 0.5, 0.2),
+    (0.5, 0.3, 0.2),
+    (0.3, 0.3, 0.2),
+];
+
+/// The spacing of the luminaires in the standard UGR table, in multiples of H.
+pub const STANDARD_SPACING: f64 = 0.25;
+
+/// The luminaire spacings (in multiples of H) for which the variation of the UGR with the observer position is given.
+pub const STANDARD_SPACING_VARIATIONS: [f64; 3] = [1.0, 1.5, 2.0];
+
+/// The number of observer positions along each axis, within one luminaire spacing, used to find the variation of the UGR.
+const N_OBSERVER_OFFSETS: usize = 4;
+
+/// The direction of the line of sight of the observer, relative to the axes of the luminaires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewingDirection {
+    /// Looking along the C0-C180 axis of the luminaires, across the length of linear luminaires.
+    Crosswise,
+    /// Looking along the C90-C270 axis of the luminaires.
+    Endwise,
+}
+
+/// A room used in the tabular method, with dimensions in multiples of H.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UgrRoom {
+    /// The width of the room, across the line of sight.
+    pub x: f64,
+    /// The length of the room, along the line of sight.
+    pub y: f64,
+    /// The (ceiling, wall, floor) reflectances of the room.
+    pub reflectances: (f64, f64, f64),
+    /// The spacing of the luminaires.
+    pub spacing: f64,
+    /// The offset of the array of luminaires, relative to the standard arrangement centred on the observer, across and along the line of sight.
+    pub offset: (f64, f64),
+}
+
+impl UgrRoom {
+    /// A room of the standard table, with the luminaires at the standard spacing.
+    pub fn new(x: f64, y: f64, reflectances: (f64, f64, f64)) -> Self {
+        Self {
+            x,
+            y,
+            reflectances,
+            spacing: STANDARD_SPACING,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// The positions (across, along) of the luminaires in the room, relative to the observer at (x / 2, 0).
+    fn luminaire_positions(&self) -> Vec<(f64, f64)> {
+        let axis = |length: f64, offset: f64| {
+            let n = (length / self.spacing).round().max(1.0) as usize;
+            let start = 0.5 * (length - (n - 1) as f64 * self.spacing) + offset.rem_euclid(self.spacing);
+            (0..n)
+                .map(|i| start + i as f64 * self.spacing)
+                .map(|pos| if pos > length { pos - n as f64 * self.spacing } else { pos })
+                .collect::<Vec<f64>>()
+        };
+        let xs = axis(self.x, self.offset.0);
+        let ys = axis(self.y, self.offset.1);
+        xs.iter()
+            .flat_map(|x| ys.iter().map(move |y| (x - 0.5 * self.x, *y)))
+            .collect()
+    }
+}
+
+/// Computes the UGR for an observer in a room, given the web of each luminaire and its luminous area.
+pub fn unified_glare_rating(
+    web: &PhotometricWeb,
+    area: &dyn ProjectedArea,
+    room: &UgrRoom,
+    viewing: ViewingDirection,
+) -> f64 {
+    let positions = room.luminaire_positions();
+    let background = background_luminance(web, room, positions.len());
+
+    // Sum L^2 omega / p^2 over the luminaires, where L = I / A_p and omega = A_p / r^2, giving I^2 / (A_p r^2 p^2).
+    let sum: f64 = positions
+        .iter()
+        .map(|(across, along)| {
+            // The direction from the luminaire towards the observer, in the room and then the luminaire coordinate systems.
+            let (dx, dy, dz) = (-across, -along, -1.0);
+            let direction = match viewing {
+                ViewingDirection::Crosswise => Vector3::new(dy, -dx, dz),
+                ViewingDirection::Endwise => Vector3::new(dx, dy, dz),
+            };
+            let projected_area = area.projected_area(&direction);
+            if projected_area <= 0.0 {
+                return 0.0;
+            }
+            let intensity = web.intensity_towards(&direction);
+            let distance_sq = direction.dot(&direction);
+            intensity.powi(2) / (projected_area * distance_sq * position_index(*across, *along, 1.0).powi(2))
+        })
+        .sum();
+
+    if background <= 0.0 || sum <= 0.0 {
+        return 0.0;
+    }
+    8.0 * (0.25 / background * sum).log10()
+}
+
+/// The Guth position index of a source at a lateral offset T, a distance R along the line of sight and a height H above the eye.
+pub fn position_index(lateral: f64, along: f64, height: f64) -> f64 {
+    let tau = lateral.abs().atan2(height).to_degrees();
+    let distance = (lateral * lateral + along * along + height * height).sqrt();
+    let sigma = (along / distance).clamp(-1.0, 1.0).acos().to_degrees();
+    ((35.2 - 0.31889 * tau - 1.22 * (-2.0 * tau / 9.0).exp()) * 1E-3 * sigma
+        + (21.0 + 0.26667 * tau - 0.002963 * tau * tau) * 1E-5 * sigma * sigma)
+        .exp()
+}
+
+/// The background luminance at the eye, taken as the indirect illuminance of the walls divided by pi.
+/// The room is treated as an enclosure of the ceiling (in the plane of the luminaires), the walls and the floor (at eye level),
+/// with the luminaires spread uniformly across the ceiling.
+fn background_luminance(web: &PhotometricWeb, room: &UgrRoom, n_luminaires: usize) -> f64 {
+    let (x, y) = (room.x, room.y);
+    let (rho_c, rho_w, rho_f) = room.reflectances;
+    let n = n_luminaires as f64;
+
+    let down = web.integrate_intensity_between(f64::NEG_INFINITY, PI / 2.0);
+    let up = web.integrate_intensity_between(PI / 2.0, f64::INFINITY);
+    let to_floor = direct_fraction_to_floor(web, x, y) * down;
+    let direct = [n * up, n * (down - to_floor), n * to_floor];
+
+    let (area_floor, area_walls) = (x * y, 2.0 * (x + y));
+    let f_cf = parallel_rectangles_form_factor(x, y);
+    let f_wc = (1.0 - f_cf) * area_floor / area_walls;
+    let form_factors = [
+        [0.0, 1.0 - f_cf, f_cf],
+        [f_wc, 1.0 - 2.0 * f_wc, f_wc],
+        [f_cf, 1.0 - f_cf, 0.0],
+    ];
+    let rho = [rho_c, rho_w, rho_f];
+    let mut matrix = [[0.0; 3]; 3];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, val) in row.iter_mut().enumerate() {
+            *val = if i == j { 1.0 } else { 0.0 } - rho[i] * form_factors[j][i];
+        }
+    }
+    let exitance = solve3(matrix, [rho[0] * direct[0], rho[1] * direct[1], rho[2] * direct[2]]);
+
+    let indirect_to_walls: f64 = (0..3).map(|i| form_factors[i][1] * exitance[i]).sum();
+    indirect_to_walls / area_walls / PI
+}
+
+/// The fraction of the downward flux of luminaires spread uniformly across the ceiling that falls directly onto the floor
+/// of a room of X by Y, at a unit distance below.
+fn direct_fraction_to_floor(web: &PhotometricWeb, x: f64, y: f64) -> f64 {
+    let step = 1.0_f64.to_radians();
+    let n_c = (2.0 * PI / step).round() as usize;
+    let n_gamma = (PI / 2.0 / step).round() as usize;
+    let (direct, total) = (0..n_c)
+        .flat_map(|ic| (0..n_gamma).map(move |ig| (ic, ig)))
+        .fold((0.0, 0.0), |(direct, total), (ic, ig)| {
+            let (c_angle, gamma) = ((ic as f64 + 0.5) * step, (ig as f64 + 0.5) * step);
+            let flux = web.intensity_at(c_angle, gamma) * gamma.sin();
+            let (dx, dy) = (gamma.tan() * c_angle.cos(), gamma.tan() * c_angle.sin());
+            let probability = (1.0 - dx.abs() / x).max(0.0) * (1.0 - dy.abs() / y).max(0.0);
+            (direct + flux * probability, total + flux)
+        });
+    if total > 0.0 {
+        direct / total
+    } else {
+        0.0
+    }
+}
+
+/// The form factor between two directly opposed parallel rectangles of X by Y, at a unit distance apart.
+fn parallel_rectangles_form_factor(x: f64, y: f64) -> f64 {
+    let (x2, y2) = (x * x, y * y);
+    2.0 / (PI * x * y)
+        * (0.5 * ((1.0 + x2) * (1.0 + y2) / (1.0 + x2 + y2)).ln()
+            + x * (1.0 + y2).sqrt() * (x / (1.0 + y2).sqrt()).atan()
+            + y * (1.0 + x2).sqrt() * (y / (1.0 + x2).sqrt()).atan()
+            - x * x.atan()
+            - y * y.atan())
+}
+
+/// The range of UGR values as the observer moves within one luminaire spacing, relative to the value of the standard table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UgrVariation {
+    /// The spacing of the luminaires, in multiples of H.
+    pub spacing: f64,
+    /// The largest increase and largest decrease of the UGR when viewed crosswise.
+    pub crosswise: (f64, f64),
+    /// The largest increase and largest decrease of the UGR when viewed endwise.
+    pub endwise: (f64, f64),
+}
+
+/// The standard UGR table of a luminaire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UgrTable {
+    /// The room dimensions (X, Y) of each row of the table, in multiples of H.
+    pub rooms: Vec<(f64, f64)>,
+    /// The (ceiling, wall, floor) reflectances of each column of the table.
+    pub reflectances: Vec<(f64, f64, f64)>,
+    /// The UGR values when viewed crosswise, indexed by [room][reflectances].
+    pub crosswise: Vec<Vec<f64>>,
+    /// The UGR values when viewed endwise, indexed by [room][reflectances].
+    pub endwise: Vec<Vec<f64>>,
+    /// The variation of the UGR with the observer position for wider luminaire spacings, in a room of 4H by 8H.
+    pub variations: Vec<UgrVariation>,
+}
+
+impl UgrTable {
+    /// Computes the standard 19 x 10 UGR table, and the variations with the observer position in the 4H by 8H room.
+    pub fn new(web: &PhotometricWeb, area: &dyn ProjectedArea) -> Self {
+        let table = |viewing: ViewingDirection| {
+            STANDARD_ROOM_DIMENSIONS
+                .iter()
+                .map(|(x, y)| {
+                    STANDARD_UGR_REFLECTANCES
+                        .iter()
+                        .map(|refl| unified_glare_rating(web, area, &UgrRoom::new(*x, *y, *refl), viewing))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let variation = |spacing: f64, viewing: ViewingDirection| {
+            let reference = unified_glare_rating(web, area, &UgrRoom::new(4.0, 8.0, STANDARD_UGR_REFLECTANCES[0]), viewing);
+            let offsets: Vec<f64> = (0..N_OBSERVER_OFFSETS)
+                .map(|i| spacing * i as f64 / N_OBSERVER_OFFSETS as f64)
+                .collect();
+            offsets
+                .iter()
+                .flat_map(|across| offsets.iter().map(move |along| (*across, *along)))
+                .map(|offset| {
+                    let room = UgrRoom {
+                        spacing,
+                        offset,
+                        ..UgrRoom::new(4.0, 8.0, STANDARD_UGR_REFLECTANCES[0])
+                    };
+                    unified_glare_rating(web, area, &room, viewing) - reference
+                })
+                .fold((0.0, 0.0), |(max, min): (f64, f64), diff| (max.max(diff), min.min(diff)))
+        };
+
+        Self {
+            rooms: STANDARD_ROOM_DIMENSIONS.to_vec(),
+            reflectances: STANDARD_UGR_REFLECTANCES.to_vec(),
+            crosswise: table(ViewingDirection::Crosswise),
+            endwise: table(ViewingDirection::Endwise),
+            variations: STANDARD_SPACING_VARIATIONS
+                .iter()
+                .map(|spacing| UgrVariation {
+                    spacing: *spacing,
+                    crosswise: variation(*spacing, ViewingDirection::Crosswise),
+                    endwise: variation(*spacing, ViewingDirection::Endwise),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for UgrTable {
+    /// Formats the table as text in the usual layout, with the crosswise and endwise values side by side.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reflectance_row = |f: &mut std::fmt::Formatter<'_>, label: &str, func: fn(&(f64, f64, f64)) -> f64| {
+            write!(f, "{:>12}", label)?;
+            for _ in 0..2 {
+                for refl in &self.reflectances {
+                    write!(f, "{:>6.0}", 100.0 * func(refl))?;
+                }
+            }
+            writeln!(f)
+        };
+        reflectance_row(f, "Ceiling", |refl| refl.0)?;
+        reflectance_row(f, "Walls", |refl| refl.1)?;
+        reflectance_row(f, "Floor", |refl| refl.2)?;
+        writeln!(
+            f,
+            "{:>12}{:^width$}{:^width$}",
+            "X      Y",
+            "Crosswise",
+            "Endwise",
+            width = 6 * self.reflectances.len()
+        )?;
+        for (irow, (x, y)) in self.rooms.iter().enumerate() {
+            write!(f, "{:>4}H {:>5}H", x, y)?;
+            for val in self.crosswise[irow].iter().chain(&self.endwise[irow]) {
+                write!(f, "{:>6.1}", val)?;
+            }
+            writeln!(f)?;
+        }
+        for var in &self.variations {
+            writeln!(
+                f,
+                "S = {:.1}H {:>+6.1} /{:>+5.1} {:>+6.1} /{:>+5.1}",
+                var.spacing, var.crosswise.0, var.crosswise.1, var.endwise.0, var.endwise.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::LuminousArea, ops::scale_photometric_web, photweb::test_webs::lambertian_web};
+    use approx::assert_abs_diff_eq;
+
+    fn area() -> LuminousArea {
+        LuminousArea::flat(0.6, 0.6, false)
+    }
+
+    #[test]
+    fn test_position_index() {
+        // A source on the line of sight has a position index of 1, which increases away from the line of sight.
+        assert_abs_diff_eq!(position_index(0.0, 1.0, 0.0), 1.0, epsilon = 1E-9);
+        assert!(position_index(0.0, 1.0, 1.0) > 1.0);
+        assert!(position_index(2.0, 1.0, 1.0) > position_index(0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_form_factor() {
+        // Two unit squares at a unit distance have a form factor of 0.1998.
+        assert_abs_diff_eq!(parallel_rectangles_form_factor(1.0, 1.0), 0.1998, epsilon = 1E-4);
+    }
+
+    /// The glare scales with the square of the flux of the luminaires, while the background luminance scales linearly,
+    /// so scaling the flux by a factor of 10 should raise the UGR by 8 log10(10) = 8.
+    #[test]
+    fn test_ugr_scaling() {
+        let room = UgrRoom::new(4.0, 8.0, STANDARD_UGR_REFLECTANCES[0]);
+        let ugr = unified_glare_rating(&lambertian_web(1000.0), &area(), &room, ViewingDirection::Crosswise);
+        let scaled = unified_glare_rating(&scale_photometric_web(&lambertian_web(1000.0), 10.0), &area(), &room, ViewingDirection::Crosswise);
+        assert_abs_diff_eq!(scaled - ugr, 8.0, epsilon = 1E-9);
+    }
+
+    #[test]
+    fn test_ugr_table() {
+        let table = UgrTable::new(&lambertian_web(1000.0), &area());
+        assert_eq!(table.crosswise.len(), 19);
+        assert_eq!(table.crosswise[0].len(), 5);
+
+        // A rotationally symmetric luminaire has the same UGR crosswise and endwise.
+        for (cross, end) in table.crosswise.iter().zip(&table.endwise) {
+            for (cross_val, end_val) in cross.iter().zip(end) {
+                assert_abs_diff_eq!(cross_val, end_val, epsilon = 1E-6);
+            }
+        }
+
+        // More reflective rooms have a brighter background, and so less glare.
+        for row in &table.crosswise {
+            assert!(row[0] < row[1]);
+            assert!(row[2] < row[3]);
+        }
+
+        // Larger rooms contain more luminaires in the field of view, and so more glare.
+        assert!(table.crosswise[10][0] > table.crosswise[0][0]);
+
+        let variation = &table.variations[0];
+        assert!(variation.crosswise.0 >= 0.0 && variation.crosswise.1 <= 0.0);
+        assert_eq!(table.to_string().lines().count(), 4 + 19 + 3);
+    }
+}
@@ -0,0 +1,130 @@
+//! The zonal lumen summary of a luminaire, breaking the total flux down into zones of gamma angle.
+
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+
+/// The standard zone boundaries (in degrees) used in photometric reports, giving 10 degree zones from 0 -> 180 degrees.
+pub const STANDARD_ZONE_BOUNDARIES: [f64; 19] = [
+    0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0, 110.0, 120.0, 130.0, 140.0,
+    150.0, 160.0, 170.0, 180.0,
+];
+
+/// The standard aggregate zones (in degrees) used in photometric reports.
+pub const STANDARD_AGGREGATE_ZONES: [(f64, f64); 6] = [
+    (0.0, 30.0),
+    (0.0, 40.0),
+    (0.0, 60.0),
+    (0.0, 90.0),
+    (90.0, 180.0),
+    (0.0, 180.0),
+];
+
+/// The flux emitted within a single zone of gamma angles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZonalFlux {
+    /// The lower gamma angle of the zone, in degrees.
+    pub lower: f64,
+    /// The upper gamma angle of the zone, in degrees.
+    pub upper: f64,
+    /// The flux emitted within the zone, in the units of the web multiplied by steradians (lumens for a web in candela).
+    pub flux: f64,
+    /// The flux in the zone as a percentage of the lamp flux, if a lamp flux was provided.
+    pub percent_lamp: Option<f64>,
+    /// The flux in the zone as a percentage of the total luminaire flux.
+    pub percent_luminaire: f64,
+}
+
+/// The zonal lumen summary of a luminaire, as used in photometric reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZonalLumenSummary {
+    /// The flux in each of the zones between consecutive zone boundaries.
+    pub zones: Vec<ZonalFlux>,
+    /// The flux in each of the aggregate zones, such as 0 -> 90 degrees for the downward flux.
+    pub aggregates: Vec<ZonalFlux>,
+    /// The total flux emitted by the luminaire.
+    pub luminaire_flux: f64,
+    /// The total flux of the lamps, if provided.
+    pub lamp_flux: Option<f64>,
+}
+
+impl ZonalLumenSummary {
+    /// Computes the standard zonal lumen summary of a Type C web, with 10 degree zones and the standard aggregates.
+    /// The lamp flux is optional, and is used to express the zonal flux as a percentage of the lamp flux.
+    pub fn new(web: &PhotometricWeb, lamp_flux: Option<f64>) -> Self {
+        Self::with_zones(web, &STANDARD_ZONE_BOUNDARIES, &STANDARD_AGGREGATE_ZONES, lamp_flux)
+    }
+
+    /// Computes the zonal lumen summary of a Type C web with the given zone boundaries and aggregate zones (in degrees).
+    /// The zone boundaries should be given in increasing order of gamma angle.
+    pub fn with_zones(
+        web: &PhotometricWeb,
+        boundaries: &[f64],
+        aggregates: &[(f64, f64)],
+        lamp_flux: Option<f64>,
+    ) -> Self {
+        let luminaire_flux = web.total_intensity();
+        let zonal_flux = |lower: f64, upper: f64| {
+            let flux = web.integrate_intensity_between(degrees_to_radians(lower), degrees_to_radians(upper));
+            ZonalFlux {
+                lower,
+                upper,
+                flux,
+                percent_lamp: lamp_flux.filter(|lamp| *lamp > 0.0).map(|lamp| 100.0 * flux / lamp),
+                percent_luminaire: if luminaire_flux > 0.0 { 100.0 * flux / luminaire_flux } else { 0.0 },
+            }
+        };
+
+        Self {
+            zones: boundaries.windows(2).map(|bounds| zonal_flux(bounds[0], bounds[1])).collect(),
+            aggregates: aggregates.iter().map(|(lower, upper)| zonal_flux(*lower, *upper)).collect(),
+            luminaire_flux,
+            lamp_flux,
+        }
+    }
+
+    /// Returns the aggregate zone with the given bounds (in degrees), if it was computed.
+    pub fn aggregate(&self, lower: f64, upper: f64) -> Option<&ZonalFlux> {
+        self.aggregates
+            .iter()
+            .find(|zone| zone.lower == lower && zone.upper == upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::planar_web;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    /// An isotropic source of 1 cd, with planes every 15 degrees and gamma angles every degree.
+    fn isotropic_web() -> PhotometricWeb {
+        planar_web(15.0, 1.0, |_, _| 1.0)
+    }
+
+    /// The flux of an isotropic source in a zone is 2 pi (cos(lower) - cos(upper)).
+    #[test]
+    fn test_isotropic_zones() {
+        let summary = ZonalLumenSummary::new(&isotropic_web(), Some(8.0 * PI));
+        assert_eq!(summary.zones.len(), 18);
+        for zone in &summary.zones {
+            let expected = 2.0 * PI * (degrees_to_radians(zone.lower).cos() - degrees_to_radians(zone.upper).cos());
+            assert_abs_diff_eq!(zone.flux, expected, epsilon = expected * 1E-3 + 1E-4);
+        }
+
+        let total: f64 = summary.zones.iter().map(|zone| zone.flux).sum();
+        assert_abs_diff_eq!(total, summary.luminaire_flux, epsilon = 1E-9);
+
+        let down = summary.aggregate(0.0, 90.0).unwrap();
+        assert_abs_diff_eq!(down.percent_luminaire, 50.0, epsilon = 0.1);
+        assert_abs_diff_eq!(down.percent_lamp.unwrap(), 25.0, epsilon = 0.1);
+    }
+
+    #[test]
+    fn test_custom_zones() {
+        let summary = ZonalLumenSummary::with_zones(&isotropic_web(), &[0.0, 60.0, 180.0], &[(0.0, 180.0)], None);
+        assert_eq!(summary.zones.len(), 2);
+        assert_abs_diff_eq!(summary.zones[0].flux, PI, epsilon = 1E-2);
+        assert_eq!(summary.zones[0].percent_lamp, None);
+        assert_abs_diff_eq!(summary.aggregates[0].percent_luminaire, 100.0, epsilon = 1E-9);
+    }
+}
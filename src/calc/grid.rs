@@ -0,0 +1,172 @@
+//! Point-by-point calculations of the illuminance on a horizontal grid, with the usual summary values.
+
+use super::{horizontal_illuminance, LuminairePose};
+use crate::{photweb::PhotometricWeb, util::geom::Vector3};
+
+/// A rectangular grid of calculation points on a horizontal plane, such as a work plane.
+/// The points are placed at the centres of the cells of the grid, as in EN 12464-1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalculationGrid {
+    /// The (x, y) position of the corner of the grid with the lowest coordinates, in metres.
+    pub origin: (f64, f64),
+    /// The extent of the grid along the x-axis, in metres.
+    pub length: f64,
+    /// The extent of the grid along the y-axis, in metres.
+    pub width: f64,
+    /// The number of points along the x-axis.
+    pub n_x: usize,
+    /// The number of points along the y-axis.
+    pub n_y: usize,
+    /// The height of the plane of the grid, in metres.
+    pub height: f64,
+}
+
+impl CalculationGrid {
+    /// A grid covering the given area, with the given number of points along each axis.
+    pub fn new(origin: (f64, f64), length: f64, width: f64, n_x: usize, n_y: usize, height: f64) -> Self {
+        Self {
+            origin,
+            length,
+            width,
+            n_x,
+            n_y,
+            height,
+        }
+    }
+
+    /// Returns the position of the point at the given indices along the x and y axes.
+    pub fn point(&self, ix: usize, iy: usize) -> Vector3 {
+        Vector3::new(
+            self.origin.0 + (ix as f64 + 0.5) * self.length / self.n_x as f64,
+            self.origin.1 + (iy as f64 + 0.5) * self.width / self.n_y as f64,
+            self.height,
+        )
+    }
+
+    /// Returns the positions of all of the points in the grid, indexed by [x][y].
+    pub fn points(&self) -> Vec<Vec<Vector3>> {
+        (0..self.n_x)
+            .map(|ix| (0..self.n_y).map(|iy| self.point(ix, iy)).collect())
+            .collect()
+    }
+}
+
+/// The illuminance at each point of a calculation grid, in lux when the web is in candela.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IlluminanceGrid {
+    /// The grid of calculation points.
+    pub grid: CalculationGrid,
+    /// The illuminance at each point, indexed by [x][y].
+    pub values: Vec<Vec<f64>>,
+}
+
+impl IlluminanceGrid {
+    /// Computes the direct horizontal illuminance on the grid from a single luminaire.
+    pub fn new(web: &PhotometricWeb, pose: &LuminairePose, grid: &CalculationGrid) -> Self {
+        Self {
+            values: grid
+                .points()
+                .iter()
+                .map(|row| row.iter().map(|point| horizontal_illuminance(web, pose, point)).collect())
+                .collect(),
+            grid: grid.clone(),
+        }
+    }
+
+    /// Computes the direct horizontal illuminance on the floor beneath a single luminaire pointing straight down at the
+    /// given mounting height, over a square footprint of the given half-width centred on the luminaire.
+    pub fn footprint(web: &PhotometricWeb, mounting_height: f64, half_width: f64, n_points: usize) -> Self {
+        let pose = LuminairePose::new(Vector3::new(0.0, 0.0, mounting_height));
+        let side = 2.0 * half_width;
+        let grid = CalculationGrid::new((-half_width, -half_width), side, side, n_points, n_points, 0.0);
+        Self::new(web, &pose, &grid)
+    }
+
+    /// The smallest illuminance on the grid.
+    pub fn min(&self) -> f64 {
+        self.values.iter().flatten().cloned().fold(f64::INFINITY, f64::min)
+    }
+
+    /// The largest illuminance on the grid.
+    pub fn max(&self) -> f64 {
+        self.values.iter().flatten().cloned().fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// The average illuminance on the grid.
+    pub fn average(&self) -> f64 {
+        let n_points = self.grid.n_x * self.grid.n_y;
+        if n_points == 0 {
+            return 0.0;
+        }
+        self.values.iter().flatten().sum::<f64>() / n_points as f64
+    }
+
+    /// The overall uniformity U0, the ratio of the minimum to the average illuminance.
+    pub fn u0(&self) -> f64 {
+        let average = self.average();
+        if average > 0.0 {
+            self.min() / average
+        } else {
+            0.0
+        }
+    }
+
+    /// The diversity Ud, the ratio of the minimum to the maximum illuminance.
+    pub fn ud(&self) -> f64 {
+        let max = self.max();
+        if max > 0.0 {
+            self.min() / max
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{photweb::test_webs::rotational_web, util::geom::Rotation};
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    /// An isotropic source of 1000 cd gives E = I cos^3(gamma) / h^2 on a horizontal plane.
+    #[test]
+    fn test_isotropic_grid() {
+        let web = rotational_web(0.25, |_| 1000.0);
+        let pose = LuminairePose::new(Vector3::new(0.0, 0.0, 2.0));
+        let grid = CalculationGrid::new((-2.0, -2.0), 4.0, 4.0, 4, 4, 0.0);
+        let result = IlluminanceGrid::new(&web, &pose, &grid);
+
+        let expected = |point: &Vector3| {
+            let cos_gamma = 2.0 / point.x.hypot(point.y).hypot(2.0);
+            1000.0 * cos_gamma.powi(3) / 4.0
+        };
+        for (row, points) in result.values.iter().zip(grid.points()) {
+            for (val, point) in row.iter().zip(&points) {
+                assert_abs_diff_eq!(*val, expected(point), epsilon = 1E-9);
+            }
+        }
+
+        assert_abs_diff_eq!(result.max(), expected(&Vector3::new(0.5, 0.5, 0.0)), epsilon = 1E-9);
+        assert_abs_diff_eq!(result.min(), expected(&Vector3::new(1.5, 1.5, 0.0)), epsilon = 1E-9);
+        assert_abs_diff_eq!(result.ud(), result.min() / result.max(), epsilon = 1E-12);
+        assert!(result.u0() > result.ud() && result.u0() < 1.0);
+    }
+
+    /// A luminaire tilted away from the grid lights it less, and nothing reaches a grid above the luminaire.
+    #[test]
+    fn test_pose() {
+        let web = rotational_web(0.25, |gamma| 1000.0 * gamma.cos().max(0.0));
+        let grid = CalculationGrid::new((-0.5, -0.5), 1.0, 1.0, 1, 1, 0.0);
+        let level = IlluminanceGrid::new(&web, &LuminairePose::new(Vector3::new(0.0, 0.0, 2.0)), &grid);
+        assert_abs_diff_eq!(level.average(), 250.0, epsilon = 1E-9);
+
+        let tilted = LuminairePose::with_rotation(Vector3::new(0.0, 0.0, 2.0), Rotation::from_pitch(PI / 3.0));
+        let tilted = IlluminanceGrid::new(&web, &tilted, &grid);
+        assert_abs_diff_eq!(tilted.average(), 125.0, epsilon = 1E-6);
+
+        let below = IlluminanceGrid::new(&web, &LuminairePose::new(Vector3::new(0.0, 0.0, -1.0)), &grid);
+        assert_eq!(below.max(), 0.0);
+        assert_eq!(below.u0(), 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+//! The direct illuminance at a point on a surface, and the vertical, cylindrical and semi-cylindrical illuminances
+//! used for pedestrian and modelling criteria.
+//!
+//! Facing directions are given as azimuths in radians, anti-clockwise from the world x-axis.
+
+use super::LuminairePose;
+use crate::{photweb::PhotometricWeb, util::geom::Vector3};
+use std::f64::consts::PI;
+
+/// The upward normal of a horizontal surface, such as a work plane or the floor.
+pub const HORIZONTAL_NORMAL: Vector3 = Vector3::new(0.0, 0.0, 1.0);
+
+/// Returns the unit vector from a point towards the luminaire, and the illuminance that the luminaire produces on a
+/// surface at the point facing it directly. Returns `None` if the point is at the luminaire.
+fn normal_illuminance(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3) -> Option<(Vector3, f64)> {
+    let to_luminaire = pose.position - *point;
+    let distance_sq = to_luminaire.dot(&to_luminaire);
+    if distance_sq <= 0.0 {
+        return None;
+    }
+    Some((to_luminaire.normalise(), pose.intensity_towards(web, point) / distance_sq))
+}
+
+/// Computes the direct illuminance at a point on a surface with the given normal, using the inverse square and cosine laws.
+/// The normal points out of the lit face of the surface, so luminaires behind the surface contribute nothing.
+pub fn illuminance_at(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3, normal: &Vector3) -> f64 {
+    normal_illuminance(web, pose, point).map_or(0.0, |(incident, e_normal)| {
+        e_normal * incident.dot(&normal.normalise()).max(0.0)
+    })
+}
+
+/// Computes the direct illuminance at a point on a horizontal surface facing upwards.
+pub fn horizontal_illuminance(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3) -> f64 {
+    illuminance_at(web, pose, point, &HORIZONTAL_NORMAL)
+}
+
+/// Computes the direct illuminance at a point on a vertical surface facing the given azimuth (in radians).
+pub fn vertical_illuminance(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3, facing: f64) -> f64 {
+    illuminance_at(web, pose, point, &Vector3::new(facing.cos(), facing.sin(), 0.0))
+}
+
+/// Computes the direct cylindrical illuminance at a point: the average illuminance on the curved surface of a
+/// vanishingly small vertical cylinder, given by E_n sin(epsilon) / pi, where epsilon is the angle of incidence
+/// from the vertical.
+pub fn cylindrical_illuminance(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3) -> f64 {
+    normal_illuminance(web, pose, point).map_or(0.0, |(incident, e_normal)| {
+        e_normal * incident.x.hypot(incident.y) / PI
+    })
+}
+
+/// Computes the direct semi-cylindrical illuminance at a point: the average illuminance on the curved surface of a
+/// vanishingly small vertical half-cylinder facing the given azimuth (in radians), given by
+/// E_n sin(epsilon) (1 + cos(alpha)) / pi, where alpha is the horizontal angle between the light and the facing direction.
+pub fn semicylindrical_illuminance(web: &PhotometricWeb, pose: &LuminairePose, point: &Vector3, facing: f64) -> f64 {
+    normal_illuminance(web, pose, point).map_or(0.0, |(incident, e_normal)| {
+        let horizontal = incident.x.hypot(incident.y);
+        if horizontal <= 0.0 {
+            return 0.0;
+        }
+        let cos_alpha = (incident.x * facing.cos() + incident.y * facing.sin()) / horizontal;
+        e_normal * horizontal * (1.0 + cos_alpha) / PI
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::isotropic_web;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::FRAC_PI_2;
+
+    /// An isotropic source of 1000 cd, 3 m along the x-axis and 4 m above the point, at a distance of 5 m, gives
+    /// E_n = 40 lx with sin(epsilon) = 3 / 5.
+    #[test]
+    fn test_isotropic_source() {
+        let web = isotropic_web(1000.0);
+        let pose = LuminairePose::new(Vector3::new(3.0, 0.0, 4.0));
+        let point = Vector3::new(0.0, 0.0, 0.0);
+
+        assert_abs_diff_eq!(horizontal_illuminance(&web, &pose, &point), 40.0 * 0.8, epsilon = 1E-9);
+        assert_abs_diff_eq!(vertical_illuminance(&web, &pose, &point, 0.0), 40.0 * 0.6, epsilon = 1E-9);
+        assert_abs_diff_eq!(vertical_illuminance(&web, &pose, &point, PI / 3.0), 40.0 * 0.3, epsilon = 1E-9);
+        assert_eq!(vertical_illuminance(&web, &pose, &point, PI), 0.0);
+
+        assert_abs_diff_eq!(cylindrical_illuminance(&web, &pose, &point), 40.0 * 0.6 / PI, epsilon = 1E-9);
+        assert_abs_diff_eq!(semicylindrical_illuminance(&web, &pose, &point, 0.0), 40.0 * 1.2 / PI, epsilon = 1E-9);
+        assert_abs_diff_eq!(semicylindrical_illuminance(&web, &pose, &point, FRAC_PI_2), 40.0 * 0.6 / PI, epsilon = 1E-9);
+        assert_abs_diff_eq!(semicylindrical_illuminance(&web, &pose, &point, PI), 0.0, epsilon = 1E-9);
+    }
+
+    /// With sources evenly spread around the point, the semi-cylindrical illuminance in any direction equals the
+    /// cylindrical illuminance, and a source directly overhead contributes to neither.
+    #[test]
+    fn test_surrounding_sources() {
+        let web = isotropic_web(1000.0);
+        let point = Vector3::new(0.0, 0.0, 0.0);
+        let poses: Vec<LuminairePose> = (0..4)
+            .map(|i| {
+                let azimuth = i as f64 * FRAC_PI_2;
+                LuminairePose::new(Vector3::new(2.0 * azimuth.cos(), 2.0 * azimuth.sin(), 3.0))
+            })
+            .collect();
+        let total = |func: &dyn Fn(&LuminairePose) -> f64| poses.iter().map(func).sum::<f64>();
+
+        let cylindrical = total(&|pose| cylindrical_illuminance(&web, pose, &point));
+        for facing in [0.0, 0.4, 2.0] {
+            let semicylindrical = total(&|pose| semicylindrical_illuminance(&web, pose, &point, facing));
+            assert_abs_diff_eq!(semicylindrical, cylindrical, epsilon = 1E-9);
+        }
+
+        let overhead = LuminairePose::new(Vector3::new(0.0, 0.0, 3.0));
+        assert_eq!(cylindrical_illuminance(&web, &overhead, &point), 0.0);
+        assert_eq!(semicylindrical_illuminance(&web, &overhead, &point, 0.0), 0.0);
+    }
+}
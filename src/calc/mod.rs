@@ -0,0 +1,13 @@
+//! Lighting calculations, finding the illuminance produced by luminaires placed in a scene.
+//!
+//! Positions are given in metres in a right-handed world coordinate system with the z-axis pointing upwards.
+//! The orientation of each luminaire is given by a rotation from the luminaire coordinate system (see `Vector3`)
+//! into the world coordinate system.
+
+pub mod grid;
+pub mod illuminance;
+pub mod pose;
+pub mod road;
+pub mod rtable;
+pub mod scene;
+pub use self::{grid::*, illuminance::*, pose::*, road::*, rtable::*, scene::*};
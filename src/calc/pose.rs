@@ -0,0 +1,40 @@
+//! The position and orientation of a luminaire in the world.
+
+use crate::{
+    photweb::PhotometricWeb,
+    util::geom::{Rotation, Vector3},
+};
+
+/// The position and orientation of a luminaire in the world.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LuminairePose {
+    /// The position of the photometric centre of the luminaire, in metres.
+    pub position: Vector3,
+    /// The rotation from the luminaire coordinate system into the world coordinate system.
+    pub rotation: Rotation,
+}
+
+impl LuminairePose {
+    /// A luminaire at the given position, pointing straight down with its C0 plane along the world x-axis.
+    pub fn new(position: Vector3) -> Self {
+        Self {
+            position,
+            rotation: Rotation::identity(),
+        }
+    }
+
+    /// A luminaire at the given position, with the given rotation from the luminaire into the world coordinate system.
+    pub fn with_rotation(position: Vector3, rotation: Rotation) -> Self {
+        Self { position, rotation }
+    }
+
+    /// Returns the direction from the luminaire towards a point in the world, in the luminaire coordinate system.
+    pub fn local_direction_to(&self, point: &Vector3) -> Vector3 {
+        self.rotation.inverse().rotate(&(*point - self.position))
+    }
+
+    /// Returns the intensity of the luminaire in the direction of a point in the world.
+    pub fn intensity_towards(&self, web: &PhotometricWeb, point: &Vector3) -> f64 {
+        web.intensity_towards(&self.local_direction_to(point))
+    }
+}
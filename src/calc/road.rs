@@ -0,0 +1,477 @@
+//! Road lighting calculations following EN 13201-3 and CIE 140.
+//!
+//! The road runs along the world x-axis, with the near kerb at y = 0 and the far kerb at y = W. Luminaires are mounted
+//! in rows along the kerbs, with the C0 plane along the road and the C90 plane pointing across the road. The calculation
+//! field lies between two consecutive luminaires of the near row, from x = 0 to x = S, and observers look along +x.
+
+use super::{horizontal_illuminance, illuminance_at, LuminairePose, RTable};
+use crate::{
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, radians_to_degrees, Rotation, Vector3},
+};
+use std::f64::consts::PI;
+
+/// The height of the eye of the observer above the road, in metres.
+pub const OBSERVER_EYE_HEIGHT: f64 = 1.5;
+/// The distance of the observer before the first row of calculation points, in metres.
+pub const OBSERVER_DISTANCE: f64 = 60.0;
+/// The angle (in degrees) of the line of sight of the observer below the horizontal, used for the threshold increment.
+pub const LINE_OF_SIGHT_ANGLE: f64 = 1.0;
+/// The angle (in degrees) above the horizontal beyond which luminaires are screened by the roof of the vehicle.
+pub const SCREENING_ANGLE: f64 = 20.0;
+/// The largest spacing (in metres) between the rows of calculation points along the road.
+const MAX_LONGITUDINAL_SPACING: f64 = 3.0;
+/// The number of rows of calculation points along the road when the luminaires are at most 30 m apart.
+const MIN_LONGITUDINAL_POINTS: usize = 10;
+/// The number of calculation points across each lane.
+const POINTS_PER_LANE: usize = 3;
+/// The largest width (in metres) of the strips either side of each edge of the carriageway used for the edge illuminance ratio.
+const MAX_EDGE_STRIP_WIDTH: f64 = 5.0;
+/// The distance (in luminaire heights) before the calculation field within which luminaires are included.
+const LUMINAIRES_BEFORE: f64 = 5.0;
+/// The distance (in luminaire heights) after the calculation field within which luminaires are included.
+const LUMINAIRES_AFTER: f64 = 12.0;
+/// The distance (in metres) ahead of the observer within which luminaires are included in the threshold increment.
+const GLARE_DISTANCE: f64 = 500.0;
+
+/// The arrangement of the luminaires along the road.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoleArrangement {
+    /// A single row of luminaires along the near kerb.
+    SingleSided,
+    /// Rows along both kerbs, with the far row offset by half the spacing.
+    Staggered,
+    /// Rows along both kerbs, with the luminaires opposite each other.
+    Opposite,
+}
+
+/// The geometry of a road and its lighting installation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoadLayout {
+    /// The width of the carriageway, in metres.
+    pub carriageway_width: f64,
+    /// The number of lanes of the carriageway.
+    pub n_lanes: usize,
+    /// The arrangement of the luminaires.
+    pub arrangement: PoleArrangement,
+    /// The spacing between consecutive luminaires in the same row, in metres.
+    pub spacing: f64,
+    /// The height of the luminaires above the road, in metres.
+    pub mounting_height: f64,
+    /// The horizontal distance of the luminaires from their kerb, positive over the carriageway, in metres.
+    pub overhang: f64,
+    /// The tilt of the luminaires towards the carriageway, in degrees.
+    pub tilt: f64,
+}
+
+impl RoadLayout {
+    /// A road with luminaires mounted directly above the kerbs, without tilt.
+    pub fn new(
+        carriageway_width: f64,
+        n_lanes: usize,
+        arrangement: PoleArrangement,
+        spacing: f64,
+        mounting_height: f64,
+    ) -> Self {
+        Self {
+            carriageway_width,
+            n_lanes,
+            arrangement,
+            spacing,
+            mounting_height,
+            overhang: 0.0,
+            tilt: 0.0,
+        }
+    }
+
+    /// Returns the poses of the luminaires with an x position in the given range.
+    pub fn luminaire_poses(&self, from_x: f64, to_x: f64) -> Vec<LuminairePose> {
+        let tilt = Rotation::from_roll(degrees_to_radians(self.tilt));
+        let near = (0.0, self.overhang, tilt);
+        let far = (
+            0.0,
+            self.carriageway_width - self.overhang,
+            Rotation::from_yaw(PI).compose(&tilt),
+        );
+        let rows = match self.arrangement {
+            PoleArrangement::SingleSided => vec![near],
+            PoleArrangement::Staggered => vec![near, (0.5 * self.spacing, far.1, far.2)],
+            PoleArrangement::Opposite => vec![near, far],
+        };
+        rows.iter()
+            .flat_map(|(offset, y, rotation)| {
+                let first = ((from_x - offset) / self.spacing).ceil() as i64;
+                let last = ((to_x - offset) / self.spacing).floor() as i64;
+                (first..=last).map(move |i| {
+                    LuminairePose::with_rotation(
+                        Vector3::new(offset + i as f64 * self.spacing, *y, self.mounting_height),
+                        *rotation,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// The x positions of the rows of calculation points, spread evenly through the field between two luminaires.
+    pub fn longitudinal_points(&self) -> Vec<f64> {
+        let n = if self.spacing <= 30.0 {
+            MIN_LONGITUDINAL_POINTS
+        } else {
+            (self.spacing / MAX_LONGITUDINAL_SPACING).ceil() as usize
+        };
+        let step = self.spacing / n as f64;
+        (0..n).map(|i| (i as f64 + 0.5) * step).collect()
+    }
+
+    /// The y positions of the calculation points across the carriageway, three per lane.
+    pub fn transverse_points(&self) -> Vec<f64> {
+        let n = POINTS_PER_LANE * self.n_lanes.max(1);
+        let step = self.carriageway_width / n as f64;
+        (0..n).map(|i| (i as f64 + 0.5) * step).collect()
+    }
+
+    /// The y positions of the observers, at the centre of each lane.
+    pub fn observer_positions(&self) -> Vec<f64> {
+        let n_lanes = self.n_lanes.max(1);
+        let lane_width = self.carriageway_width / n_lanes as f64;
+        (0..n_lanes).map(|i| (i as f64 + 0.5) * lane_width).collect()
+    }
+}
+
+/// The results of a road lighting calculation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoadResults {
+    /// The x positions of the calculation points, in metres.
+    pub x_points: Vec<f64>,
+    /// The y positions of the calculation points, in metres.
+    pub y_points: Vec<f64>,
+    /// The horizontal illuminance at each point, indexed by [x][y].
+    pub illuminance: Vec<Vec<f64>>,
+    /// The luminance at each point for the observer in each lane, indexed by [lane][x][y].
+    pub luminance: Vec<Vec<Vec<f64>>>,
+    /// The average horizontal illuminance.
+    pub average_illuminance: f64,
+    /// The minimum horizontal illuminance.
+    pub min_illuminance: f64,
+    /// The average luminance Lav, the lowest of the observer positions.
+    pub average_luminance: f64,
+    /// The overall uniformity U0 of the luminance, the lowest of the observer positions.
+    pub overall_uniformity: f64,
+    /// The longitudinal uniformity Ul of the luminance along the centre of each lane, the lowest of the lanes.
+    pub longitudinal_uniformity: f64,
+    /// The veiling luminance Lv (in cd/m2) used for the threshold increment, the highest of the observer positions.
+    pub veiling_luminance: f64,
+    /// The threshold increment TI as a percentage, the highest of the observer positions.
+    pub threshold_increment: f64,
+    /// The edge illuminance ratio EIR, the lower of the two edges of the carriageway.
+    pub edge_illuminance_ratio: f64,
+}
+
+impl RoadResults {
+    /// Performs the road lighting calculation for the given luminaire, layout and road surface.
+    pub fn new(web: &PhotometricWeb, layout: &RoadLayout, rtable: &RTable) -> Self {
+        let height = layout.mounting_height;
+        let poses = layout.luminaire_poses(-LUMINAIRES_BEFORE * height, layout.spacing + LUMINAIRES_AFTER * height);
+        let x_points = layout.longitudinal_points();
+        let y_points = layout.transverse_points();
+        let grid = |func: &dyn Fn(&Vector3) -> f64| -> Vec<Vec<f64>> {
+            x_points
+                .iter()
+                .map(|x| y_points.iter().map(|y| func(&Vector3::new(*x, *y, 0.0))).collect())
+                .collect()
+        };
+
+        let illuminance = grid(&|point| poses.iter().map(|pose| horizontal_illuminance(web, pose, point)).sum());
+        let (average_illuminance, min_illuminance, _) = statistics(illuminance.iter().flatten());
+
+        let observer_x = x_points.first().cloned().unwrap_or_default() - OBSERVER_DISTANCE;
+        let luminance: Vec<Vec<Vec<f64>>> = layout
+            .observer_positions()
+            .iter()
+            .map(|y| {
+                let observer = Vector3::new(observer_x, *y, OBSERVER_EYE_HEIGHT);
+                grid(&|point| point_luminance(web, &poses, rtable, point, &observer))
+            })
+            .collect();
+
+        let mut average_luminance = f64::INFINITY;
+        let mut overall_uniformity = f64::INFINITY;
+        let mut longitudinal_uniformity = f64::INFINITY;
+        for (lane, values) in luminance.iter().enumerate() {
+            let (average, min, _) = statistics(values.iter().flatten());
+            average_luminance = average_luminance.min(average);
+            overall_uniformity = overall_uniformity.min(ratio(min, average));
+            let centre = lane * POINTS_PER_LANE + POINTS_PER_LANE / 2;
+            let (_, min, max) = statistics(values.iter().map(|row| &row[centre]));
+            longitudinal_uniformity = longitudinal_uniformity.min(ratio(min, max));
+        }
+        let finite_or_zero = |val: f64| if val.is_finite() { val } else { 0.0 };
+        let average_luminance = finite_or_zero(average_luminance);
+        let veiling_luminance = veiling_luminance(web, layout);
+
+        Self {
+            threshold_increment: threshold_increment(veiling_luminance, average_luminance),
+            veiling_luminance,
+            edge_illuminance_ratio: edge_illuminance_ratio(web, layout, &poses, &x_points),
+            x_points,
+            y_points,
+            illuminance,
+            luminance,
+            average_illuminance,
+            min_illuminance,
+            average_luminance: finite_or_zero(average_luminance),
+            overall_uniformity: finite_or_zero(overall_uniformity),
+            longitudinal_uniformity: finite_or_zero(longitudinal_uniformity),
+        }
+    }
+}
+
+/// Returns the (average, minimum, maximum) of the values, or zeros if there are none.
+fn statistics<'a>(values: impl Iterator<Item = &'a f64>) -> (f64, f64, f64) {
+    let (sum, min, max, n) = values.fold((0.0, f64::INFINITY, f64::NEG_INFINITY, 0), |(sum, min, max, n), val| {
+        (sum + val, min.min(*val), max.max(*val), n + 1)
+    });
+    if n == 0 {
+        (0.0, 0.0, 0.0)
+    } else {
+        (sum / n as f64, min, max)
+    }
+}
+
+/// Returns the ratio of two values, or zero if the denominator is not positive.
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Computes the luminance of a point on the road seen by the observer, L = sum(I r(beta, tan(gamma)) / H^2).
+fn point_luminance(
+    web: &PhotometricWeb,
+    poses: &[LuminairePose],
+    rtable: &RTable,
+    point: &Vector3,
+    observer: &Vector3,
+) -> f64 {
+    let to_observer = (observer.x - point.x, observer.y - point.y);
+    poses
+        .iter()
+        .map(|pose| {
+            let to_luminaire = pose.position - *point;
+            if to_luminaire.z <= 0.0 {
+                return 0.0;
+            }
+            let horizontal = to_luminaire.x.hypot(to_luminaire.y);
+            let beta = if horizontal > 0.0 {
+                let cross = to_observer.0 * to_luminaire.y - to_observer.1 * to_luminaire.x;
+                let dot = to_observer.0 * to_luminaire.x + to_observer.1 * to_luminaire.y;
+                radians_to_degrees(cross.atan2(dot))
+            } else {
+                0.0
+            };
+            let intensity = pose.intensity_towards(web, point);
+            intensity * rtable.r(beta, horizontal / to_luminaire.z) / to_luminaire.z.powi(2)
+        })
+        .sum()
+}
+
+/// Computes the veiling luminance (in cd/m2) for an observer in each lane, moving through the field from 2.75 (H - 1.5)
+/// before it, and returns the highest value. The veiling luminance is Lv = 10 sum(E / theta^2), where E is the
+/// illuminance at the eye on a plane perpendicular to the line of sight and theta the angle (in degrees) of the
+/// luminaire from the line of sight. Luminaires more than 20 degrees above the horizontal are screened.
+fn veiling_luminance(web: &PhotometricWeb, layout: &RoadLayout) -> f64 {
+    let sight_angle = degrees_to_radians(LINE_OF_SIGHT_ANGLE);
+    let sight = Vector3::new(sight_angle.cos(), 0.0, -sight_angle.sin());
+    let start = -2.75 * (layout.mounting_height - OBSERVER_EYE_HEIGHT);
+    let x_points = layout.longitudinal_points();
+    let step = x_points.get(1).map_or(layout.spacing, |x| x - x_points[0]);
+
+    let veiling_luminance = |eye: &Vector3| -> f64 {
+        layout
+            .luminaire_poses(eye.x, eye.x + GLARE_DISTANCE)
+            .iter()
+            .map(|pose| {
+                let to_luminaire = pose.position - *eye;
+                let elevation = to_luminaire.z.atan2(to_luminaire.x.hypot(to_luminaire.y));
+                let theta = radians_to_degrees(to_luminaire.normalise().dot(&sight).clamp(-1.0, 1.0).acos());
+                if elevation > degrees_to_radians(SCREENING_ANGLE) || !(1.5..=60.0).contains(&theta) {
+                    return 0.0;
+                }
+                10.0 * illuminance_at(web, pose, eye, &sight) / theta.powi(2)
+            })
+            .sum()
+    };
+
+    layout
+        .observer_positions()
+        .iter()
+        .flat_map(|y| {
+            (0..x_points.len()).map(move |i| Vector3::new(start + i as f64 * step, *y, OBSERVER_EYE_HEIGHT))
+        })
+        .map(|eye| veiling_luminance(&eye))
+        .fold(0.0, f64::max)
+}
+
+/// Computes the threshold increment (as a percentage) from the veiling luminance and the average luminance of the road,
+/// TI = 65 Lv / L^0.8 for L up to 5 cd/m2, and TI = 95 Lv / L^1.05 above.
+fn threshold_increment(veiling_luminance: f64, average_luminance: f64) -> f64 {
+    if average_luminance <= 0.0 {
+        0.0
+    } else if average_luminance <= 5.0 {
+        65.0 * veiling_luminance / average_luminance.powf(0.8)
+    } else {
+        95.0 * veiling_luminance / average_luminance.powf(1.05)
+    }
+}
+
+/// Computes the edge illuminance ratio: the average horizontal illuminance on a strip just outside each edge of the
+/// carriageway, divided by that on a strip of the same width just inside it. The strips are 5 m wide, or half the width
+/// of the carriageway if that is narrower, and the lower ratio of the two edges is returned.
+fn edge_illuminance_ratio(web: &PhotometricWeb, layout: &RoadLayout, poses: &[LuminairePose], x_points: &[f64]) -> f64 {
+    let width = MAX_EDGE_STRIP_WIDTH.min(0.5 * layout.carriageway_width);
+    let strip_average = |from_y: f64| {
+        let points: Vec<f64> = (0..POINTS_PER_LANE)
+            .flat_map(|j| {
+                let y = from_y + (j as f64 + 0.5) * width / POINTS_PER_LANE as f64;
+                x_points.iter().map(move |x| {
+                    poses
+                        .iter()
+                        .map(|pose| horizontal_illuminance(web, pose, &Vector3::new(*x, y, 0.0)))
+                        .sum::<f64>()
+                })
+            })
+            .collect();
+        statistics(points.iter()).0
+    };
+    let near = ratio(strip_average(-width), strip_average(0.0));
+    let far = ratio(
+        strip_average(layout.carriageway_width),
+        strip_average(layout.carriageway_width - width),
+    );
+    near.min(far)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calc::RoadSurface, photweb::test_webs::rotational_web};
+    use approx::assert_abs_diff_eq;
+
+    /// A rotationally symmetric web with a broad batwing distribution peaking at gamma = 60 degrees, and no upward light.
+    fn road_web() -> PhotometricWeb {
+        rotational_web(1.0, |gamma| {
+            let int = 500.0 * gamma.cos().max(0.0) + 2000.0 * (-0.5 * ((gamma - PI / 3.0) / 0.2).powi(2)).exp();
+            if int < 1E-6 {
+                0.0
+            } else {
+                int
+            }
+        })
+    }
+
+    fn layout(arrangement: PoleArrangement) -> RoadLayout {
+        RoadLayout {
+            overhang: 1.0,
+            tilt: 5.0,
+            ..RoadLayout::new(7.0, 2, arrangement, 30.0, 8.0)
+        }
+    }
+
+    #[test]
+    fn test_grid() {
+        let layout = layout(PoleArrangement::Staggered);
+        assert_eq!(layout.longitudinal_points().len(), 10);
+        assert_eq!(layout.transverse_points().len(), 6);
+        assert_abs_diff_eq!(layout.observer_positions()[1], 5.25, epsilon = 1E-12);
+
+        let long = RoadLayout::new(7.0, 2, PoleArrangement::SingleSided, 40.0, 10.0);
+        assert_eq!(long.longitudinal_points().len(), 14);
+
+        let poses = layout.luminaire_poses(0.0, 60.0);
+        assert_eq!(poses.len(), 5);
+        assert!(poses.iter().any(|pose| pose.position == Vector3::new(15.0, 6.0, 8.0)));
+    }
+
+    /// With a perfectly diffuse road surface, r = Q0 cos^3(gamma) and the luminance is Q0 E for every observer.
+    #[test]
+    fn test_diffuse_surface() {
+        let q0 = 0.1;
+        let rtable = RTable::new(
+            vec![0.0, 180.0],
+            (0..=1200).map(|i| i as f64 / 100.0).collect(),
+            (0..=1200)
+                .map(|i| {
+                    let tan_gamma = i as f64 / 100.0;
+                    vec![q0 / (1.0 + tan_gamma * tan_gamma).powf(1.5); 2]
+                })
+                .collect(),
+        );
+        let results = RoadResults::new(&road_web(), &layout(PoleArrangement::SingleSided), &rtable);
+        for lane in &results.luminance {
+            for (lum_row, ill_row) in lane.iter().zip(&results.illuminance) {
+                for (lum, ill) in lum_row.iter().zip(ill_row) {
+                    assert_abs_diff_eq!(*lum, q0 * ill, epsilon = 1E-3 * ill.max(1.0));
+                }
+            }
+        }
+        assert_abs_diff_eq!(results.average_luminance, q0 * results.average_illuminance, epsilon = 1E-2);
+    }
+
+    #[test]
+    fn test_road_results() {
+        let rtable = RTable::approximate_surface(RoadSurface::R3);
+        let results = RoadResults::new(&road_web(), &layout(PoleArrangement::Opposite), &rtable);
+
+        // Opposite rows light the road symmetrically across its width.
+        for row in &results.illuminance {
+            for (val, mirrored) in row.iter().zip(row.iter().rev()) {
+                assert_abs_diff_eq!(val, mirrored, epsilon = 1E-6 * val.max(1.0));
+            }
+        }
+
+        assert!(results.average_illuminance > results.min_illuminance && results.min_illuminance > 0.0);
+        assert!(results.average_luminance > 0.0);
+        assert!(results.overall_uniformity > 0.0 && results.overall_uniformity <= 1.0);
+        assert!(results.longitudinal_uniformity > 0.0 && results.longitudinal_uniformity <= 1.0);
+        assert!(results.threshold_increment > 0.0);
+        assert!(results.edge_illuminance_ratio > 0.0 && results.edge_illuminance_ratio < 1.0);
+
+        // The threshold increment follows from the veiling luminance, in the branch for luminances up to 5 cd/m2.
+        assert!(results.veiling_luminance > 0.0 && results.average_luminance <= 5.0);
+        assert_abs_diff_eq!(
+            results.threshold_increment,
+            65.0 * results.veiling_luminance / results.average_luminance.powf(0.8),
+            epsilon = 1E-9
+        );
+
+        // Scaling the output scales both luminances, and a high enough output moves into the branch above 5 cd/m2.
+        let factor = 10.0 / results.average_luminance;
+        let brighter = RoadResults::new(
+            &crate::ops::scale_photometric_web(&road_web(), factor),
+            &layout(PoleArrangement::Opposite),
+            &rtable,
+        );
+        assert_abs_diff_eq!(brighter.average_luminance, 10.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(brighter.veiling_luminance, factor * results.veiling_luminance, epsilon = 1E-9);
+        assert_abs_diff_eq!(
+            brighter.threshold_increment,
+            95.0 * brighter.veiling_luminance / 10.0_f64.powf(1.05),
+            epsilon = 1E-9
+        );
+        assert_abs_diff_eq!(brighter.overall_uniformity, results.overall_uniformity, epsilon = 1E-9);
+    }
+
+    /// Hand-computed threshold increments for a known veiling luminance, either side of 5 cd/m2.
+    #[test]
+    fn test_threshold_increment() {
+        // 65 x 0.2 / 1^0.8 and 65 x 0.2 / 2^0.8 = 13 / 1.7411.
+        assert_abs_diff_eq!(threshold_increment(0.2, 1.0), 13.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(threshold_increment(0.2, 2.0), 7.4665, epsilon = 1E-4);
+        assert_abs_diff_eq!(threshold_increment(0.5, 5.0), 8.9682, epsilon = 1E-4);
+        // 95 x 1 / 10^1.05 = 95 / 11.2202.
+        assert_abs_diff_eq!(threshold_increment(1.0, 10.0), 8.4669, epsilon = 1E-4);
+        assert_eq!(threshold_increment(1.0, 0.0), 0.0);
+    }
+}
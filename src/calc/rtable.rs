@@ -0,0 +1,250 @@
+//! Reduced luminance coefficient tables (r-tables) of road surfaces, as used by CIE 144 and EN 13201-3.
+//!
+//! An r-table gives the reduced luminance coefficient r = q cos^3(gamma) of a dry road surface, as a function of the angle
+//! beta between the vertical planes of observation and of incidence, and tan(gamma), where gamma is the angle of incidence.
+//! The luminance of a point lit by a luminaire of intensity I at a height H is then L = I r / H^2.
+//!
+//! The standard tables of the classes C1, C2 and R1 to R4 are published in CIE 144 as r x 10^4 on the grid of
+//! `R_TABLE_TAN_GAMMAS` and `R_TABLE_BETAS`, and are not bundled with this crate. Load them with
+//! `RTable::from_standard_grid` to reproduce the luminances of EN 13201-3. `RTable::approximate_surface` only matches the
+//! Q0 and S1 of a class, so its luminances and uniformities will differ from those computed with the published table.
+
+use std::f64::consts::PI;
+
+/// The beta angles (in degrees) of the standard CIE r-tables.
+pub const R_TABLE_BETAS: [f64; 20] = [
+    0.0, 2.0, 5.0, 10.0, 15.0, 20.0, 25.0, 30.0, 35.0, 40.0, 45.0, 60.0, 75.0, 90.0, 105.0, 120.0, 135.0, 150.0, 165.0,
+    180.0,
+];
+
+/// The values of tan(gamma) of the standard CIE r-tables.
+pub const R_TABLE_TAN_GAMMAS: [f64; 29] = [
+    0.0, 0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0, 5.5, 6.0, 6.5, 7.0, 7.5, 8.0, 8.5, 9.0,
+    9.5, 10.0, 10.5, 11.0, 11.5, 12.0,
+];
+
+/// The width (in degrees) of the forward-scattering lobe of the approximated r-tables.
+const SPECULAR_LOBE_WIDTH: f64 = 30.0;
+
+/// The standard road surface classes of CIE 144.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoadSurface {
+    C1,
+    C2,
+    R1,
+    R2,
+    R3,
+    R4,
+}
+
+impl RoadSurface {
+    /// The average luminance coefficient Q0 of the standard surface.
+    pub fn q0(&self) -> f64 {
+        match self {
+            Self::C1 => 0.10,
+            Self::C2 => 0.07,
+            Self::R1 => 0.10,
+            Self::R2 => 0.07,
+            Self::R3 => 0.07,
+            Self::R4 => 0.08,
+        }
+    }
+
+    /// The specular factor S1 of the standard surface, r(0, 2) / r(0, 0).
+    pub fn s1(&self) -> f64 {
+        match self {
+            Self::C1 => 0.24,
+            Self::C2 => 0.97,
+            Self::R1 => 0.25,
+            Self::R2 => 0.58,
+            Self::R3 => 1.11,
+            Self::R4 => 1.55,
+        }
+    }
+}
+
+/// A table of reduced luminance coefficients, indexed by tan(gamma) and beta.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RTable {
+    /// The beta angles of the table, in degrees, from 0 to 180.
+    pub betas: Vec<f64>,
+    /// The values of tan(gamma) of the table, in increasing order.
+    pub tan_gammas: Vec<f64>,
+    /// The reduced luminance coefficients (not scaled by 10^4), indexed by [tan(gamma)][beta].
+    pub values: Vec<Vec<f64>>,
+}
+
+impl RTable {
+    /// Creates an r-table from its values, for example from a published or measured table.
+    /// The values are the reduced luminance coefficients themselves, so tables published as r x 10^4 must be scaled.
+    pub fn new(betas: Vec<f64>, tan_gammas: Vec<f64>, values: Vec<Vec<f64>>) -> Self {
+        Self {
+            betas,
+            tan_gammas,
+            values,
+        }
+    }
+
+    /// Creates an r-table on the standard CIE grid of `R_TABLE_TAN_GAMMAS` (rows) and `R_TABLE_BETAS` (columns) from
+    /// values published as r x 10^4, such as the standard tables of CIE 144. Blank cells of a published table, beyond
+    /// the range of the measurements, are given as zero.
+    pub fn from_standard_grid(scaled_values: &[[f64; 20]; 29]) -> Self {
+        Self::new(
+            R_TABLE_BETAS.to_vec(),
+            R_TABLE_TAN_GAMMAS.to_vec(),
+            scaled_values
+                .iter()
+                .map(|row| row.iter().map(|val| val * 1E-4).collect())
+                .collect(),
+        )
+    }
+
+    /// Creates an approximation of the r-table of a standard road surface class from its Q0 and S1, see `approximate`.
+    /// This is a fallback for when the published table of the class is not available, and its luminances will differ
+    /// from those computed with the published table. Use `from_standard_grid` with the published table where possible.
+    pub fn approximate_surface(surface: RoadSurface) -> Self {
+        Self::approximate(surface.q0(), surface.s1())
+    }
+
+    /// Creates an approximate r-table on the standard grid with the given average luminance coefficient Q0 and specular
+    /// factor S1. The table is not a published CIE table: the surface is modelled as a diffuse reflector with a
+    /// forward-scattering lobe, q = q_d (1 + k tan^2(gamma) exp(-tan^2(gamma) / 8) exp(-(beta / 30)^2)), with k chosen to
+    /// give S1 and q_d scaled to give Q0, so only these two parameters match the class.
+    pub fn approximate(q0: f64, s1: f64) -> Self {
+        let lambertian_s1 = 5.0_f64.powf(-1.5);
+        let k = ((s1 / lambertian_s1 - 1.0) / (4.0 * (-0.5_f64).exp())).max(0.0);
+        let shape = |beta: f64, tan_gamma: f64| {
+            let t2 = tan_gamma * tan_gamma;
+            (1.0 + t2).powf(-1.5) * (1.0 + k * t2 * (-t2 / 8.0).exp() * (-(beta / SPECULAR_LOBE_WIDTH).powi(2)).exp())
+        };
+        let unscaled = Self::new(
+            R_TABLE_BETAS.to_vec(),
+            R_TABLE_TAN_GAMMAS.to_vec(),
+            R_TABLE_TAN_GAMMAS
+                .iter()
+                .map(|tan_gamma| R_TABLE_BETAS.iter().map(|beta| shape(*beta, *tan_gamma)).collect())
+                .collect(),
+        );
+        let scale = q0 / unscaled.q0();
+        Self {
+            values: unscaled
+                .values
+                .iter()
+                .map(|row| row.iter().map(|val| scale * val).collect())
+                .collect(),
+            ..unscaled
+        }
+    }
+
+    /// Returns the reduced luminance coefficient at the given beta (in degrees) and tan(gamma), interpolated bilinearly.
+    /// Beta is folded into the range 0 -> 180 degrees, and directions beyond the last tan(gamma) of the table give zero.
+    pub fn r(&self, beta: f64, tan_gamma: f64) -> f64 {
+        let beta = beta.abs().rem_euclid(360.0);
+        let beta = if beta > 180.0 { 360.0 - beta } else { beta };
+        match (bracket(&self.tan_gammas, tan_gamma), bracket(&self.betas, beta)) {
+            (Some((it, ft)), Some((ib, fb))) => {
+                let val = |it: usize, ib: usize| self.values[it][ib];
+                let lower = (1.0 - fb) * val(it, ib) + fb * val(it, ib + 1);
+                let upper = (1.0 - fb) * val(it + 1, ib) + fb * val(it + 1, ib + 1);
+                (1.0 - ft) * lower + ft * upper
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Computes the average luminance coefficient Q0, the average of the luminance coefficient q = r / cos^3(gamma) over
+    /// the solid angle of the table.
+    pub fn q0(&self) -> f64 {
+        let max_gamma = self.tan_gammas.last().map_or(0.0, |tan| tan.atan());
+        let n_gamma = 200;
+        let n_beta = 90;
+        let (d_gamma, d_beta) = (max_gamma / n_gamma as f64, PI / n_beta as f64);
+        let (weighted, solid_angle) = (0..n_gamma)
+            .flat_map(|ig| (0..n_beta).map(move |ib| (ig, ib)))
+            .fold((0.0, 0.0), |(weighted, solid_angle), (ig, ib)| {
+                let (gamma, beta) = ((ig as f64 + 0.5) * d_gamma, (ib as f64 + 0.5) * d_beta);
+                let d_omega = gamma.sin() * d_gamma * d_beta;
+                let q = self.r(beta.to_degrees(), gamma.tan()) / gamma.cos().powi(3);
+                (weighted + q * d_omega, solid_angle + d_omega)
+            });
+        if solid_angle > 0.0 {
+            weighted / solid_angle
+        } else {
+            0.0
+        }
+    }
+
+    /// Computes the specular factor S1, r(0, 2) / r(0, 0).
+    pub fn s1(&self) -> f64 {
+        let r0 = self.r(0.0, 0.0);
+        if r0 > 0.0 {
+            self.r(0.0, 2.0) / r0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Finds the index of the sample at or below the value, and the fraction of the way to the next sample.
+/// Returns `None` if the value is outside of the samples.
+fn bracket(samples: &[f64], value: f64) -> Option<(usize, f64)> {
+    match samples {
+        [] | [_] => None,
+        _ => samples.windows(2).position(|pair| value <= pair[1]).and_then(|i| {
+            let (lower, upper) = (samples[i], samples[i + 1]);
+            if value < lower {
+                return None;
+            }
+            let frac = if upper > lower { (value - lower) / (upper - lower) } else { 0.0 };
+            Some((i, frac))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_approximate_surfaces() {
+        for surface in [
+            RoadSurface::C1,
+            RoadSurface::C2,
+            RoadSurface::R1,
+            RoadSurface::R2,
+            RoadSurface::R3,
+            RoadSurface::R4,
+        ] {
+            let table = RTable::approximate_surface(surface);
+            assert_abs_diff_eq!(table.q0(), surface.q0(), epsilon = 1E-9);
+            assert_abs_diff_eq!(table.s1(), surface.s1(), epsilon = 1E-9);
+        }
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let table = RTable::new(vec![0.0, 180.0], vec![0.0, 1.0], vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_abs_diff_eq!(table.r(90.0, 0.5), 2.5, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.r(-90.0, 0.5), 2.5, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.r(270.0, 0.0), 1.5, epsilon = 1E-12);
+        assert_eq!(table.r(0.0, 1.5), 0.0);
+    }
+
+    /// Values published as r x 10^4 are scaled, and reproduced exactly at the cells of the grid.
+    #[test]
+    fn test_standard_grid() {
+        let mut scaled = [[0.0; 20]; 29];
+        for (it, row) in scaled.iter_mut().enumerate() {
+            for (ib, val) in row.iter_mut().enumerate() {
+                *val = (1000 - 30 * it - ib) as f64;
+            }
+        }
+        let table = RTable::from_standard_grid(&scaled);
+        assert_abs_diff_eq!(table.r(0.0, 0.0), 0.1, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.r(2.0, 0.25), 0.0969, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.r(180.0, 12.0), (1000.0 - 30.0 * 28.0 - 19.0) * 1E-4, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.r(1.0, 0.125), 0.5 * (0.1 + 0.0999) - 0.5 * 0.003, epsilon = 1E-12);
+        assert_abs_diff_eq!(table.s1(), table.r(0.0, 2.0) / 0.1, epsilon = 1E-12);
+    }
+}
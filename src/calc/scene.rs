@@ -0,0 +1,404 @@
+//! A scene of many luminaires, each referencing a shared photometric web by name.
+//!
+//! With the `serde` feature enabled, a scene can be described in a JSON or TOML file, which lists the photometric files
+//! to load and the luminaires and arrays placed in the scene. Paths to photometric files are relative to the description.
+//!
+//! ```toml
+//! [photometry]
+//! downlight = "downlight.ldt"
+//!
+//! [[luminaires]]
+//! photometry = "downlight"
+//! position = { x = 1.0, y = 2.0, z = 3.0 }
+//!
+//! [[arrays]]
+//! photometry = "downlight"
+//! origin = { x = 0.0, y = 0.0, z = 3.0 }
+//! n_x = 4
+//! n_y = 2
+//! spacing_x = 2.0
+//! spacing_y = 3.0
+//! maintenance_factor = 0.8
+//! ```
+
+use super::{illuminance_at, CalculationGrid, IlluminanceGrid, LuminairePose, HORIZONTAL_NORMAL};
+use crate::{
+    err::Error,
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, Rotation, Vector3},
+};
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use {
+    crate::photweb::PhotometricWebBuilder,
+    std::path::{Path, PathBuf},
+};
+
+#[cfg(feature = "serde")]
+fn default_factor() -> f64 {
+    1.0
+}
+
+/// A single luminaire placed in a scene.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LuminaireInstance {
+    /// The name of the photometric web of the luminaire in the scene.
+    pub photometry: String,
+    /// The position of the photometric centre of the luminaire, in metres.
+    pub position: Vector3,
+    /// The (yaw, pitch, roll) of the luminaire in degrees, see `Rotation::from_yaw_pitch_roll`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub orientation: (f64, f64, f64),
+    /// A point at which the nadir of the luminaire is aimed. When given, this replaces the pitch and roll of the
+    /// orientation, while the yaw still turns the luminaire about its own axis.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aim: Option<Vector3>,
+    /// The fraction of the full output of the luminaire at which it is dimmed.
+    #[cfg_attr(feature = "serde", serde(default = "default_factor"))]
+    pub dimming: f64,
+    /// The maintenance factor of the luminaire.
+    #[cfg_attr(feature = "serde", serde(default = "default_factor"))]
+    pub maintenance_factor: f64,
+}
+
+impl LuminaireInstance {
+    /// A luminaire at full output pointing straight down, with the given photometry and position.
+    pub fn new(photometry: &str, position: Vector3) -> Self {
+        Self {
+            photometry: photometry.to_owned(),
+            position,
+            orientation: (0.0, 0.0, 0.0),
+            aim: None,
+            dimming: 1.0,
+            maintenance_factor: 1.0,
+        }
+    }
+
+    /// Returns the pose of the luminaire, from its position and either its orientation or aiming point.
+    pub fn pose(&self) -> LuminairePose {
+        let (yaw, pitch, roll) = self.orientation;
+        let rotation = match self.aim {
+            Some(aim) => {
+                let offset = aim - self.position;
+                let tilt = offset.x.hypot(offset.y).atan2(-offset.z);
+                Rotation::from_yaw(offset.y.atan2(offset.x))
+                    .compose(&Rotation::from_pitch(tilt))
+                    .compose(&Rotation::from_yaw(-offset.y.atan2(offset.x) + degrees_to_radians(yaw)))
+            }
+            None => Rotation::from_yaw_pitch_roll(
+                degrees_to_radians(yaw),
+                degrees_to_radians(pitch),
+                degrees_to_radians(roll),
+            ),
+        };
+        LuminairePose::with_rotation(self.position, rotation)
+    }
+
+    /// The factor applied to the intensities of the photometric web, combining the dimming and maintenance factors.
+    pub fn output_factor(&self) -> f64 {
+        self.dimming * self.maintenance_factor
+    }
+}
+
+/// A regular rectangular array of identical luminaires, aligned with the world x and y axes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LuminaireArray {
+    /// The name of the photometric web of the luminaires in the scene.
+    pub photometry: String,
+    /// The position of the first luminaire of the array, in metres.
+    pub origin: Vector3,
+    /// The number of luminaires along the x-axis.
+    pub n_x: usize,
+    /// The number of luminaires along the y-axis.
+    pub n_y: usize,
+    /// The spacing of the luminaires along the x-axis, in metres.
+    pub spacing_x: f64,
+    /// The spacing of the luminaires along the y-axis, in metres.
+    pub spacing_y: f64,
+    /// The (yaw, pitch, roll) of each luminaire in degrees.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub orientation: (f64, f64, f64),
+    /// The fraction of the full output at which the luminaires are dimmed.
+    #[cfg_attr(feature = "serde", serde(default = "default_factor"))]
+    pub dimming: f64,
+    /// The maintenance factor of the luminaires.
+    #[cfg_attr(feature = "serde", serde(default = "default_factor"))]
+    pub maintenance_factor: f64,
+}
+
+impl LuminaireArray {
+    /// An array of luminaires at full output pointing straight down.
+    pub fn new(photometry: &str, origin: Vector3, (n_x, n_y): (usize, usize), (spacing_x, spacing_y): (f64, f64)) -> Self {
+        Self {
+            photometry: photometry.to_owned(),
+            origin,
+            n_x,
+            n_y,
+            spacing_x,
+            spacing_y,
+            orientation: (0.0, 0.0, 0.0),
+            dimming: 1.0,
+            maintenance_factor: 1.0,
+        }
+    }
+
+    /// An array of n_x by n_y luminaires evenly filling a room of the given length (along x) and width (along y),
+    /// with half a spacing between the outer luminaires and the walls.
+    pub fn filling_room(
+        photometry: &str,
+        (length, width): (f64, f64),
+        mounting_height: f64,
+        (n_x, n_y): (usize, usize),
+    ) -> Self {
+        let (spacing_x, spacing_y) = (length / n_x.max(1) as f64, width / n_y.max(1) as f64);
+        Self::new(
+            photometry,
+            Vector3::new(0.5 * spacing_x, 0.5 * spacing_y, mounting_height),
+            (n_x, n_y),
+            (spacing_x, spacing_y),
+        )
+    }
+
+    /// Returns the individual luminaires of the array.
+    pub fn instances(&self) -> Vec<LuminaireInstance> {
+        (0..self.n_x)
+            .flat_map(|ix| (0..self.n_y).map(move |iy| (ix, iy)))
+            .map(|(ix, iy)| LuminaireInstance {
+                orientation: self.orientation,
+                dimming: self.dimming,
+                maintenance_factor: self.maintenance_factor,
+                ..LuminaireInstance::new(
+                    &self.photometry,
+                    self.origin + Vector3::new(ix as f64 * self.spacing_x, iy as f64 * self.spacing_y, 0.0),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A scene of luminaires, with the photometric webs that they share.
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    /// The photometric webs of the scene, by name.
+    pub photometry: BTreeMap<String, PhotometricWeb>,
+    /// The luminaires placed in the scene.
+    pub luminaires: Vec<LuminaireInstance>,
+}
+
+impl Scene {
+    /// Creates an empty scene.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a photometric web to the scene under the given name, replacing any web with the same name.
+    pub fn add_photometry(&mut self, name: &str, web: PhotometricWeb) {
+        self.photometry.insert(name.to_owned(), web);
+    }
+
+    /// Adds a luminaire to the scene. An error is returned if its photometry is not in the scene.
+    pub fn add_luminaire(&mut self, luminaire: LuminaireInstance) -> Result<(), Error> {
+        if !self.photometry.contains_key(&luminaire.photometry) {
+            return Err(Error::UnknownPhotometry(luminaire.photometry));
+        }
+        self.luminaires.push(luminaire);
+        Ok(())
+    }
+
+    /// Adds each of the luminaires of an array to the scene.
+    pub fn add_array(&mut self, array: &LuminaireArray) -> Result<(), Error> {
+        array.instances().into_iter().try_for_each(|lum| self.add_luminaire(lum))
+    }
+
+    /// Computes the direct illuminance at a point on a surface with the given normal, summed over the luminaires.
+    pub fn illuminance_at(&self, point: &Vector3, normal: &Vector3) -> f64 {
+        self.luminaires
+            .iter()
+            .filter_map(|lum| {
+                self.photometry
+                    .get(&lum.photometry)
+                    .map(|web| lum.output_factor() * illuminance_at(web, &lum.pose(), point, normal))
+            })
+            .sum()
+    }
+
+    /// Computes the direct horizontal illuminance on a grid, summed over the luminaires.
+    pub fn illuminance_grid(&self, grid: &CalculationGrid) -> IlluminanceGrid {
+        IlluminanceGrid {
+            values: grid
+                .points()
+                .iter()
+                .map(|row| row.iter().map(|point| self.illuminance_at(point, &HORIZONTAL_NORMAL)).collect())
+                .collect(),
+            grid: grid.clone(),
+        }
+    }
+}
+
+/// The description of a scene as stored in a JSON or TOML file.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct SceneDescription {
+    /// The paths of the photometric files of the scene, by name.
+    #[serde(default)]
+    pub photometry: BTreeMap<String, PathBuf>,
+    /// The individual luminaires of the scene.
+    #[serde(default)]
+    pub luminaires: Vec<LuminaireInstance>,
+    /// The arrays of luminaires of the scene.
+    #[serde(default)]
+    pub arrays: Vec<LuminaireArray>,
+}
+
+#[cfg(feature = "serde")]
+impl SceneDescription {
+    /// Parses a scene description from a JSON string.
+    pub fn from_json_str(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Parses a scene description from a TOML string.
+    pub fn from_toml_str(toml: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Reads a scene description from a `.json` or `.toml` file.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json_str(&contents),
+            Some("toml") => Self::from_toml_str(&contents),
+            ext => Err(Error::InvalidFileType(ext.unwrap_or_default().to_owned())),
+        }
+    }
+
+    /// Builds the scene, using the given photometric webs in place of loading the photometric files.
+    pub fn build_with_photometry(&self, photometry: BTreeMap<String, PhotometricWeb>) -> Result<Scene, Error> {
+        let mut scene = Scene {
+            photometry,
+            luminaires: Vec::new(),
+        };
+        self.luminaires
+            .iter()
+            .try_for_each(|lum| scene.add_luminaire(lum.clone()))?;
+        self.arrays.iter().try_for_each(|array| scene.add_array(array))?;
+        Ok(scene)
+    }
+
+    /// Builds the scene, loading the photometric files relative to the given directory.
+    pub fn build(&self, base_dir: &Path) -> Result<Scene, Error> {
+        let photometry = self
+            .photometry
+            .iter()
+            .map(|(name, path)| {
+                PhotometricWebBuilder::from_file(&base_dir.join(path))
+                    .build()
+                    .map(|web| (name.clone(), web))
+            })
+            .collect::<Result<BTreeMap<String, PhotometricWeb>, Error>>()?;
+        self.build_with_photometry(photometry)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Scene {
+    /// Reads a scene from a `.json` or `.toml` description, loading the photometric files relative to the description.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        SceneDescription::from_file(path)?.build(base_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::{isotropic_web, rotational_web};
+    use approx::assert_abs_diff_eq;
+
+    /// A web with intensity only close to the nadir, to check the aiming of the luminaires.
+    fn spot_web() -> PhotometricWeb {
+        rotational_web(1.0, |gamma| if gamma <= degrees_to_radians(5.0) { 1000.0 } else { 0.0 })
+    }
+
+    #[test]
+    fn test_array_illuminance() {
+        let mut scene = Scene::new();
+        scene.add_photometry("iso", isotropic_web(1000.0));
+        let mut array = LuminaireArray::filling_room("iso", (4.0, 2.0), 2.0, (2, 1));
+        array.maintenance_factor = 0.8;
+        scene.add_array(&array).unwrap();
+        assert_eq!(scene.luminaires.len(), 2);
+        assert_eq!(scene.luminaires[1].position, Vector3::new(3.0, 1.0, 2.0));
+
+        // Directly below each luminaire, E = 0.8 * 1000 / 4 from that luminaire, plus 0.8 * 1000 cos / 8 from the other,
+        // 2 m away horizontally.
+        let cos_gamma = 2.0 / 8.0_f64.sqrt();
+        let expected = 0.8 * (250.0 + 1000.0 * cos_gamma / 8.0);
+        let point = Vector3::new(1.0, 1.0, 0.0);
+        assert_abs_diff_eq!(scene.illuminance_at(&point, &HORIZONTAL_NORMAL), expected, epsilon = 1E-9);
+
+        let grid = scene.illuminance_grid(&CalculationGrid::new((0.0, 0.0), 4.0, 2.0, 2, 1, 0.0));
+        assert_abs_diff_eq!(grid.min(), expected, epsilon = 1E-9);
+        assert_abs_diff_eq!(grid.u0(), 1.0, epsilon = 1E-9);
+
+        assert!(matches!(
+            scene.add_luminaire(LuminaireInstance::new("missing", point)),
+            Err(Error::UnknownPhotometry(name)) if name == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_aiming() {
+        let mut scene = Scene::new();
+        scene.add_photometry("spot", spot_web());
+        let target = Vector3::new(3.0, -4.0, 0.0);
+        scene
+            .add_luminaire(LuminaireInstance {
+                aim: Some(target),
+                dimming: 0.5,
+                ..LuminaireInstance::new("spot", Vector3::new(0.0, 0.0, 5.0))
+            })
+            .unwrap();
+
+        // The spot lands on the target, facing the luminaire at 50 m^2 distance, and misses the point below.
+        let normal = Vector3::new(0.0, 0.0, 5.0) - target;
+        assert_abs_diff_eq!(scene.illuminance_at(&target, &normal), 0.5 * 1000.0 / 50.0, epsilon = 1E-9);
+        assert_eq!(scene.illuminance_at(&Vector3::new(0.0, 0.0, 0.0), &HORIZONTAL_NORMAL), 0.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_description() {
+        let toml = r#"
+            [photometry]
+            iso = "iso.ldt"
+
+            [[luminaires]]
+            photometry = "iso"
+            position = { x = 1.0, y = 2.0, z = 3.0 }
+            dimming = 0.5
+
+            [[arrays]]
+            photometry = "iso"
+            origin = { x = 0.0, y = 0.0, z = 3.0 }
+            n_x = 3
+            n_y = 2
+            spacing_x = 2.0
+            spacing_y = 3.0
+        "#;
+        let desc = SceneDescription::from_toml_str(toml).unwrap();
+        assert_eq!(desc.luminaires[0].maintenance_factor, 1.0);
+        assert_eq!(desc.luminaires[0].dimming, 0.5);
+
+        let json = serde_json::to_string(&desc).unwrap();
+        assert_eq!(SceneDescription::from_json_str(&json).unwrap(), desc);
+
+        let photometry = BTreeMap::from([("iso".to_owned(), isotropic_web(1000.0))]);
+        let scene = desc.build_with_photometry(photometry).unwrap();
+        assert_eq!(scene.luminaires.len(), 7);
+        assert!(matches!(SceneDescription::from_json_str("{\"luminaires\": 1}"), Err(Error::JsonError(_))));
+        assert!(matches!(SceneDescription::from_toml_str("luminaires = 1"), Err(Error::TomlError(_))));
+    }
+}
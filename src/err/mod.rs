@@ -8,7 +8,12 @@ pub enum Error {
     LDTError(crate::io::eulumdat::Error),
     InvalidFileType(String),
     BuildError(Box<Error>),
-    OperationError(Box<crate::ops::err::Error>)
+    OperationError(Box<crate::ops::err::Error>),
+    UnknownPhotometry(String),
+    #[cfg(feature = "serde")]
+    JsonError(serde_json::Error),
+    #[cfg(feature = "serde")]
+    TomlError(toml::de::Error),
 }
 
 impl From<std::io::Error> for Error {
@@ -35,6 +40,20 @@ impl From<crate::ops::err::Error> for Error {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::JsonError(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::TomlError(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", {
@@ -45,6 +64,11 @@ impl Display for Error {
                 Error::BuildError(ref err) => format!("Photometric Web Build Error: {}", err),
                 Error::InvalidFileType(ref ext) => format!("Invalid file type: {}", ext),
                 Error::OperationError(ref e) => format!("Operation Error: {}", e),
+                Error::UnknownPhotometry(ref name) => format!("Scene Error: unknown photometry {}", name),
+                #[cfg(feature = "serde")]
+                Error::JsonError(ref e) => format!("Scene JSON Parse Error: {}", e),
+                #[cfg(feature = "serde")]
+                Error::TomlError(ref e) => format!("Scene TOML Parse Error: {}", e),
             }
         })
     }
@@ -1,9 +1,7 @@
 use super::err as ldt_err;
 use super::{util, EulumdatSymmetry, EulumdatType};
-use crate::photweb::{
-    mirror_first_hemisphere, mirror_first_quadrant, IntensityUnits, PhotometricWeb, Plane,
-    PlaneOrientation, mirror_second_and_third_quadrants,
-};
+use crate::photweb::{expand_symmetry, IntensityUnits, PhotometricWeb, Plane, PlaneOrientation};
+use crate::ops::tilt_photometric_web;
 use crate::util::geom::degrees_to_radians;
 use crate::{err::Error, photweb::PhotometricWebReader};
 use property::Property;
@@ -454,7 +452,7 @@ impl EulumdatFile {
 
     /// Gets the planes from this file.
     pub fn get_planes(&self) -> Vec<Plane> {
-        let mut planes: Vec<Plane> = self
+        let planes: Vec<Plane> = self
             .intensities
             .chunks(self.n_luminous_intensities_per_cplane)
             .zip(self.c_angles.iter())
@@ -481,24 +479,20 @@ impl EulumdatFile {
             .collect();
 
         // Now fill the planes if we have symmetry.
-        // Fill the last 180 degrees of the plane from the C0-C180 plane contents.
-        if self.symmetry == EulumdatSymmetry::C0C180C90C270Plane {
-            planes = mirror_first_quadrant(&planes);
-        }
-
-        // Fill the last 180 degrees of the plane from the C0-C180 plane contents.
-        if self.symmetry == EulumdatSymmetry::C0C180Plane
-            || self.symmetry == EulumdatSymmetry::C0C180C90C270Plane
-        {
-            planes = mirror_first_hemisphere(&planes);
-        }
+        expand_symmetry(&planes, self.symmetry.clone().into())
+    }
 
-        // On the other hand, let's generate the symmetry for the C90-C270 case.
-        if self.symmetry == EulumdatSymmetry::C90C270Plane {
-            planes = mirror_second_and_third_quadrants(&planes);
+    /// Returns the photometric web of the luminaire when installed at the given tilt (in degrees).
+    /// The measured distribution already includes the tilt during measurement (the `tilt` field),
+    /// so only the difference between the installed and measured tilt is applied.
+    pub fn get_photweb_at_tilt(&self, installed_tilt: f64) -> PhotometricWeb {
+        let mut photweb = PhotometricWeb::new();
+        photweb.set_planes(self.get_planes());
+        if installed_tilt == self.tilt {
+            photweb
+        } else {
+            tilt_photometric_web(&photweb, degrees_to_radians(installed_tilt - self.tilt))
         }
-
-        planes
     }
 }
 
@@ -1,4 +1,5 @@
 use num_enum::TryFromPrimitive;
+use crate::photweb::Symmetry;
 
 #[derive(Debug, Clone, TryFromPrimitive, PartialEq)]
 #[repr(usize)]
@@ -15,3 +16,27 @@ impl Default for EulumdatSymmetry {
         EulumdatSymmetry::NoSymmetry
     }
 }
+
+impl From<EulumdatSymmetry> for Symmetry {
+    fn from(symmetry: EulumdatSymmetry) -> Self {
+        match symmetry {
+            EulumdatSymmetry::NoSymmetry => Symmetry::None,
+            EulumdatSymmetry::AboutVerticalAxis => Symmetry::Rotational,
+            EulumdatSymmetry::C0C180Plane => Symmetry::C0C180,
+            EulumdatSymmetry::C90C270Plane => Symmetry::C90C270,
+            EulumdatSymmetry::C0C180C90C270Plane => Symmetry::Quadrant,
+        }
+    }
+}
+
+impl From<Symmetry> for EulumdatSymmetry {
+    fn from(symmetry: Symmetry) -> Self {
+        match symmetry {
+            Symmetry::None => EulumdatSymmetry::NoSymmetry,
+            Symmetry::Rotational => EulumdatSymmetry::AboutVerticalAxis,
+            Symmetry::C0C180 => EulumdatSymmetry::C0C180Plane,
+            Symmetry::C90C270 => EulumdatSymmetry::C90C270Plane,
+            Symmetry::Quadrant => EulumdatSymmetry::C0C180C90C270Plane,
+        }
+    }
+}
@@ -252,3 +252,32 @@ fn test_get_planes_spherical_symmetry() {
         vec![1.0]
     );
 }
+
+/// Check that the tilt during measurement is taken into account when installing the luminaire at a tilt.
+#[test]
+fn test_get_photweb_at_tilt() {
+    let mut ldt = EulumdatFile::new();
+    ldt.set_n_cplanes(1_usize);
+    ldt.set_c_angles(vec![0.0]);
+    ldt.set_g_angles((0..=180).map(|ang| ang as f64).collect::<Vec<f64>>());
+    ldt.set_n_luminous_intensities_per_cplane(181_usize);
+    ldt.set_intensities(
+        (0..=180)
+            .map(|ang| f64::max(0.0, (ang as f64).to_radians().cos()))
+            .collect::<Vec<f64>>(),
+    );
+    ldt.set_symmetry(EulumdatSymmetry::AboutVerticalAxis);
+    ldt.set_tilt(5.0);
+
+    // Installing at the measured tilt leaves the web untouched.
+    let untilted = ldt.get_photweb_at_tilt(5.0);
+    assert_eq!(untilted.n_planes(), 1);
+
+    // Installing at 15 degrees tilts the peak a further 10 degrees towards C90.
+    let tilted = ldt.get_photweb_at_tilt(15.0);
+    assert_relative_eq!(
+        tilted.intensity_at(90f64.to_radians(), 10f64.to_radians()),
+        1.0,
+        epsilon = 1E-3
+    );
+}
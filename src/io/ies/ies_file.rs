@@ -1,7 +1,7 @@
 use super::err as ies_err;
 use super::lum_opening::IesLuminousOpening;
 use super::{phot_type::IesPhotometryType, standard::IesStandard, tilt::Tilt};
-use crate::photweb::{Plane, mirror_first_quadrant, mirror_first_hemisphere};
+use crate::photweb::{expand_symmetry, Plane, Symmetry};
 use crate::{
     err::Error,
     photweb::{IntensityUnits, PhotometricWeb, PhotometricWebReader, PlaneOrientation},
@@ -15,7 +15,6 @@ use std::{
     io::{BufReader, Read, Write},
     path::Path,
     rc::Rc,
-    f64::consts::{PI}
 };
 
 pub const DELIMITERS_PATTERN: &str = "[ ]+|,|[\r\n]";
@@ -569,13 +568,85 @@ impl IesFile {
     }
 
     /// Get the planes from a Type A photometry IES file.
+    /// Each horizontal angle defines an A-plane, hinged on the axis along the luminaire, containing the vertical angles.
+    /// If only the positive horizontal angles are provided, the distribution is mirrored to the negative angles.
     pub fn get_planes_type_a(&self) -> Vec<Plane> {
-        todo!()
+        let planes = self
+            .candela_values
+            .chunks(self.n_vertical_angles)
+            .zip(self.horizontal_angles.iter())
+            .map(|(intensities_candelas, h_angle)| {
+                let mut curr_plane = Plane::new();
+                curr_plane.set_angle_degrees(*h_angle);
+                curr_plane.set_orientation(PlaneOrientation::Longitudinal);
+                curr_plane.set_intensities(Vec::from(intensities_candelas));
+                curr_plane.set_angles_degrees(&self.vertical_angles);
+                curr_plane.set_units(IntensityUnits::Candela);
+                curr_plane
+            })
+            .collect::<Vec<Plane>>();
+
+        if self.horizontal_angles.first() == Some(&0.0) {
+            // Mirror the planes about the H = 0 plane.
+            planes
+                .iter()
+                .skip(1)
+                .rev()
+                .map(|pl| {
+                    let mut new_plane = pl.clone();
+                    new_plane.set_angle(-pl.angle());
+                    new_plane
+                })
+                .chain(planes.iter().cloned())
+                .collect()
+        } else {
+            planes
+        }
     }
 
-    /// Get the planes from a Type A photometry IES file.
+    /// Get the planes from a Type B photometry IES file.
+    /// The file lists the intensities for each horizontal angle, so these are transposed into B-planes, one for each
+    /// vertical angle, hinged on the axis across the luminaire and containing the horizontal angles.
+    /// If only the positive horizontal angles are provided, the distribution is mirrored to the negative angles.
     pub fn get_planes_type_b(&self) -> Vec<Plane> {
-        todo!()
+        let mirror = self.horizontal_angles.first() == Some(&0.0);
+        let h_angles: Vec<f64> = self
+            .horizontal_angles
+            .iter()
+            .skip(1)
+            .rev()
+            .filter(|_| mirror)
+            .map(|ang| -ang)
+            .chain(self.horizontal_angles.iter().cloned())
+            .collect();
+
+        self.vertical_angles
+            .iter()
+            .enumerate()
+            .map(|(iv, v_angle)| {
+                let intensities: Vec<f64> = self
+                    .candela_values
+                    .chunks(self.n_vertical_angles)
+                    .map(|intensities_candelas| intensities_candelas[iv])
+                    .collect();
+                let mut curr_plane = Plane::new();
+                curr_plane.set_angle_degrees(*v_angle);
+                curr_plane.set_orientation(PlaneOrientation::Horizontal);
+                curr_plane.set_intensities(
+                    intensities
+                        .iter()
+                        .skip(1)
+                        .rev()
+                        .filter(|_| mirror)
+                        .chain(intensities.iter())
+                        .cloned()
+                        .collect::<Vec<f64>>(),
+                );
+                curr_plane.set_angles_degrees(&h_angles);
+                curr_plane.set_units(IntensityUnits::Candela);
+                curr_plane
+            })
+            .collect()
     }
 
     /// Get the planes from a Type C photometry IES file.
@@ -596,18 +667,9 @@ impl IesFile {
             })
             .collect::<Vec<Plane>>();
         
-        // Now resolve the symmetries.
-        // First, check if we have the first quadrant filled (from 0 -> 90 deg).
-        // If so, mirror this to fill the 0 -> 180 degree hemisphere. 
-        if (planes.iter().last().unwrap().angle() - (PI / 2.0)).abs() <= f64::EPSILON {
-            planes = mirror_first_quadrant(&planes);
-        }
-
-        // Now, check to see if we have the first hemisphere (0 -> 180 deg). 
-        // If so mirror this to fill the final hemisphere.
-        if (planes.iter().last().unwrap().angle() - (PI)).abs() <= f64::EPSILON {
-            planes = mirror_first_hemisphere(&planes);
-        }
+        // Now resolve the symmetries, which are implied by the range of horizontal angles.
+        let plane_angles = planes.iter().map(|pl| pl.angle()).collect::<Vec<f64>>();
+        planes = expand_symmetry(&planes, Symmetry::from_plane_angles(&plane_angles));
 
         planes
     }
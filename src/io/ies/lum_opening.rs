@@ -159,6 +159,94 @@ impl IesLuminousOpening {
             }
         }
     }
+
+    /// Returns the luminous opening with all of its dimensions multiplied by the given factor, for example to convert
+    /// from feet to metres.
+    pub fn scaled(&self, factor: f64) -> Self {
+        match *self {
+            Self::Point => Self::Point,
+            Self::Rectangular { width, length } => Self::Rectangular {
+                width: factor * width,
+                length: factor * length,
+            },
+            Self::RectanguarLuminousSides {
+                width,
+                length,
+                height,
+            } => Self::RectanguarLuminousSides {
+                width: factor * width,
+                length: factor * length,
+                height: factor * height,
+            },
+            Self::Circular { diameter } => Self::Circular {
+                diameter: factor * diameter,
+            },
+            Self::Ellipse { width, length } => Self::Ellipse {
+                width: factor * width,
+                length: factor * length,
+            },
+            Self::VerticalCylinder { diameter, height } => Self::VerticalCylinder {
+                diameter: factor * diameter,
+                height: factor * height,
+            },
+            Self::VerticalEllipsoidalCylinder {
+                width,
+                length,
+                height,
+            } => Self::VerticalEllipsoidalCylinder {
+                width: factor * width,
+                length: factor * length,
+                height: factor * height,
+            },
+            Self::Sphere { diameter } => Self::Sphere {
+                diameter: factor * diameter,
+            },
+            Self::EllipsoidalSpheroid {
+                width,
+                length,
+                height,
+            } => Self::EllipsoidalSpheroid {
+                width: factor * width,
+                length: factor * length,
+                height: factor * height,
+            },
+            Self::HorizontalCylinderAlong { diameter, length } => Self::HorizontalCylinderAlong {
+                diameter: factor * diameter,
+                length: factor * length,
+            },
+            Self::HorizontalEllipsoidalCylinderAlong {
+                width,
+                length,
+                height,
+            } => Self::HorizontalEllipsoidalCylinderAlong {
+                width: factor * width,
+                length: factor * length,
+                height: factor * height,
+            },
+            Self::HorizontalCylinderPerpendicular { width, diameter } => {
+                Self::HorizontalCylinderPerpendicular {
+                    width: factor * width,
+                    diameter: factor * diameter,
+                }
+            }
+            Self::HorizontalEllipsoidalCylinderPerpendicular {
+                width,
+                length,
+                height,
+            } => Self::HorizontalEllipsoidalCylinderPerpendicular {
+                width: factor * width,
+                length: factor * length,
+                height: factor * height,
+            },
+            Self::VerticalCircle { diameter } => Self::VerticalCircle {
+                diameter: factor * diameter,
+            },
+            Self::VerticalEllipse { width, height } => Self::VerticalEllipse {
+                width: factor * width,
+                height: factor * height,
+            },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,8 @@
-use crate::{io::ies::lum_opening::IesLuminousOpening, photweb::PhotometricWeb};
+use crate::{
+    io::ies::lum_opening::IesLuminousOpening,
+    photweb::{PhotometricWeb, PlaneOrientation},
+    util::geom::Vector3,
+};
 
 use super::{IesFile, LuminousOpeningUnits};
 
@@ -367,3 +371,55 @@ fn test_photweb_from_ies_typec() {
         }
     }
 }
+
+const TYPE_B_FILE: &str = "IESNA:LM-63-2002
+[TEST] Simple floodlight
+TILT=NONE
+1 1000 1 3 2 2 2 0.0 0.0 0.0
+1.0 1.0 100
+-10 0 10
+0 10
+50 100 50
+20 40 20
+";
+
+/// Check that a Type B file is transposed into B-planes (one for each vertical angle),
+/// and that the symmetric horizontal angles are mirrored.
+#[test]
+fn test_photweb_from_ies_typeb() {
+    let mut ies = IesFile::new();
+    match ies.parse(&TYPE_B_FILE.to_owned()) {
+        Err(e) => panic!("Parse error: {}", e),
+        Ok(_) => {
+            let photweb: PhotometricWeb = ies.clone().into();
+            assert_eq!(photweb.n_planes(), 3);
+            assert_eq!(photweb.orientation(), PlaneOrientation::Horizontal);
+            assert_eq!(photweb.planes()[1].angles_deg(), vec![-10.0, 0.0, 10.0]);
+            assert_eq!(photweb.planes()[1].intensities(), vec![40.0, 100.0, 40.0]);
+            assert_eq!(photweb.planes()[0].intensities(), vec![20.0, 50.0, 20.0]);
+
+            // The peak should be found in the direction of the nadir.
+            assert_eq!(photweb.intensity_towards(&Vector3::new(0.0, 0.0, -1.0)), 100.0);
+        }
+    }
+}
+
+/// Check that a Type A file keeps an A-plane for each horizontal angle, mirrored about H = 0.
+#[test]
+fn test_photweb_from_ies_typea() {
+    let mut ies = IesFile::new();
+    match ies.parse(&TYPE_B_FILE.replace("1 1000 1 3 2 2", "1 1000 1 3 2 3")) {
+        Err(e) => panic!("Parse error: {}", e),
+        Ok(_) => {
+            let photweb: PhotometricWeb = ies.clone().into();
+            assert_eq!(photweb.n_planes(), 3);
+            assert_eq!(photweb.orientation(), PlaneOrientation::Longitudinal);
+            assert_eq!(
+                photweb.planes().iter().map(|pl| pl.angle_deg().round()).collect::<Vec<f64>>(),
+                vec![-10.0, 0.0, 10.0]
+            );
+            assert_eq!(photweb.planes()[0].intensities(), vec![20.0, 40.0, 20.0]);
+            assert_eq!(photweb.intensity_towards(&Vector3::new(0.0, 0.0, -1.0)), 100.0);
+        }
+    }
+}
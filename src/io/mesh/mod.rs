@@ -0,0 +1,66 @@
+//! Export of the photometric solid of a web as a 3D mesh.
+//!
+//! The photometric solid is the surface whose distance from the luminaire in each direction is proportional to the
+//! intensity in that direction. It is triangulated into a closed mesh, which can be written as Wavefront OBJ, PLY
+//! (ASCII or binary) or STL (ASCII or binary) without any external dependencies.
+
+pub mod photometric_mesh;
+mod obj;
+mod ply;
+mod stl;
+pub use photometric_mesh::*;
+
+use crate::err::Error;
+use std::{fs::File, io::BufWriter, io::Write, path::Path};
+
+/// The file formats a mesh can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    /// Wavefront OBJ, with vertex normals and colours.
+    Obj,
+    /// ASCII PLY, with vertex normals and colours.
+    PlyAscii,
+    /// Binary (little endian) PLY, with vertex normals and colours.
+    PlyBinary,
+    /// ASCII STL, with face normals only.
+    StlAscii,
+    /// Binary STL, with face normals only.
+    StlBinary,
+}
+
+impl MeshFormat {
+    /// Returns the format for the extension of a file path, using the binary variants of PLY and STL.
+    /// Returns an `Error::InvalidFileType` error if the extension is not `obj`, `ply` or `stl`.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("obj") => Ok(Self::Obj),
+            Some("ply") => Ok(Self::PlyBinary),
+            Some("stl") => Ok(Self::StlBinary),
+            ext => Err(Error::InvalidFileType(ext.unwrap_or_default().to_owned())),
+        }
+    }
+}
+
+impl PhotometricMesh {
+    /// Writes the mesh in the given format.
+    pub fn write(&self, writer: &mut impl Write, format: MeshFormat) -> std::io::Result<()> {
+        match format {
+            MeshFormat::Obj => self.write_obj(writer),
+            MeshFormat::PlyAscii => self.write_ply(writer, false),
+            MeshFormat::PlyBinary => self.write_ply(writer, true),
+            MeshFormat::StlAscii => self.write_stl(writer, false),
+            MeshFormat::StlBinary => self.write_stl(writer, true),
+        }
+    }
+
+    /// Writes the mesh to a file in the given format.
+    pub fn write_to_file(&self, path: &Path, format: MeshFormat) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,26 @@
+use super::PhotometricMesh;
+use std::io::Write;
+
+impl PhotometricMesh {
+    /// Writes the mesh as a Wavefront OBJ file, with the vertex colours (0 to 1) following the positions of the vertices
+    /// where the mesh is coloured.
+    pub fn write_obj(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(writer, "# Photometric solid")?;
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            write!(writer, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+            if let Some(colour) = self.colours.as_ref().and_then(|colours| colours.get(i)) {
+                let [r, g, b] = colour.map(|channel| channel as f64 / 255.0);
+                write!(writer, " {:.4} {:.4} {:.4}", r, g, b)?;
+            }
+            writeln!(writer)?;
+        }
+        for normal in &self.normals {
+            writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+        }
+        // OBJ indices start at 1.
+        for [a, b, c] in &self.triangles {
+            writeln!(writer, "f {a}//{a} {b}//{b} {c}//{c}", a = a + 1, b = b + 1, c = c + 1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    photweb::PhotometricWeb,
+    util::geom::{degrees_to_radians, Vector3},
+};
+
+/// The colours (RGB) along the ramp used to colour the vertices by intensity, from zero to the peak intensity.
+const COLOUR_RAMP: [[f64; 3]; 5] = [
+    [68.0, 1.0, 84.0],
+    [59.0, 82.0, 139.0],
+    [33.0, 145.0, 140.0],
+    [94.0, 201.0, 98.0],
+    [253.0, 231.0, 37.0],
+];
+
+/// The options used when triangulating a photometric web into a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshOptions {
+    /// The angle (in degrees) between the C-planes of the mesh.
+    pub c_step: f64,
+    /// The angle (in degrees) between the gamma angles of the mesh.
+    pub gamma_step: f64,
+    /// The radius of the peak intensity, or `None` for a radius equal to the intensity.
+    pub peak_radius: Option<f64>,
+    /// Whether to colour the vertices by their intensity.
+    pub colours: bool,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self {
+            c_step: 5.0,
+            gamma_step: 2.5,
+            peak_radius: Some(1.0),
+            colours: true,
+        }
+    }
+}
+
+/// A closed triangle mesh of the photometric solid of a web, in which the distance of each vertex from the origin is
+/// proportional to the intensity in its direction. The mesh uses the luminaire coordinate system, with z upwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotometricMesh {
+    /// The positions of the vertices.
+    pub vertices: Vec<Vector3>,
+    /// The unit normals of the vertices, averaged over the triangles around each vertex.
+    pub normals: Vec<Vector3>,
+    /// The colours (RGB) of the vertices, if coloured by intensity.
+    pub colours: Option<Vec<[u8; 3]>>,
+    /// The vertex indices of the triangles, ordered anti-clockwise when seen from outside of the mesh.
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl PhotometricMesh {
+    /// Triangulates a web into a closed mesh on a regular grid of C and gamma angles, with a single vertex at each pole.
+    /// Spherically symmetric webs give a sphere, and directions without intensity collapse onto the origin.
+    pub fn new(web: &PhotometricWeb, options: &MeshOptions) -> Self {
+        let n_c = ((360.0 / options.c_step).round() as usize).max(3);
+        let n_gamma = ((180.0 / options.gamma_step).round() as usize).max(2);
+        let directions: Vec<Vector3> = std::iter::once(Vector3::from_c_gamma(0.0, 0.0))
+            .chain((1..n_gamma).flat_map(|ig| {
+                let gamma = degrees_to_radians(180.0 * ig as f64 / n_gamma as f64);
+                (0..n_c).map(move |ic| Vector3::from_c_gamma(degrees_to_radians(360.0 * ic as f64 / n_c as f64), gamma))
+            }))
+            .chain(std::iter::once(Vector3::from_c_gamma(0.0, std::f64::consts::PI)))
+            .collect();
+
+        let intensities: Vec<f64> = directions.iter().map(|dir| web.intensity_towards(dir).max(0.0)).collect();
+        let peak = intensities.iter().cloned().fold(0.0, f64::max);
+        let scale = match options.peak_radius {
+            Some(radius) if peak > 0.0 => radius / peak,
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+        let vertices: Vec<Vector3> = directions
+            .iter()
+            .zip(&intensities)
+            .map(|(dir, intensity)| *dir * (scale * intensity))
+            .collect();
+
+        // Vertex 0 is the nadir, followed by rings of n_c vertices for each gamma, and finally the zenith.
+        let ring = |ig: usize, ic: usize| 1 + (ig - 1) * n_c + ic % n_c;
+        let zenith = directions.len() - 1;
+        let mut triangles = Vec::with_capacity(2 * n_c * (n_gamma - 1));
+        for ic in 0..n_c {
+            triangles.push([0, ring(1, ic), ring(1, ic + 1)]);
+            for ig in 1..n_gamma - 1 {
+                triangles.push([ring(ig, ic), ring(ig + 1, ic), ring(ig + 1, ic + 1)]);
+                triangles.push([ring(ig, ic), ring(ig + 1, ic + 1), ring(ig, ic + 1)]);
+            }
+            triangles.push([ring(n_gamma - 1, ic), zenith, ring(n_gamma - 1, ic + 1)]);
+        }
+        // The winding is fixed on the unit sphere, where every triangle is well formed, so that it also holds where
+        // the solid is concave or collapses onto the origin.
+        for triangle in triangles.iter_mut() {
+            let [a, b, c] = triangle.map(|i| directions[i]);
+            if (b - a).cross(&(c - a)).dot(&(a + b + c)) < 0.0 {
+                triangle.swap(1, 2);
+            }
+        }
+
+        let mut normals = vec![Vector3::default(); vertices.len()];
+        for triangle in &triangles {
+            let [a, b, c] = triangle.map(|i| vertices[i]);
+            // The cross product is weighted by the area of the triangle.
+            let face = (b - a).cross(&(c - a));
+            for i in triangle {
+                normals[*i] = normals[*i] + face;
+            }
+        }
+        let normals = normals
+            .iter()
+            .zip(&directions)
+            .map(|(normal, dir)| if normal.norm() > 0.0 { normal.normalise() } else { *dir })
+            .collect();
+
+        let colours = options.colours.then(|| {
+            intensities
+                .iter()
+                .map(|intensity| intensity_colour(if peak > 0.0 { intensity / peak } else { 0.0 }))
+                .collect()
+        });
+
+        Self {
+            vertices,
+            normals,
+            colours,
+            triangles,
+        }
+    }
+
+    /// Returns the unit normal of a triangle, or a zero vector if the triangle is degenerate.
+    pub fn triangle_normal(&self, triangle: &[usize; 3]) -> Vector3 {
+        let [a, b, c] = triangle.map(|i| self.vertices[i]);
+        (b - a).cross(&(c - a)).normalise()
+    }
+}
+
+/// Returns the colour of a fraction (0 to 1) of the peak intensity, interpolated along the colour ramp.
+fn intensity_colour(fraction: f64) -> [u8; 3] {
+    let position = fraction.clamp(0.0, 1.0) * (COLOUR_RAMP.len() - 1) as f64;
+    let lower = (position.floor() as usize).min(COLOUR_RAMP.len() - 2);
+    let frac = position - lower as f64;
+    let channel = |i: usize| ((1.0 - frac) * COLOUR_RAMP[lower][i] + frac * COLOUR_RAMP[lower + 1][i]).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
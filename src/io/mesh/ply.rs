@@ -0,0 +1,56 @@
+use super::PhotometricMesh;
+use std::io::Write;
+
+impl PhotometricMesh {
+    /// Writes the mesh as a PLY file, either in ASCII or in binary (little endian), with the normals and, where the
+    /// mesh is coloured, the colours of the vertices.
+    pub fn write_ply(&self, writer: &mut impl Write, binary: bool) -> std::io::Result<()> {
+        let format = if binary { "binary_little_endian" } else { "ascii" };
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format {} 1.0", format)?;
+        writeln!(writer, "comment Photometric solid")?;
+        writeln!(writer, "element vertex {}", self.vertices.len())?;
+        for property in ["x", "y", "z", "nx", "ny", "nz"] {
+            writeln!(writer, "property float {}", property)?;
+        }
+        if self.colours.is_some() {
+            for property in ["red", "green", "blue"] {
+                writeln!(writer, "property uchar {}", property)?;
+            }
+        }
+        writeln!(writer, "element face {}", self.triangles.len())?;
+        writeln!(writer, "property list uchar int vertex_indices")?;
+        writeln!(writer, "end_header")?;
+
+        for (i, (vertex, normal)) in self.vertices.iter().zip(&self.normals).enumerate() {
+            let values = [vertex.x, vertex.y, vertex.z, normal.x, normal.y, normal.z].map(|v| v as f32);
+            let colour = self.colours.as_ref().map(|colours| colours[i]);
+            if binary {
+                for value in values {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+                if let Some(colour) = colour {
+                    writer.write_all(&colour)?;
+                }
+            } else {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(writer, "{}", values.join(" "))?;
+                if let Some([r, g, b]) = colour {
+                    write!(writer, " {} {} {}", r, g, b)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        for triangle in &self.triangles {
+            if binary {
+                writer.write_all(&[3])?;
+                for i in triangle {
+                    writer.write_all(&(*i as i32).to_le_bytes())?;
+                }
+            } else {
+                writeln!(writer, "3 {} {} {}", triangle[0], triangle[1], triangle[2])?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use super::PhotometricMesh;
+use std::io::Write;
+
+impl PhotometricMesh {
+    /// Writes the mesh as an STL file, either in ASCII or in binary. STL has no shared vertices, vertex normals or
+    /// colours, so each triangle is written with its own face normal.
+    pub fn write_stl(&self, writer: &mut impl Write, binary: bool) -> std::io::Result<()> {
+        if binary {
+            let mut header = [0u8; 80];
+            let title = b"Photometric solid";
+            header[..title.len()].copy_from_slice(title);
+            writer.write_all(&header)?;
+            writer.write_all(&(self.triangles.len() as u32).to_le_bytes())?;
+            for triangle in &self.triangles {
+                let normal = self.triangle_normal(triangle);
+                let points = std::iter::once(normal).chain(triangle.iter().map(|i| self.vertices[*i]));
+                for point in points {
+                    for value in [point.x, point.y, point.z] {
+                        writer.write_all(&(value as f32).to_le_bytes())?;
+                    }
+                }
+                // The attribute byte count, which is unused.
+                writer.write_all(&[0, 0])?;
+            }
+        } else {
+            writeln!(writer, "solid photometric_solid")?;
+            for triangle in &self.triangles {
+                let normal = self.triangle_normal(triangle);
+                writeln!(writer, "  facet normal {} {} {}", normal.x as f32, normal.y as f32, normal.z as f32)?;
+                writeln!(writer, "    outer loop")?;
+                for i in triangle {
+                    let vertex = self.vertices[*i];
+                    writeln!(writer, "      vertex {} {} {}", vertex.x as f32, vertex.y as f32, vertex.z as f32)?;
+                }
+                writeln!(writer, "    endloop")?;
+                writeln!(writer, "  endfacet")?;
+            }
+            writeln!(writer, "endsolid photometric_solid")?;
+        }
+        Ok(())
+    }
+}
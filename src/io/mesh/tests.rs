@@ -0,0 +1,151 @@
+use approx::assert_abs_diff_eq;
+use std::{collections::HashMap, f64::consts::FRAC_PI_2};
+
+use crate::photweb::{
+    test_webs::{isotropic_web, planar_web},
+    PhotometricWeb,
+};
+
+use super::{MeshFormat, MeshOptions, PhotometricMesh};
+
+/// A downlight, wider in the C90-C270 planes, with no upward light.
+fn downlight() -> PhotometricWeb {
+    planar_web(90.0, 5.0, |c_angle, gamma| {
+        let sigma = if (c_angle / FRAC_PI_2).round() as i32 % 2 == 0 { 20.0_f64 } else { 35.0 };
+        if gamma < FRAC_PI_2 {
+            1000.0 * (-0.5 * (gamma.to_degrees() / sigma).powi(2)).exp()
+        } else {
+            0.0
+        }
+    })
+}
+
+/// The number of uses of each edge, keyed by the indices of its vertices.
+type EdgeCounts = HashMap<(usize, usize), usize>;
+
+/// Returns the number of triangles on each (undirected) edge, and the number of times each directed edge is used.
+fn edge_counts(mesh: &PhotometricMesh) -> (EdgeCounts, EdgeCounts) {
+    let mut undirected = HashMap::new();
+    let mut directed = HashMap::new();
+    for [a, b, c] in &mesh.triangles {
+        for (p, q) in [(*a, *b), (*b, *c), (*c, *a)] {
+            *undirected.entry((p.min(q), p.max(q))).or_insert(0) += 1;
+            *directed.entry((p, q)).or_insert(0) += 1;
+        }
+    }
+    (undirected, directed)
+}
+
+#[test]
+fn test_mesh_is_closed() {
+    let options = MeshOptions {
+        c_step: 10.0,
+        gamma_step: 10.0,
+        ..MeshOptions::default()
+    };
+    let mesh = PhotometricMesh::new(&downlight(), &options);
+    assert_eq!(mesh.vertices.len(), 2 + 36 * 17);
+    assert_eq!(mesh.normals.len(), mesh.vertices.len());
+    assert_eq!(mesh.triangles.len(), 2 * 36 * 17);
+    assert_eq!(mesh.colours.as_ref().map(|colours| colours.len()), Some(mesh.vertices.len()));
+
+    // Every edge is shared by two triangles, which use it in opposite directions.
+    let (undirected, directed) = edge_counts(&mesh);
+    assert!(undirected.values().all(|count| *count == 2));
+    assert!(directed.values().all(|count| *count == 1));
+}
+
+#[test]
+fn test_mesh_scaling() {
+    let web = downlight();
+    let mesh = PhotometricMesh::new(&web, &MeshOptions::default());
+    assert_abs_diff_eq!(mesh.vertices[0].z, -1.0, epsilon = 1E-9);
+    assert_abs_diff_eq!(mesh.vertices.last().unwrap().norm(), 0.0, epsilon = 1E-9);
+    assert_eq!(mesh.colours.as_ref().unwrap()[0], [253, 231, 37]);
+
+    let options = MeshOptions {
+        peak_radius: None,
+        colours: false,
+        ..MeshOptions::default()
+    };
+    let mesh = PhotometricMesh::new(&web, &options);
+    assert_abs_diff_eq!(mesh.vertices[0].z, -1000.0, epsilon = 1E-6);
+    assert!(mesh.colours.is_none());
+}
+
+#[test]
+fn test_spherically_symmetric_mesh() {
+    let web = isotropic_web(500.0);
+    let mesh = PhotometricMesh::new(&web, &MeshOptions::default());
+    for (vertex, normal) in mesh.vertices.iter().zip(&mesh.normals) {
+        assert_abs_diff_eq!(vertex.norm(), 1.0, epsilon = 1E-9);
+        // The normals of a sphere point away from its centre.
+        assert!(vertex.dot(normal) > 0.99);
+    }
+    // The triangles face outwards.
+    for triangle in &mesh.triangles {
+        let centre = triangle.iter().fold(crate::util::geom::Vector3::default(), |acc, i| acc + mesh.vertices[*i]);
+        assert!(mesh.triangle_normal(triangle).dot(&centre) > 0.0);
+    }
+}
+
+#[test]
+fn test_write_obj() {
+    let options = MeshOptions {
+        c_step: 90.0,
+        gamma_step: 90.0,
+        ..MeshOptions::default()
+    };
+    let mesh = PhotometricMesh::new(&isotropic_web(100.0), &options);
+    let mut buf = Vec::new();
+    mesh.write(&mut buf, MeshFormat::Obj).unwrap();
+    let obj = String::from_utf8(buf).unwrap();
+    assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 6);
+    assert_eq!(obj.lines().filter(|line| line.starts_with("vn ")).count(), 6);
+    assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 8);
+    assert!(obj.lines().any(|line| line == "v 0 0 -1 0.9922 0.9059 0.1451"));
+}
+
+#[test]
+fn test_write_ply() {
+    let mesh = PhotometricMesh::new(&downlight(), &MeshOptions::default());
+    let mut ascii = Vec::new();
+    mesh.write(&mut ascii, MeshFormat::PlyAscii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    let header_end = ascii.find("end_header\n").unwrap() + "end_header\n".len();
+    assert!(ascii.starts_with("ply\nformat ascii 1.0\n"));
+    assert!(ascii.contains(&format!("element vertex {}\n", mesh.vertices.len())));
+    assert!(ascii.contains("property uchar red\n"));
+    assert_eq!(ascii[header_end..].lines().count(), mesh.vertices.len() + mesh.triangles.len());
+
+    let mut binary = Vec::new();
+    mesh.write(&mut binary, MeshFormat::PlyBinary).unwrap();
+    let header = ascii[..header_end].replace("format ascii", "format binary_little_endian");
+    assert!(binary.starts_with(header.as_bytes()));
+    assert_eq!(binary.len(), header.len() + 27 * mesh.vertices.len() + 13 * mesh.triangles.len());
+}
+
+#[test]
+fn test_write_stl() {
+    let mesh = PhotometricMesh::new(&downlight(), &MeshOptions::default());
+    let mut binary = Vec::new();
+    mesh.write(&mut binary, MeshFormat::StlBinary).unwrap();
+    assert_eq!(binary.len(), 84 + 50 * mesh.triangles.len());
+    assert_eq!(u32::from_le_bytes(binary[80..84].try_into().unwrap()) as usize, mesh.triangles.len());
+
+    let mut ascii = Vec::new();
+    mesh.write(&mut ascii, MeshFormat::StlAscii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    assert!(ascii.starts_with("solid photometric_solid\n"));
+    assert!(ascii.ends_with("endsolid photometric_solid\n"));
+    assert_eq!(ascii.matches("facet normal").count(), mesh.triangles.len());
+}
+
+#[test]
+fn test_mesh_format_from_path() {
+    use std::path::Path;
+    assert_eq!(MeshFormat::from_path(Path::new("solid.obj")).unwrap(), MeshFormat::Obj);
+    assert_eq!(MeshFormat::from_path(Path::new("solid.ply")).unwrap(), MeshFormat::PlyBinary);
+    assert_eq!(MeshFormat::from_path(Path::new("solid.stl")).unwrap(), MeshFormat::StlBinary);
+    assert!(MeshFormat::from_path(Path::new("solid.dxf")).is_err());
+}
@@ -3,3 +3,4 @@
 // Available photometric file formats.
 pub mod eulumdat;
 pub mod ies;
+pub mod mesh;
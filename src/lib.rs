@@ -8,8 +8,11 @@
 //! It is also able to interpret these files to produce a full spherical photometric web from the inputs.
 //!
 
+pub mod analysis;
+pub mod calc;
 pub mod err;
 pub mod io;
 pub mod ops;
 pub mod photweb;
+pub mod plot;
 pub mod util;
@@ -0,0 +1,295 @@
+//! Arithmetic operations on photometric webs.
+//!
+//! These operations allow webs to be scaled, normalised and combined. Where two webs are combined and their
+//! grids differ, the lower resolution web is resampled onto the grid of the higher resolution web.
+
+use crate::{
+    ops::err::Error,
+    photweb::{IntensityUnits, PhotometricWeb, Plane},
+};
+
+/// Returns a copy of the photometric web with each of the intensities multiplied by a constant factor.
+/// This is useful for modelling dimmed output, or re-rating a web for a lamp with a different output.
+pub fn scale_photometric_web(web: &PhotometricWeb, factor: f64) -> PhotometricWeb {
+    map_photometric_web(web, web.units(), |int| int * factor)
+}
+
+/// Scales a photometric web such that the total flux emitted, as given by `PhotometricWeb::total_intensity`,
+/// is equal to the target flux. An error is returned if the web does not emit any flux.
+pub fn normalise_photometric_web_to_flux(
+    web: &PhotometricWeb,
+    target_flux: f64,
+) -> Result<PhotometricWeb, Error> {
+    if web.n_planes() == 0 {
+        return Err(Error::NoPlanes);
+    }
+
+    let flux = web.total_intensity();
+    if flux <= 0.0 {
+        return Err(Error::ZeroFlux);
+    }
+    Ok(scale_photometric_web(web, target_flux / flux))
+}
+
+/// Normalises a photometric web such that its peak intensity is 1.0.
+/// As the resulting intensities are dimensionless, the planes are given relative units.
+pub fn normalise_photometric_web_to_peak(web: &PhotometricWeb) -> Result<PhotometricWeb, Error> {
+    if web.n_planes() == 0 {
+        return Err(Error::NoPlanes);
+    }
+
+    let peak = web.max_intensity();
+    if peak <= 0.0 {
+        return Err(Error::ZeroIntensity);
+    }
+    Ok(map_photometric_web(web, IntensityUnits::Relative, |int| {
+        int / peak
+    }))
+}
+
+/// Adds the intensities of two photometric webs, for example to compose a direct / indirect luminaire
+/// from two separately measured components. The webs must be in the same units.
+pub fn add_photometric_webs(
+    web_a: &PhotometricWeb,
+    web_b: &PhotometricWeb,
+) -> Result<PhotometricWeb, Error> {
+    check_units(web_a, web_b)?;
+    combine_photometric_webs(web_a, web_b, web_a.units(), |a, b| a + b)
+}
+
+/// Subtracts the intensities of the second photometric web from those of the first.
+/// No clamping is performed, so the result may contain negative intensities.
+pub fn subtract_photometric_webs(
+    web_a: &PhotometricWeb,
+    web_b: &PhotometricWeb,
+) -> Result<PhotometricWeb, Error> {
+    check_units(web_a, web_b)?;
+    combine_photometric_webs(web_a, web_b, web_a.units(), |a, b| a - b)
+}
+
+/// Computes the element-wise ratio of the intensities of the first web to those of the second.
+/// Directions in which the second web has no intensity are given a ratio of zero.
+/// The webs must be in the same units. As the ratio is dimensionless, the planes are given relative units.
+pub fn ratio_photometric_webs(
+    web_a: &PhotometricWeb,
+    web_b: &PhotometricWeb,
+) -> Result<PhotometricWeb, Error> {
+    check_units(web_a, web_b)?;
+    combine_photometric_webs(web_a, web_b, IntensityUnits::Relative, |a, b| {
+        if b == 0.0 {
+            0.0
+        } else {
+            a / b
+        }
+    })
+}
+
+/// Resamples the photometric web onto the plane and intensity angles of the template web.
+/// The intensities of the template are ignored; only its structure is used.
+pub fn resample_photometric_web(web: &PhotometricWeb, template: &PhotometricWeb) -> PhotometricWeb {
+    let planes = template
+        .planes()
+        .iter()
+        .map(|tpl| {
+            let mut pl = tpl.clone();
+            pl.set_intensities(
+                tpl.angles()
+                    .iter()
+                    .map(|ang| web.intensity_at(tpl.angle(), *ang))
+                    .collect::<Vec<f64>>(),
+            );
+            pl.set_units(web.units());
+            pl
+        })
+        .collect();
+
+    let mut photweb = PhotometricWeb::new();
+    photweb.set_planes(planes);
+    photweb
+}
+
+/// Applies a function to each of the intensities in the web, returning a new web in the given units.
+fn map_photometric_web(
+    web: &PhotometricWeb,
+    units: IntensityUnits,
+    func: impl Fn(f64) -> f64,
+) -> PhotometricWeb {
+    let planes = web
+        .planes()
+        .iter()
+        .map(|pl| {
+            let mut plane = pl.clone();
+            plane.set_intensities(pl.intensities().iter().map(|int| func(*int)).collect::<Vec<f64>>());
+            plane.set_units(units.clone());
+            plane
+        })
+        .collect();
+
+    let mut photweb = PhotometricWeb::new();
+    photweb.set_planes(planes);
+    photweb
+}
+
+/// Checks that two webs store their intensities in the same units.
+fn check_units(web_a: &PhotometricWeb, web_b: &PhotometricWeb) -> Result<(), Error> {
+    if web_a.units() != web_b.units() {
+        return Err(Error::InconsistentUnits);
+    }
+    Ok(())
+}
+
+/// Combines two webs element-wise using the provided function, returning a web in the given units.
+/// The web with the most samples provides the grid, and the other web is resampled onto it.
+fn combine_photometric_webs(
+    web_a: &PhotometricWeb,
+    web_b: &PhotometricWeb,
+    units: IntensityUnits,
+    func: impl Fn(f64, f64) -> f64,
+) -> Result<PhotometricWeb, Error> {
+    if web_a.n_planes() == 0 || web_b.n_planes() == 0 {
+        return Err(Error::NoPlanes);
+    }
+
+    let template = if web_b.n_samples() > web_a.n_samples() {
+        web_b
+    } else {
+        web_a
+    };
+    let planes: Vec<Plane> = template
+        .planes()
+        .iter()
+        .map(|tpl| {
+            let mut pl = tpl.clone();
+            pl.set_intensities(
+                tpl.angles()
+                    .iter()
+                    .map(|ang| {
+                        func(
+                            web_a.intensity_at(tpl.angle(), *ang),
+                            web_b.intensity_at(tpl.angle(), *ang),
+                        )
+                    })
+                    .collect::<Vec<f64>>(),
+            );
+            pl.set_units(units.clone());
+            pl
+        })
+        .collect();
+
+    let mut photweb = PhotometricWeb::new();
+    photweb.set_planes(planes);
+    Ok(photweb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::planar_web;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    /// Builds a web of equally spaced planes, each containing a constant intensity.
+    fn uniform_web(n_planes: usize, step_deg: usize, intensity: f64) -> PhotometricWeb {
+        planar_web(360.0 / n_planes as f64, step_deg as f64, |_, _| intensity)
+    }
+
+    #[test]
+    fn test_scale() {
+        let web = uniform_web(36, 1, 1.0);
+        let scaled = scale_photometric_web(&web, 0.5);
+        assert_abs_diff_eq!(scaled.total_intensity(), 2.0 * PI, epsilon = 2.0 * PI * 1E-4);
+        assert_eq!(scaled.units(), IntensityUnits::Candela);
+    }
+
+    #[test]
+    fn test_normalise_to_flux() {
+        let web = uniform_web(36, 1, 3.0);
+        let norm = normalise_photometric_web_to_flux(&web, 1000.0).unwrap();
+        assert_abs_diff_eq!(norm.total_intensity(), 1000.0, epsilon = 1E-9);
+
+        // A web with no output can't be normalised.
+        let dark = uniform_web(36, 1, 0.0);
+        assert!(matches!(
+            normalise_photometric_web_to_flux(&dark, 1000.0),
+            Err(Error::ZeroFlux)
+        ));
+    }
+
+    #[test]
+    fn test_normalise_to_peak() {
+        let mut web = uniform_web(4, 10, 2.0);
+        let mut planes = web.planes().to_vec();
+        planes[1].set_intensities(vec![8.0; 19]);
+        web.set_planes(planes);
+
+        let norm = normalise_photometric_web_to_peak(&web).unwrap();
+        assert_eq!(norm.max_intensity(), 1.0);
+        assert_eq!(norm.planes()[0].intensities()[0], 0.25);
+        assert_eq!(norm.units(), IntensityUnits::Relative);
+    }
+
+    /// Adding two webs on the same grid should sum the intensities, and hence the flux.
+    #[test]
+    fn test_add_same_grid() {
+        let web = uniform_web(36, 1, 1.0);
+        let sum = add_photometric_webs(&web, &web).unwrap();
+        assert_eq!(sum.n_planes(), 36);
+        assert_abs_diff_eq!(sum.total_intensity(), 8.0 * PI, epsilon = 8.0 * PI * 1E-4);
+    }
+
+    /// A spherically symmetric web added to a full web should be resampled onto the full web's grid.
+    #[test]
+    fn test_add_and_subtract_different_grids() {
+        let full = uniform_web(36, 5, 2.0);
+        let symmetric = uniform_web(1, 1, 1.0);
+
+        let sum = add_photometric_webs(&symmetric, &full).unwrap();
+        assert_eq!(sum.n_planes(), 36);
+        assert!(sum
+            .planes()
+            .iter()
+            .all(|pl| pl.intensities().iter().all(|int| (*int - 3.0).abs() < 1E-9)));
+
+        let diff = subtract_photometric_webs(&full, &symmetric).unwrap();
+        assert_abs_diff_eq!(diff.total_intensity(), 4.0 * PI, epsilon = 4.0 * PI * 1E-3);
+    }
+
+    #[test]
+    fn test_add_inconsistent_units() {
+        let web = uniform_web(4, 10, 1.0);
+        let relative = normalise_photometric_web_to_peak(&web).unwrap();
+        assert!(matches!(
+            add_photometric_webs(&web, &relative),
+            Err(Error::InconsistentUnits)
+        ));
+    }
+
+    #[test]
+    fn test_ratio() {
+        let numerator = uniform_web(8, 10, 3.0);
+        let denominator = uniform_web(8, 10, 2.0);
+        let ratio = ratio_photometric_webs(&numerator, &denominator).unwrap();
+        assert_eq!(ratio.max_intensity(), 1.5);
+        assert_eq!(ratio.units(), IntensityUnits::Relative);
+
+        // Dividing by a dark web gives zero rather than infinity.
+        let dark = uniform_web(8, 10, 0.0);
+        assert_eq!(ratio_photometric_webs(&numerator, &dark).unwrap().max_intensity(), 0.0);
+
+        let relative = normalise_photometric_web_to_peak(&denominator).unwrap();
+        assert!(matches!(
+            ratio_photometric_webs(&numerator, &relative),
+            Err(Error::InconsistentUnits)
+        ));
+    }
+
+    #[test]
+    fn test_resample() {
+        let coarse = uniform_web(4, 30, 5.0);
+        let fine = uniform_web(36, 1, 0.0);
+        let resampled = resample_photometric_web(&coarse, &fine);
+        assert_eq!(resampled.n_planes(), 36);
+        assert_eq!(resampled.n_samples(), fine.n_samples());
+        assert_abs_diff_eq!(resampled.total_intensity(), 20.0 * PI, epsilon = 20.0 * PI * 1E-4);
+    }
+}
@@ -0,0 +1,125 @@
+//! Conversions between the Type A, Type B and Type C angle systems.
+//!
+//! All three systems describe the same physical directions around the luminaire, see `Vector3` for the
+//! conventions used. A conversion resamples the intensity distribution onto a full-sphere grid in the target
+//! system, using the orientation of the planes to identify the system of the source web.
+
+use crate::{
+    ops::resampling_grid,
+    photweb::{PhotometricWeb, Plane, PlaneOrientation},
+    util::geom::Vector3,
+};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Converts a photometric web into the angle system given by the plane orientation:
+/// - `PlaneOrientation::Vertical` for Type C, with C-planes from 0 -> 2 pi and gamma angles from 0 -> pi.
+/// - `PlaneOrientation::Horizontal` for Type B, with V-planes from -pi -> pi and H angles from -pi / 2 -> pi / 2.
+/// - `PlaneOrientation::Longitudinal` for Type A, with H-planes from -pi -> pi and V angles from -pi / 2 -> pi / 2.
+///
+/// The resolution of the output grid is taken from the input web.
+pub fn convert_photometric_web(web: &PhotometricWeb, orientation: PlaneOrientation) -> PhotometricWeb {
+    let (c_angles, gamma_angles) = resampling_grid(web);
+    let (plane_angles, angles): (Vec<f64>, Vec<f64>) = match orientation {
+        PlaneOrientation::Vertical => (c_angles, gamma_angles),
+        PlaneOrientation::Horizontal | PlaneOrientation::Longitudinal => (
+            c_angles.iter().map(|ang| ang - PI).collect(),
+            gamma_angles.iter().map(|ang| ang - FRAC_PI_2).collect(),
+        ),
+    };
+
+    let planes = plane_angles
+        .iter()
+        .map(|plane_angle| {
+            let mut plane = Plane::new();
+            plane.set_angle(*plane_angle);
+            plane.set_orientation(orientation.clone());
+            plane.set_angles(angles.clone());
+            plane.set_intensities(
+                angles
+                    .iter()
+                    .map(|angle| {
+                        let direction = match orientation {
+                            PlaneOrientation::Vertical => Vector3::from_c_gamma(*plane_angle, *angle),
+                            PlaneOrientation::Horizontal => Vector3::from_type_b(*plane_angle, *angle),
+                            PlaneOrientation::Longitudinal => Vector3::from_type_a(*plane_angle, *angle),
+                        };
+                        web.intensity_towards(&direction)
+                    })
+                    .collect::<Vec<f64>>(),
+            );
+            plane.set_units(web.units());
+            plane
+        })
+        .collect();
+
+    let mut photweb = PhotometricWeb::new();
+    photweb.set_planes(planes);
+    photweb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{photweb::test_webs::planar_web, util::geom::degrees_to_radians};
+    use approx::assert_abs_diff_eq;
+
+    /// An asymmetric, smoothly varying distribution defined on directions, so that
+    /// any of the angle systems can be compared in the same physical direction.
+    fn distribution(direction: &Vector3) -> f64 {
+        let forward = Vector3::new(0.4, 0.2, -1.0).normalise();
+        100.0 * (1.0 + direction.dot(&forward)).powi(2)
+    }
+
+    /// A Type C web sampling the distribution every 5 degrees.
+    fn type_c_web() -> PhotometricWeb {
+        planar_web(5.0, 5.0, |c_angle, gamma| distribution(&Vector3::from_c_gamma(c_angle, gamma)))
+    }
+
+    /// The tolerance on interpolated intensities, 1% of the peak of the distribution (400 cd).
+    const TOLERANCE: f64 = 400.0 * 1E-2;
+
+    /// The intensities of the converted web should match the original in the same physical directions.
+    #[test]
+    fn test_conversion_preserves_directions() {
+        let web_c = type_c_web();
+        let web_b = convert_photometric_web(&web_c, PlaneOrientation::Horizontal);
+        let web_a = convert_photometric_web(&web_c, PlaneOrientation::Longitudinal);
+        assert_eq!(web_b.orientation(), PlaneOrientation::Horizontal);
+        assert_eq!(web_a.orientation(), PlaneOrientation::Longitudinal);
+
+        for (c_deg, gamma_deg) in [(0.0, 0.0), (20.0, 30.0), (135.0, 60.0), (250.0, 100.0), (300.0, 170.0)] {
+            let dir = Vector3::from_c_gamma(degrees_to_radians(c_deg), degrees_to_radians(gamma_deg));
+            let expected = distribution(&dir);
+            assert_abs_diff_eq!(web_b.intensity_towards(&dir), expected, epsilon = TOLERANCE);
+            assert_abs_diff_eq!(web_a.intensity_towards(&dir), expected, epsilon = TOLERANCE);
+        }
+    }
+
+    /// Converting round trips through each of the systems should return the original intensities,
+    /// and conserve the total flux.
+    #[test]
+    fn test_round_trips() {
+        let web_c = type_c_web();
+        let flux = web_c.total_intensity();
+
+        for orientation in [PlaneOrientation::Horizontal, PlaneOrientation::Longitudinal] {
+            let converted = convert_photometric_web(&web_c, orientation);
+            assert_abs_diff_eq!(converted.total_intensity(), flux, epsilon = flux * 1E-2);
+
+            let back = convert_photometric_web(&converted, PlaneOrientation::Vertical);
+            assert_eq!(back.orientation(), PlaneOrientation::Vertical);
+            for plane in back.planes().iter().step_by(7) {
+                for (gamma, intensity) in plane.angles().iter().zip(plane.intensities()).step_by(5) {
+                    let expected = web_c.intensity_at(plane.angle(), *gamma);
+                    assert_abs_diff_eq!(*intensity, expected, epsilon = TOLERANCE);
+                }
+            }
+        }
+
+        // Converting between Type A and Type B directly.
+        let web_b = convert_photometric_web(&web_c, PlaneOrientation::Horizontal);
+        let web_a = convert_photometric_web(&web_b, PlaneOrientation::Longitudinal);
+        let dir = Vector3::from_type_a(degrees_to_radians(25.0), degrees_to_radians(-40.0));
+        assert_abs_diff_eq!(web_a.intensity_towards(&dir), distribution(&dir), epsilon = TOLERANCE);
+    }
+}
@@ -6,6 +6,9 @@ pub enum Error {
     InconsistentNumberOfPlanes(usize, usize, usize),
     InconsistentIntensitiesInPlane(usize, usize),
     InconsistentPlaneAngles,
+    InconsistentUnits,
+    ZeroFlux,
+    ZeroIntensity,
 }
 
 impl Display for Error {
@@ -16,6 +19,9 @@ impl Display for Error {
                 Self::InconsistentNumberOfPlanes(ref expect, ref found, ref idx) => format!("Expected {} planes. Found {} planes and index {}. ", expect, found, idx),
                 Self::InconsistentIntensitiesInPlane(ref expect, ref found) => format!("Expected {} intensities in plane. Found {} intensisites. ", expect, found),
                 Self::InconsistentPlaneAngles => format!("Angles are inconsistent between photometric web planes. "),
+                Self::InconsistentUnits => "Intensity units are inconsistent between photometric webs. ".to_string(),
+                Self::ZeroFlux => "The photometric web emits no flux, so it cannot be normalised. ".to_string(),
+                Self::ZeroIntensity => "The photometric web has no non-zero intensities, so it cannot be normalised. ".to_string(),
             }
         })
     }
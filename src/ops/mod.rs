@@ -1,4 +1,8 @@
+pub mod arithmetic;
+pub mod coordinates;
 pub mod err;
+pub mod transform;
+pub use self::{arithmetic::*, coordinates::*, transform::*};
 
 use crate::{
     ops::err::Error,
@@ -0,0 +1,188 @@
+//! Geometric transformations of photometric webs.
+//!
+//! A `PhotometricWeb` is defined in the orientation in which the luminaire was measured. These operations rotate
+//! the intensity distribution, for example to account for a luminaire installed with a tilt, and resample it onto
+//! a new C-gamma web.
+
+use crate::{
+    photweb::{PhotometricWeb, Plane, PlaneOrientation},
+    util::geom::{Rotation, Vector3},
+};
+use std::f64::consts::PI;
+
+/// The coarsest angular step (in radians) used for resampling, also used when the resolution of the input web cannot be determined.
+const DEFAULT_STEP: f64 = 5.0 * PI / 180.0;
+/// The finest angular step (in radians) used for resampling, to avoid excessively large webs.
+const MIN_STEP: f64 = 0.1 * PI / 180.0;
+
+/// Rotates a photometric web by the given yaw, pitch and roll angles (in radians), see `Rotation` for the conventions used.
+/// The rotated distribution is resampled onto a full C-gamma web, with a resolution matching the input web.
+pub fn rotate_photometric_web(web: &PhotometricWeb, yaw: f64, pitch: f64, roll: f64) -> PhotometricWeb {
+    let (c_angles, gamma_angles) = resampling_grid(web);
+    transform_photometric_web(
+        web,
+        &Rotation::from_yaw_pitch_roll(yaw, pitch, roll),
+        &c_angles,
+        &gamma_angles,
+    )
+}
+
+/// Tilts a photometric web by the given angle (in radians) about the C0 axis.
+/// A positive tilt raises the luminaire towards the C90 side, as is the convention for road lighting luminaires,
+/// swinging the light distribution towards the C90 plane.
+pub fn tilt_photometric_web(web: &PhotometricWeb, tilt: f64) -> PhotometricWeb {
+    rotate_photometric_web(web, 0.0, 0.0, tilt)
+}
+
+/// Rotates a photometric web, and resamples the result onto the provided C-plane and gamma angles (in radians).
+/// The intensity of the output in a given direction is the intensity of the input in the direction that the rotation maps onto it.
+pub fn transform_photometric_web(
+    web: &PhotometricWeb,
+    rotation: &Rotation,
+    c_angles: &[f64],
+    gamma_angles: &[f64],
+) -> PhotometricWeb {
+    let inverse = rotation.inverse();
+    let planes = c_angles
+        .iter()
+        .map(|c_angle| {
+            let mut plane = Plane::new();
+            plane.set_angle(*c_angle);
+            plane.set_orientation(PlaneOrientation::Vertical);
+            plane.set_angles(gamma_angles.to_vec());
+            plane.set_intensities(
+                gamma_angles
+                    .iter()
+                    .map(|gamma| {
+                        let direction = Vector3::from_c_gamma(*c_angle, *gamma);
+                        web.intensity_towards(&inverse.rotate(&direction))
+                    })
+                    .collect::<Vec<f64>>(),
+            );
+            plane.set_units(web.units());
+            plane
+        })
+        .collect();
+
+    let mut photweb = PhotometricWeb::new();
+    photweb.set_planes(planes);
+    photweb
+}
+
+/// Determines a full C-gamma grid (0 -> 2 pi, 0 -> pi) onto which a web can be resampled without losing resolution.
+/// The grid spacing is taken from the finest spacing of the planes, and of the angles within the planes, but is never coarser than 5 degrees.
+pub fn resampling_grid(web: &PhotometricWeb) -> (Vec<f64>, Vec<f64>) {
+    let mut plane_angles: Vec<f64> = web
+        .planes()
+        .iter()
+        .map(|pl| pl.angle().rem_euclid(2.0 * PI))
+        .collect();
+    plane_angles.sort_by(|a, b| a.total_cmp(b));
+    let c_step = finest_step(&plane_angles);
+
+    let gamma_step = web
+        .planes()
+        .iter()
+        .map(|pl| finest_step(pl.angles()))
+        .fold(DEFAULT_STEP, f64::min);
+
+    (
+        even_steps(2.0 * PI, c_step, false),
+        even_steps(PI, gamma_step, true),
+    )
+}
+
+/// Finds the smallest non-zero spacing between consecutive values, limited to the default step.
+fn finest_step(values: &[f64]) -> f64 {
+    values
+        .windows(2)
+        .map(|vals| vals[1] - vals[0])
+        .filter(|step| *step > MIN_STEP)
+        .fold(DEFAULT_STEP, f64::min)
+}
+
+/// Divides the range 0 -> `end` into equal steps no larger than `step`, optionally including the end point.
+fn even_steps(end: f64, step: f64, inclusive: bool) -> Vec<f64> {
+    let n_steps = (end / step.max(MIN_STEP) - 1E-6).ceil().max(1.0) as usize;
+    let n_values = if inclusive { n_steps + 1 } else { n_steps };
+    (0..n_values)
+        .map(|i| end * i as f64 / n_steps as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{photweb::test_webs::planar_web, util::geom::degrees_to_radians};
+    use approx::assert_abs_diff_eq;
+
+    /// Builds a rotationally symmetric Lambertian (cosine) web, emitting pi lumens into the lower hemisphere.
+    fn lambertian_web(n_planes: usize) -> PhotometricWeb {
+        planar_web(360.0 / n_planes as f64, 1.0, |_, gamma| gamma.cos().max(0.0))
+    }
+
+    /// Tilting and rotating should move the light around, but not create or destroy any of it.
+    #[test]
+    fn test_flux_conservation() {
+        let web = lambertian_web(72);
+        let flux = web.total_intensity();
+
+        let tilted = tilt_photometric_web(&web, degrees_to_radians(15.0));
+        assert_abs_diff_eq!(tilted.total_intensity(), flux, epsilon = flux * 1E-2);
+
+        let rotated = rotate_photometric_web(
+            &web,
+            degrees_to_radians(30.0),
+            degrees_to_radians(-20.0),
+            degrees_to_radians(5.0),
+        );
+        assert_abs_diff_eq!(rotated.total_intensity(), flux, epsilon = flux * 1E-2);
+    }
+
+    /// The peak of a tilted downlight should move towards the C90 plane by the tilt angle.
+    #[test]
+    fn test_tilt_moves_peak() {
+        let web = lambertian_web(1);
+        let tilted = tilt_photometric_web(&web, degrees_to_radians(10.0));
+
+        assert_abs_diff_eq!(
+            tilted.intensity_at(degrees_to_radians(90.0), degrees_to_radians(10.0)),
+            1.0,
+            epsilon = 1E-3
+        );
+        assert_abs_diff_eq!(
+            tilted.intensity_at(degrees_to_radians(270.0), degrees_to_radians(10.0)),
+            degrees_to_radians(20.0).cos(),
+            epsilon = 1E-3
+        );
+    }
+
+    /// Symmetric cases: yawing a rotationally symmetric web leaves it unchanged,
+    /// and turning a downlight upside down produces an uplight.
+    #[test]
+    fn test_symmetric_rotations() {
+        let web = lambertian_web(36);
+        let yawed = rotate_photometric_web(&web, degrees_to_radians(37.0), 0.0, 0.0);
+        for gamma_deg in [0.0, 20.0, 45.0, 80.0, 120.0] {
+            for c_deg in [0.0, 55.0, 190.0] {
+                assert_abs_diff_eq!(
+                    yawed.intensity_at(degrees_to_radians(c_deg), degrees_to_radians(gamma_deg)),
+                    web.intensity_at(degrees_to_radians(c_deg), degrees_to_radians(gamma_deg)),
+                    epsilon = 1E-3
+                );
+            }
+        }
+
+        let inverted = rotate_photometric_web(&web, 0.0, PI, 0.0);
+        assert_abs_diff_eq!(inverted.intensity_at(0.0, PI), 1.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(inverted.intensity_at(0.0, 0.0), 0.0, epsilon = 1E-9);
+    }
+
+    #[test]
+    fn test_resampling_grid() {
+        let (c_angles, gamma_angles) = resampling_grid(&lambertian_web(36));
+        assert_eq!(c_angles.len(), 72);
+        assert_eq!(gamma_angles.len(), 181);
+        assert_eq!(*gamma_angles.last().unwrap(), PI);
+    }
+}
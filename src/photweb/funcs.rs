@@ -1,74 +1,154 @@
-use super::Plane;
-use std::f64::consts::{FRAC_PI_2, PI};
+use super::{Plane, Symmetry};
+use std::f64::consts::PI;
 
-/// A utility function which mirrors the first quadrant of planes in a vector into the second quadrant.
-/// This means that we start with 0 -> \pi / 2 filled, and we return 0 -> \pi filled.
-pub fn mirror_first_quadrant(planes: &Vec<Plane>) -> Vec<Plane> {
-    let mut ret_planes = planes.clone();
-    ret_planes.extend(planes.iter().rev().skip(1).map(|pl| {
-        let mut newpl = pl.clone();
-        *newpl.mut_angle() = (FRAC_PI_2 - pl.angle()) + FRAC_PI_2;
-        newpl
-    }));
+/// The tolerance (in radians) within which two planes are considered to be at the same angle.
+const PLANE_ANGLE_TOLERANCE: f64 = 1E-6;
+
+/// Expands the planes of the unique sector for a given symmetry into a full set of C-planes from 0 -> 2 \pi.
+/// The input planes may have any spacing, may be unordered, and may contain duplicate planes (such as both 0 and 360 degrees).
+/// The output planes are sorted by angle, in the range 0 -> 2 \pi, with duplicates removed.
+/// Planes already present in the input take priority over mirrored copies at the same angle.
+/// In the case of rotational symmetry, only the first plane is returned.
+pub fn expand_symmetry(planes: &[Plane], symmetry: Symmetry) -> Vec<Plane> {
+    let planes = normalise_plane_angles(planes);
+    match symmetry {
+        Symmetry::None => planes,
+        Symmetry::Rotational => planes.into_iter().take(1).collect(),
+        Symmetry::C0C180 => mirror_planes(&planes, |angle| -angle),
+        Symmetry::C90C270 => mirror_planes(&planes, |angle| PI - angle),
+        Symmetry::Quadrant => mirror_planes(&mirror_planes(&planes, |angle| PI - angle), |angle| -angle),
+    }
+}
 
+/// Wraps the plane angles into the range 0 -> 2 \pi, sorts the planes by angle and removes any duplicates.
+fn normalise_plane_angles(planes: &[Plane]) -> Vec<Plane> {
+    let mut ret_planes: Vec<Plane> = planes
+        .iter()
+        .map(|pl| {
+            let mut newpl = pl.clone();
+            *newpl.mut_angle() = wrap_angle(pl.angle());
+            newpl
+        })
+        .collect();
+    ret_planes.sort_by(|a, b| a.angle().total_cmp(&b.angle()));
+    ret_planes.dedup_by(|a, b| (a.angle() - b.angle()).abs() <= PLANE_ANGLE_TOLERANCE);
     ret_planes
 }
 
+/// Wraps an angle into the range 0 -> 2 \pi, treating angles within tolerance of 2 \pi as 0.
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(2.0 * PI);
+    if 2.0 * PI - wrapped <= PLANE_ANGLE_TOLERANCE {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// Adds a mirrored copy of each plane, with the angle given by the `mirror` function, unless a plane already exists at that angle.
+fn mirror_planes(planes: &[Plane], mirror: impl Fn(f64) -> f64) -> Vec<Plane> {
+    let mut ret_planes = planes.to_vec();
+    for pl in planes {
+        let angle = wrap_angle(mirror(pl.angle()));
+        if !ret_planes
+            .iter()
+            .any(|existing| (existing.angle() - angle).abs() <= PLANE_ANGLE_TOLERANCE)
+        {
+            let mut newpl = pl.clone();
+            *newpl.mut_angle() = angle;
+            ret_planes.push(newpl);
+        }
+    }
+    normalise_plane_angles(&ret_planes)
+}
+
 /// A utility function which mirrors the first quadrant of planes in a vector into the second quadrant.
-/// This means that we start with 0 -> \pi filled, and we return 0 -> 2 \pi filled.
-pub fn mirror_first_hemisphere(planes: &Vec<Plane>) -> Vec<Plane> {
-    let mut ret_planes = planes.clone();
-    let take_planes = planes.iter().count() - 2;
-
-    ret_planes.extend(
-        planes
-            .into_iter()
-            .rev()
-            .skip(1)
-            .take(take_planes)
-            .map(|pl| {
-                let mut newpl = pl.clone();
-                *newpl.mut_angle() = (PI - pl.angle()) + PI;
-                newpl
-            }),
-    );
+/// This means that we start with 0 -> \pi / 2 filled, and we return 0 -> \pi filled.
+#[deprecated(note = "use `expand_symmetry` instead")]
+pub fn mirror_first_quadrant(planes: &[Plane]) -> Vec<Plane> {
+    mirror_planes(&normalise_plane_angles(planes), |angle| PI - angle)
+}
 
-    ret_planes
+/// A utility function which mirrors the first hemisphere of planes in a vector into the second hemisphere.
+/// This means that we start with 0 -> \pi filled, and we return 0 -> 2 \pi filled.
+#[deprecated(note = "use `expand_symmetry` instead")]
+pub fn mirror_first_hemisphere(planes: &[Plane]) -> Vec<Plane> {
+    expand_symmetry(planes, Symmetry::C0C180)
 }
 
 /// A utility function which takes the hemisphere occupying the second and third quadrants (90 degree - 270 degree)
-/// and mirrors this onto the opposing (270 degree -> 90 degree) hemisphere. 
-pub fn mirror_second_and_third_quadrants(planes: &Vec<Plane>) -> Vec<Plane> {
-    let half = planes.iter().count() / 2;
+/// and mirrors this onto the opposing (270 degree -> 90 degree) hemisphere.
+#[deprecated(note = "use `expand_symmetry` instead")]
+pub fn mirror_second_and_third_quadrants(planes: &[Plane]) -> Vec<Plane> {
+    expand_symmetry(planes, Symmetry::C90C270)
+}
 
-    // Assemble the first quadrant from the data we have. 
-    let mut ret_planes: Vec<Plane> = planes
-    .iter()
-    .skip(1)
-    .take(half)
-    .rev()
-    .map(|pl| {
-        let mut new_plane = pl.clone();
-        *new_plane.mut_angle() = PI - pl.angle();
-        new_plane
-    })
-    .collect();
-
-    // Now copy the 90 - 270 degree planes. 
-    ret_planes.extend(planes.iter().map(|pl| pl.clone()));
-
-    let tmp_planes = planes
-        .iter()
-        .skip(half + 1)
-        .take(half - 1)
-        .rev()
-        .map(|pl| {
-            let mut new_plane = pl.clone();
-            *new_plane.mut_angle() = pl.angle() + 2.0 * (3.0 * FRAC_PI_2 - pl.angle());
-            new_plane
-        });
-    ret_planes.extend(tmp_planes.into_iter());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    ret_planes
+    /// Builds planes at the given angles (in degrees), each with a single intensity equal to its angle.
+    fn planes_at(angles_deg: &[f64]) -> Vec<Plane> {
+        angles_deg
+            .iter()
+            .map(|ang| {
+                let mut plane = Plane::new();
+                plane.set_angle_degrees(*ang);
+                plane.set_angles_degrees(&vec![0.0]);
+                plane.set_intensities(vec![*ang]);
+                plane
+            })
+            .collect()
+    }
 
-}
\ No newline at end of file
+    fn angles_deg(planes: &[Plane]) -> Vec<f64> {
+        planes.iter().map(|pl| (pl.angle_deg() * 1E6).round() / 1E6).collect()
+    }
+
+    fn intensities(planes: &[Plane]) -> Vec<f64> {
+        planes.iter().map(|pl| pl.intensities()[0]).collect()
+    }
+
+    /// Uneven spacings should be mirrored plane-by-plane.
+    #[test]
+    fn test_expand_uneven_spacing() {
+        let expanded = expand_symmetry(&planes_at(&[0.0, 15.0, 45.0, 90.0]), Symmetry::Quadrant);
+        assert_eq!(
+            angles_deg(&expanded),
+            vec![0.0, 15.0, 45.0, 90.0, 135.0, 165.0, 180.0, 195.0, 225.0, 270.0, 315.0, 345.0]
+        );
+        assert_eq!(
+            intensities(&expanded),
+            vec![0.0, 15.0, 45.0, 90.0, 45.0, 15.0, 0.0, 15.0, 45.0, 90.0, 45.0, 15.0]
+        );
+
+        let expanded = expand_symmetry(&planes_at(&[90.0, 100.0, 180.0, 270.0]), Symmetry::C90C270);
+        assert_eq!(angles_deg(&expanded), vec![0.0, 80.0, 90.0, 100.0, 180.0, 270.0]);
+        assert_eq!(intensities(&expanded), vec![180.0, 100.0, 90.0, 100.0, 180.0, 270.0]);
+    }
+
+    /// A duplicate 360 degree plane should be merged with the 0 degree plane.
+    #[test]
+    fn test_expand_duplicate_planes() {
+        let expanded = expand_symmetry(&planes_at(&[0.0, 90.0, 180.0, 270.0, 360.0]), Symmetry::None);
+        assert_eq!(angles_deg(&expanded), vec![0.0, 90.0, 180.0, 270.0]);
+        assert_eq!(intensities(&expanded), vec![0.0, 90.0, 180.0, 270.0]);
+    }
+
+    /// Small and empty inputs should never panic.
+    #[test]
+    fn test_expand_small_inputs() {
+        for symmetry in [
+            Symmetry::None,
+            Symmetry::Rotational,
+            Symmetry::C0C180,
+            Symmetry::C90C270,
+            Symmetry::Quadrant,
+        ] {
+            assert!(expand_symmetry(&[], symmetry).is_empty());
+            assert!(!expand_symmetry(&planes_at(&[0.0]), symmetry).is_empty());
+        }
+        assert_eq!(angles_deg(&expand_symmetry(&planes_at(&[0.0]), Symmetry::Quadrant)), vec![0.0, 180.0]);
+        assert_eq!(angles_deg(&expand_symmetry(&planes_at(&[0.0, 10.0]), Symmetry::Rotational)), vec![0.0]);
+    }
+}
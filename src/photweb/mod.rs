@@ -10,6 +10,7 @@ pub use self::{photweb_reader::*, photweb_writer::*};
 // Structs
 pub mod photweb;
 pub mod plane;
+pub mod symmetry;
 pub mod units;
 
 // Builders
@@ -18,7 +19,9 @@ pub mod photweb_builder;
 // Module functions.
 mod funcs;
 
-pub use self::{funcs::*, photweb::*, photweb_builder::*, plane::*, units::*};
+pub use self::{funcs::*, photweb::*, photweb_builder::*, plane::*, symmetry::*, units::*};
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+pub mod test_webs;
@@ -1,9 +1,15 @@
-use super::{Plane, PlaneWidth};
+use super::{IntensityUnits, Plane, PlaneOrientation, PlaneWidth};
 use property::Property;
 use std::{default::Default, f64::consts::PI};
-use crate::util::geom::angle_difference;
+use crate::util::geom::{angle_difference, Vector3};
 
-#[derive(Default, Debug, Property)]
+/// The tolerance (in radians) within which a plane angle is taken to lie on the edge of the measured range.
+const PLANE_ANGLE_TOLERANCE: f64 = 1E-9;
+
+/// The planes either side of a plane angle, each alongside its angular distance from that angle.
+type PlaneBracket<'a> = ((&'a Plane, f64), (&'a Plane, f64));
+
+#[derive(Default, Debug, Clone, Property)]
 #[property(get(public), set(public))]
 pub struct PhotometricWeb {
     /// The planes that are contained in the photometric web.
@@ -49,8 +55,11 @@ impl PhotometricWeb {
             let (lp, up) = self.get_adjacent_planes(i as i32);
 
             // Now find the difference in angles angles at both sides of the plane. 
-            let lower = angle_difference(curr_plane.angle(), lp.angle());
-            let upper = angle_difference(up.angle(), curr_plane.angle());
+            // Type C planes wrap around the full circle, whereas the planes of Type A and B webs only cover the measured
+            // range, so the first and last planes stop at the ends of that range rather than reaching around to each other.
+            let wraps = self.orientation() == PlaneOrientation::Vertical;
+            let lower = if !wraps && i == 0 { 0.0 } else { angle_difference(curr_plane.angle(), lp.angle()) };
+            let upper = if !wraps && i + 1 == self.n_planes() { 0.0 } else { angle_difference(up.angle(), curr_plane.angle()) };
             
             if lower == upper {
                 PlaneWidth::Symmetric(0.5 * (lower + upper))
@@ -71,6 +80,110 @@ impl PhotometricWeb {
             .sum()
     }
 
+    /// Integrates the energy emitted between two angles (in radians) within the planes, such as a zone of gamma angles for Type C webs.
+    pub fn integrate_intensity_between(&self, lower: f64, upper: f64) -> f64 {
+        self.planes
+            .iter()
+            .map(|p| p.integrate_intensity_between(lower, upper))
+            .sum()
+    }
+
+    /// Returns the largest intensity found anywhere in the web.
+    pub fn max_intensity(&self) -> f64 {
+        self.planes
+            .iter()
+            .fold(0.0, |max, pl| f64::max(max, pl.max_intensity()))
+    }
+
+    /// Returns the units of the intensities stored in the web, taken from the first plane.
+    pub fn units(&self) -> IntensityUnits {
+        self.planes
+            .first()
+            .map(|pl| pl.units().clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the orientation of the planes in the web, and hence its angle system, taken from the first plane.
+    pub fn orientation(&self) -> PlaneOrientation {
+        self.planes
+            .first()
+            .map(|pl| pl.orientation().clone())
+            .unwrap_or_default()
+    }
+
+    /// The total number of intensity samples across all of the planes in the web.
+    pub fn n_samples(&self) -> usize {
+        self.planes.iter().map(|pl| pl.n_samples()).sum()
+    }
+
+    /// Interpolates the intensity in the direction given by a plane angle and an angle within that plane (both in radians).
+    /// Bilinear interpolation is used, so the planes do not need to be equally spaced. Type C planes wrap around the full
+    /// circle, whereas Type A and B planes only cover their measured range, outside of which the intensity is zero.
+    /// A spherically symmetric web only interpolates within its single plane.
+    pub fn intensity_at(&self, plane_angle: f64, angle: f64) -> f64 {
+        match self.n_planes() {
+            0 => 0.0,
+            1 => self.planes[0].intensity_at(angle),
+            _ => {
+                let Some(((lower, lower_dist), (upper, upper_dist))) = self.bracket_planes(plane_angle) else {
+                    return 0.0;
+                };
+                if lower_dist + upper_dist <= 0.0 {
+                    return lower.intensity_at(angle);
+                }
+                let frac = lower_dist / (lower_dist + upper_dist);
+                (1.0 - frac) * lower.intensity_at(angle) + frac * upper.intensity_at(angle)
+            }
+        }
+    }
+
+    /// Interpolates the intensity emitted in the direction of the given vector, expressed in the luminaire coordinate system.
+    /// The direction is converted into the angle system of the web before interpolating.
+    pub fn intensity_towards(&self, direction: &Vector3) -> f64 {
+        let (plane_angle, angle) = match self.orientation() {
+            PlaneOrientation::Vertical => direction.to_c_gamma(),
+            PlaneOrientation::Horizontal => direction.to_type_b(),
+            PlaneOrientation::Longitudinal => direction.to_type_a(),
+        };
+        self.intensity_at(plane_angle, angle)
+    }
+
+    /// Finds the planes immediately below and above the given plane angle, going anti-clockwise around the circle.
+    /// Each plane is returned alongside its angular distance from the requested angle.
+    /// Only Type C planes wrap around the circle; for Type A and B webs the planes are never bracketed across the gap
+    /// between the last and first planes, and `None` is returned if the angle lies outside the measured range.
+    fn bracket_planes(&self, plane_angle: f64) -> Option<PlaneBracket<'_>> {
+        if self.orientation() != PlaneOrientation::Vertical {
+            return self.bracket_planes_in_range(plane_angle);
+        }
+
+        let first = (&self.planes[0], f64::INFINITY);
+        Some(self.planes.iter().fold((first, first), |(lower, upper), pl| {
+            let lower_dist = (plane_angle - pl.angle()).rem_euclid(2.0 * PI);
+            let upper_dist = (pl.angle() - plane_angle).rem_euclid(2.0 * PI);
+            (
+                if lower_dist < lower.1 { (pl, lower_dist) } else { lower },
+                if upper_dist < upper.1 { (pl, upper_dist) } else { upper },
+            )
+        }))
+    }
+
+    /// Brackets the plane angle between the planes of a web that does not wrap, such as a Type A or B web.
+    /// The planes are expected in ascending order, and the angle is compared to them going anti-clockwise from the first
+    /// plane, so that a web given from -90 to 90 degrees and one given from 270 to 450 degrees behave alike.
+    fn bracket_planes_in_range(&self, plane_angle: f64) -> Option<PlaneBracket<'_>> {
+        let start = self.planes[0].angle() - PLANE_ANGLE_TOLERANCE;
+        let angle = start + (plane_angle - start).rem_euclid(2.0 * PI);
+        let end = self.planes[self.n_planes() - 1].angle();
+        if angle > end + PLANE_ANGLE_TOLERANCE {
+            return None;
+        }
+
+        let iupper = self.planes.partition_point(|pl| pl.angle() < angle).clamp(1, self.n_planes() - 1);
+        let (lower, upper) = (&self.planes[iupper - 1], &self.planes[iupper]);
+        Some(((lower, (angle - lower.angle()).max(0.0)), (upper, (upper.angle() - angle).max(0.0))))
+    }
+
     /// This resolves a plane index into a plane. 
     /// If the index is between 0 and the number of planes - 1, this function will
     /// just directly resolve the index. However, if the index is outside of this range
@@ -124,7 +237,7 @@ impl PhotometricWeb {
 mod tests {
     use crate::util::geom::degrees_to_radians;
 
-    use super::{PhotometricWeb, Plane};
+    use super::{PhotometricWeb, Plane, PlaneOrientation};
     use approx::assert_abs_diff_eq;
     use std::f64::consts::PI;
 
@@ -278,4 +391,84 @@ mod tests {
         assert_eq!(lp.angle(), degrees_to_radians(0.));
         assert_eq!(up.angle(), degrees_to_radians(0.));
     }
+
+    /// Checks that interpolation between planes wraps around the 0 / 360 degree point,
+    /// and that the nodes of the web are reproduced exactly.
+    #[test]
+    fn test_intensity_at() {
+        let mut web = PhotometricWeb::new();
+        web.set_planes(
+            (0..360)
+                .step_by(90)
+                .map(|ang_deg| {
+                    let mut plane = Plane::new();
+                    plane.set_angle_degrees(ang_deg as f64);
+                    plane.set_angles_degrees(&vec![0.0, 90.0, 180.0]);
+                    plane.set_intensities(vec![ang_deg as f64; 3]);
+                    plane
+                })
+                .collect(),
+        );
+
+        assert_eq!(web.intensity_at(degrees_to_radians(90.0), 0.0), 90.0);
+        assert_abs_diff_eq!(web.intensity_at(degrees_to_radians(45.0), 0.5), 45.0, epsilon = 1E-9);
+        // Between the 270 and 0 degree planes.
+        assert_abs_diff_eq!(web.intensity_at(degrees_to_radians(315.0), 0.5), 135.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(web.intensity_at(degrees_to_radians(-45.0), 0.5), 135.0, epsilon = 1E-9);
+        assert_eq!(web.max_intensity(), 270.0);
+    }
+
+    /// Builds a Type B web of uniform 1 cd, with B-planes every 5 degrees between the given V angles (in degrees) and
+    /// H angles from -90 to 90 degrees within each plane.
+    fn uniform_type_b_web(v_lower: i32, v_upper: i32) -> PhotometricWeb {
+        let mut web = PhotometricWeb::new();
+        web.set_planes(
+            (v_lower..=v_upper)
+                .step_by(5)
+                .map(|v| {
+                    let mut plane = Plane::new();
+                    plane.set_angle_degrees(v as f64);
+                    plane.set_orientation(PlaneOrientation::Horizontal);
+                    plane.set_angles_degrees(&(-90..=90).map(|h| h as f64).collect());
+                    plane.set_intensities(vec![1.0; 181]);
+                    plane
+                })
+                .collect(),
+        );
+        web
+    }
+
+    /// A uniform web over the front hemisphere (V from -90 to 90 degrees) covers a solid angle of 2 pi.
+    /// The first and last planes must not wrap around to each other, so they only reach to the ends of the range.
+    #[test]
+    fn test_integrate_type_b_hemisphere() {
+        let web = uniform_type_b_web(-90, 90);
+        assert_abs_diff_eq!(web.delta_angle(0).total(), degrees_to_radians(2.5), epsilon = 1E-9);
+        assert_abs_diff_eq!(web.delta_angle(36).total(), degrees_to_radians(2.5), epsilon = 1E-9);
+        assert_abs_diff_eq!(web.delta_angle(18).total(), degrees_to_radians(5.0), epsilon = 1E-9);
+        assert_abs_diff_eq!(web.total_intensity(), 2.0 * PI, epsilon = 2.0 * PI * 1E-4);
+    }
+
+    /// A uniform web covering V from -10 to 10 degrees subtends the 20 degree wedge between those planes, which is a
+    /// solid angle of 2 x 20 degrees (in radians), as the H angles span the full -90 to 90 degrees.
+    #[test]
+    fn test_integrate_type_b_partial() {
+        let web = uniform_type_b_web(-10, 10);
+        assert_abs_diff_eq!(web.total_intensity(), 2.0 * degrees_to_radians(20.0), epsilon = 1E-4);
+    }
+
+    /// A Type B web only covering V from -10 to 10 degrees emits nothing outside that range. In particular, directions
+    /// behind the luminaire must not be interpolated across the gap between the last and first planes.
+    #[test]
+    fn test_intensity_at_type_b_partial() {
+        let web = uniform_type_b_web(-10, 10);
+        assert_eq!(web.intensity_at(degrees_to_radians(0.0), degrees_to_radians(30.0)), 1.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(-7.5), 0.0), 1.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(10.0), 0.0), 1.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(-10.0), 0.0), 1.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(20.0), 0.0), 0.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(90.0), 0.0), 0.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(170.0), 0.0), 0.0);
+        assert_eq!(web.intensity_at(degrees_to_radians(-90.0), 0.0), 0.0);
+    }
 }
@@ -4,10 +4,18 @@ use std::{default::Default};
 
 use super::units::IntensityUnits;
 
-#[derive(Debug, Clone)]
+/// The tolerance (in radians) used when deciding whether an angle lies on the edge of the measured range.
+const ANGLE_TOLERANCE: f64 = 1E-9;
+
+/// The orientation of the planes in a photometric web, which determines the angle system in use.
+#[derive(Debug, Clone, PartialEq)]
 pub enum PlaneOrientation {
+    /// C-planes, hinged on the vertical axis of the luminaire, with gamma angles measured from the nadir (Type C).
     Vertical,
+    /// B-planes (V), hinged on the horizontal axis across the luminaire, with H angles measured from the nadir (Type B).
     Horizontal,
+    /// A-planes (H), hinged on the horizontal axis along the luminaire, with V angles measured from the nadir (Type A).
+    Longitudinal,
 }
 
 impl Default for PlaneOrientation {
@@ -130,18 +138,85 @@ impl Plane {
     }
 
     /// Integrate the total energy being emitted by this plane.
+    /// For Type C planes the angles are measured from the polar axis, whereas for Type A and B planes
+    /// they are measured from the equator, which changes the solid angle covered by each sample.
     pub fn integrate_intensity(&self) -> f64 {
+        let solid_angle_factor = |ang: f64| match self.orientation {
+            PlaneOrientation::Vertical => ang.sin(),
+            PlaneOrientation::Horizontal | PlaneOrientation::Longitudinal => ang.cos(),
+        };
         self.width.total() * self.intensities
             .iter()
             .enumerate()
-            .map(|(i, int)| int * f64::sin(self.angles[i]) * self.delta_angle(i))
+            .map(|(i, int)| int * solid_angle_factor(self.angles[i]) * self.delta_angle(i))
+            .sum::<f64>()
+    }
+
+    /// Integrate the energy being emitted by this plane between two angles (in radians) within the plane.
+    /// Each sample is taken to cover the angular range half way to its neighbours, clipped to the measured range of the
+    /// plane, and contributes in proportion to the overlap of that range with the requested range. No light is attributed
+    /// beyond the first and last angles, so a plane measured up to 90 degrees emits nothing above 90 degrees.
+    /// Consecutive ranges therefore sum to the integral over the measured range, which differs from `integrate_intensity`
+    /// only by the half spacing that it gives to the first and last samples beyond the ends of the range.
+    pub fn integrate_intensity_between(&self, lower: f64, upper: f64) -> f64 {
+        let n = self.angles.len().min(self.intensities.len());
+        if n < 2 || upper <= lower {
+            return 0.0;
+        }
+
+        let solid_angle_factor = |ang: f64| match self.orientation {
+            PlaneOrientation::Vertical => ang.sin(),
+            PlaneOrientation::Horizontal | PlaneOrientation::Longitudinal => ang.cos(),
+        };
+        self.width.total() * (0..n)
+            .map(|i| {
+                let half_below = 0.5 * (self.angles[i.max(1)] - self.angles[i.max(1) - 1]);
+                let half_above = 0.5 * (self.angles[(i + 1).min(n - 1)] - self.angles[(i + 1).min(n - 1) - 1]);
+                let cell_upper = f64::min(self.angles[i] + half_above, self.angles[n - 1]);
+                let cell_lower = f64::max(self.angles[i] - half_below, self.angles[0]);
+                let overlap = f64::min(cell_upper, upper) - f64::max(cell_lower, lower);
+                self.intensities[i] * solid_angle_factor(self.angles[i]) * overlap.max(0.0)
+            })
             .sum::<f64>()
     }
+
+    /// Linearly interpolates the intensity at a given angle (in radians) within the plane.
+    /// Angles outside of the measured range of the plane are assumed to emit no light, so zero is returned.
+    pub fn intensity_at(&self, angle: f64) -> f64 {
+        let n = self.angles.len().min(self.intensities.len());
+        if n == 0
+            || angle < self.angles[0] - ANGLE_TOLERANCE
+            || angle > self.angles[n - 1] + ANGLE_TOLERANCE
+        {
+            return 0.0;
+        }
+        if n == 1 {
+            return self.intensities[0];
+        }
+
+        // Find the pair of samples that bracket the requested angle.
+        let iupper = self.angles[..n]
+            .partition_point(|ang| *ang < angle)
+            .clamp(1, n - 1);
+        let (lower_angle, upper_angle) = (self.angles[iupper - 1], self.angles[iupper]);
+        if upper_angle <= lower_angle {
+            return self.intensities[iupper - 1];
+        }
+
+        let frac = ((angle - lower_angle) / (upper_angle - lower_angle)).clamp(0.0, 1.0);
+        (1.0 - frac) * self.intensities[iupper - 1] + frac * self.intensities[iupper]
+    }
+
+    /// Returns the largest intensity in the plane, or zero if the plane is empty.
+    pub fn max_intensity(&self) -> f64 {
+        self.intensities.iter().fold(0.0, |max, int| f64::max(max, *int))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Plane, PlaneWidth};
+    use crate::util::geom::degrees_to_radians;
     use approx::assert_abs_diff_eq;
 
     /// In this case, I am filling the array with a constant of 1.0, which simplifies the integral for each plane
@@ -162,5 +237,45 @@ mod tests {
 
         // Check that this is true to within 0.01 per cent.
         assert_abs_diff_eq!(plane.integrate_intensity(), 2.0, epsilon = 2.0E-4);
+
+        // Integrating over two halves should sum to the whole, with each half being 1 - cos(pi / 2) = 1.
+        let lower = plane.integrate_intensity_between(-1.0, degrees_to_radians(90.0));
+        let upper = plane.integrate_intensity_between(degrees_to_radians(90.0), 4.0);
+        assert_abs_diff_eq!(lower + upper, plane.integrate_intensity(), epsilon = 1E-12);
+        assert_abs_diff_eq!(lower, 1.0, epsilon = 2.0E-4);
+    }
+
+    /// A plane measured from 0 to 90 degrees, as in an IES file for a downlight, must not attribute any light to
+    /// directions above 90 degrees, and the lower hemisphere holds the integral of sin over the measured range.
+    #[test]
+    fn test_integrate_plane_between_ends_at_90() {
+        let mut plane = Plane::new();
+        plane.set_width(PlaneWidth::Symmetric(1.0));
+        plane.set_angles_degrees(&(0..=90).map(|ang| ang as f64).collect());
+        plane.set_intensities(vec![1.0; 91]);
+
+        assert_eq!(plane.integrate_intensity_between(degrees_to_radians(90.0), f64::INFINITY), 0.0);
+        assert_eq!(plane.integrate_intensity_between(degrees_to_radians(95.0), f64::INFINITY), 0.0);
+        assert_abs_diff_eq!(
+            plane.integrate_intensity_between(f64::NEG_INFINITY, degrees_to_radians(90.0)),
+            1.0,
+            epsilon = 1E-4
+        );
+    }
+
+    /// Checks that interpolation reproduces the samples exactly, interpolates linearly between them,
+    /// and returns zero outside of the measured range.
+    #[test]
+    fn test_intensity_at() {
+        let mut plane = Plane::new();
+        plane.set_angles_degrees(&vec![0.0, 10.0, 30.0]);
+        plane.set_intensities(vec![100.0, 50.0, 10.0]);
+
+        assert_eq!(plane.intensity_at(0.0), 100.0);
+        assert_eq!(plane.intensity_at(degrees_to_radians(30.0)), 10.0);
+        assert_abs_diff_eq!(plane.intensity_at(degrees_to_radians(5.0)), 75.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(plane.intensity_at(degrees_to_radians(20.0)), 30.0, epsilon = 1E-9);
+        assert_eq!(plane.intensity_at(degrees_to_radians(45.0)), 0.0);
+        assert_eq!(plane.max_intensity(), 100.0);
     }
 }
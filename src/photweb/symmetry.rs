@@ -0,0 +1,260 @@
+use super::{expand_symmetry, PhotometricWeb, Plane};
+use std::f64::consts::PI;
+
+/// The tolerance (in radians) used when deciding whether a plane lies within a sector.
+const SECTOR_TOLERANCE: f64 = 1E-9;
+
+/// The lateral symmetries that a Type C photometric web may have.
+/// These are the symmetries supported by the EULUMDAT and IES file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    /// No symmetry, all C-planes from 0 -> 360 degrees are required.
+    #[default]
+    None,
+    /// Symmetric about the vertical axis, a single C-plane describes the whole web.
+    Rotational,
+    /// Symmetric about the C0-C180 plane, the C-planes from 0 -> 180 degrees are required.
+    C0C180,
+    /// Symmetric about the C90-C270 plane, the C-planes from 90 -> 270 degrees are required.
+    C90C270,
+    /// Symmetric about both the C0-C180 and C90-C270 planes, the C-planes from 0 -> 90 degrees are required.
+    Quadrant,
+}
+
+impl Symmetry {
+    /// Infers the symmetry from the C-plane angles (in radians) that are present, following the IES conventions
+    /// for the horizontal angles of Type C photometry:
+    /// - A single plane is rotationally symmetric.
+    /// - Planes from 0 -> 90 degrees are symmetric in each quadrant.
+    /// - Planes from 0 -> 180 degrees are symmetric about the C0-C180 plane.
+    /// - Planes from 90 -> 270 degrees are symmetric about the C90-C270 plane.
+    /// - Anything else is assumed to have no symmetry.
+    pub fn from_plane_angles(angles: &[f64]) -> Self {
+        let is_close = |angle: f64, target: f64| (angle - target).abs() <= SECTOR_TOLERANCE.max(1E-6);
+        match (angles.first(), angles.last()) {
+            (Some(_), Some(_)) if angles.len() == 1 => Symmetry::Rotational,
+            (Some(first), Some(last)) if is_close(*first, 0.0) && is_close(*last, PI / 2.0) => Symmetry::Quadrant,
+            (Some(first), Some(last)) if is_close(*first, 0.0) && is_close(*last, PI) => Symmetry::C0C180,
+            (Some(first), Some(last)) if is_close(*first, PI / 2.0) && is_close(*last, 3.0 * PI / 2.0) => {
+                Symmetry::C90C270
+            }
+            _ => Symmetry::None,
+        }
+    }
+
+    /// Returns the range of C-plane angles (in radians) that is unique under this symmetry.
+    pub fn unique_sector(&self) -> (f64, f64) {
+        match self {
+            Symmetry::None => (0.0, 2.0 * PI),
+            Symmetry::Rotational => (0.0, 0.0),
+            Symmetry::C0C180 => (0.0, PI),
+            Symmetry::C90C270 => (PI / 2.0, 3.0 * PI / 2.0),
+            Symmetry::Quadrant => (0.0, PI / 2.0),
+        }
+    }
+
+    /// Returns the C-plane angles (in radians) that are equivalent to the given angle under this symmetry,
+    /// including the angle itself. The rotationally symmetric case is not included as every angle is equivalent.
+    fn equivalent_angles(&self, angle: f64) -> Vec<f64> {
+        match self {
+            Symmetry::None | Symmetry::Rotational => vec![angle],
+            Symmetry::C0C180 => vec![angle, -angle],
+            Symmetry::C90C270 => vec![angle, PI - angle],
+            Symmetry::Quadrant => vec![angle, -angle, PI - angle, PI + angle],
+        }
+    }
+}
+
+impl PhotometricWeb {
+    /// Determines the strongest symmetry that this Type C web satisfies, to within a tolerance relative to the peak intensity.
+    /// Each intensity is compared against the interpolated intensity in the mirrored direction(s).
+    /// The symmetries are tested from strongest to weakest: rotational, quadrant, C0-C180, C90-C270 and finally none.
+    pub fn detect_symmetry(&self, tolerance: f64) -> Symmetry {
+        if self.n_planes() <= 1 {
+            return Symmetry::Rotational;
+        }
+
+        let threshold = tolerance * self.max_intensity();
+        let satisfies = |mirror: &dyn Fn(f64) -> f64| {
+            self.planes().iter().all(|pl| {
+                pl.angles()
+                    .iter()
+                    .zip(pl.intensities())
+                    .all(|(ang, int)| (self.intensity_at(mirror(pl.angle()), *ang) - int).abs() <= threshold)
+            })
+        };
+
+        let first_angle = self.planes()[0].angle();
+        let c0c180 = satisfies(&|angle| -angle);
+        let c90c270 = satisfies(&|angle| PI - angle);
+        if satisfies(&|_| first_angle) {
+            Symmetry::Rotational
+        } else if c0c180 && c90c270 {
+            Symmetry::Quadrant
+        } else if c0c180 {
+            Symmetry::C0C180
+        } else if c90c270 {
+            Symmetry::C90C270
+        } else {
+            Symmetry::None
+        }
+    }
+
+    /// Returns the planes in the unique sector of the web for the given symmetry, discarding the redundant planes.
+    /// This is the reverse of `expand_symmetry`, which expands the unique sector back into a full set of planes.
+    pub fn compact(&self, symmetry: Symmetry) -> Vec<Plane> {
+        if symmetry == Symmetry::Rotational {
+            return self.planes().iter().take(1).cloned().collect();
+        }
+
+        let (start, end) = symmetry.unique_sector();
+        self.planes()
+            .iter()
+            .filter(|pl| {
+                let angle = pl.angle().rem_euclid(2.0 * PI);
+                angle >= start - SECTOR_TOLERANCE && angle <= end + SECTOR_TOLERANCE
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a web that exactly satisfies the given symmetry, useful for cleaning noisy measured data.
+    /// Each plane in the unique sector is replaced with the average of itself and its mirror images,
+    /// and the sector is then expanded back into a full web using `expand_symmetry`.
+    pub fn symmetrise(&self, symmetry: Symmetry) -> PhotometricWeb {
+        let all_angles: Vec<f64> = self.planes().iter().map(|pl| pl.angle()).collect();
+        let averaged: Vec<Plane> = self
+            .compact(symmetry)
+            .into_iter()
+            .map(|mut pl| {
+                let equivalent = match symmetry {
+                    Symmetry::Rotational => all_angles.clone(),
+                    _ => symmetry.equivalent_angles(pl.angle()),
+                };
+                let intensities: Vec<f64> = pl
+                    .angles()
+                    .iter()
+                    .map(|ang| {
+                        equivalent
+                            .iter()
+                            .map(|plane_angle| self.intensity_at(*plane_angle, *ang))
+                            .sum::<f64>()
+                            / equivalent.len() as f64
+                    })
+                    .collect();
+                pl.set_intensities(intensities);
+                pl
+            })
+            .collect();
+
+        let mut photweb = PhotometricWeb::new();
+        photweb.set_planes(expand_symmetry(&averaged, symmetry));
+        photweb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a web with C-planes every 10 degrees, with intensities given by a function of the C and gamma angles (in degrees).
+    fn web_from_fn(func: impl Fn(f64, f64) -> f64) -> PhotometricWeb {
+        let mut web = PhotometricWeb::new();
+        web.set_planes(
+            (0..360)
+                .step_by(10)
+                .map(|c_deg| {
+                    let mut plane = Plane::new();
+                    plane.set_angle_degrees(c_deg as f64);
+                    plane.set_angles_degrees(&(0..=180).step_by(10).map(|ang| ang as f64).collect());
+                    plane.set_intensities(
+                        plane
+                            .angles_deg()
+                            .iter()
+                            .map(|gamma| func(c_deg as f64, *gamma))
+                            .collect::<Vec<f64>>(),
+                    );
+                    plane
+                })
+                .collect(),
+        );
+        web
+    }
+
+    #[test]
+    fn test_detect_symmetry() {
+        let rotational = web_from_fn(|_, gamma| 100.0 + gamma);
+        assert_eq!(rotational.detect_symmetry(1E-6), Symmetry::Rotational);
+
+        let quadrant = web_from_fn(|c, gamma| 100.0 + gamma * (2.0 * c.to_radians()).cos());
+        assert_eq!(quadrant.detect_symmetry(1E-6), Symmetry::Quadrant);
+
+        let c0c180 = web_from_fn(|c, gamma| 100.0 + gamma * c.to_radians().cos());
+        assert_eq!(c0c180.detect_symmetry(1E-6), Symmetry::C0C180);
+
+        let c90c270 = web_from_fn(|c, gamma| 100.0 + gamma * c.to_radians().sin());
+        assert_eq!(c90c270.detect_symmetry(1E-6), Symmetry::C90C270);
+
+        let none = web_from_fn(|c, gamma| 100.0 + gamma * (c.to_radians() + 0.3).cos());
+        assert_eq!(none.detect_symmetry(1E-6), Symmetry::None);
+    }
+
+    /// Noise smaller than the tolerance should not prevent a symmetry from being detected.
+    #[test]
+    fn test_detect_symmetry_tolerance() {
+        let noisy = web_from_fn(|c, gamma| 100.0 + gamma + if c == 40.0 { 1.0 } else { 0.0 });
+        assert_eq!(noisy.detect_symmetry(1E-3), Symmetry::None);
+        assert_eq!(noisy.detect_symmetry(1E-2), Symmetry::Rotational);
+    }
+
+    /// Compacting a web and mirroring the sector back out should reproduce the original web.
+    #[test]
+    fn test_compact() {
+        let c0c180 = web_from_fn(|c, gamma| 100.0 + gamma * c.to_radians().cos());
+        let sector = c0c180.compact(Symmetry::C0C180);
+        assert_eq!(sector.len(), 19);
+
+        let expanded = expand_symmetry(&sector, Symmetry::C0C180);
+        assert_eq!(expanded.len(), 36);
+        for (expanded_plane, plane) in expanded.iter().zip(c0c180.planes()) {
+            assert!((expanded_plane.angle() - plane.angle()).abs() < 1E-9);
+            for (expanded_int, int) in expanded_plane.intensities().iter().zip(plane.intensities()) {
+                assert!((expanded_int - int).abs() < 1E-9);
+            }
+        }
+
+        assert_eq!(c0c180.compact(Symmetry::Quadrant).len(), 10);
+        assert_eq!(c0c180.compact(Symmetry::C90C270).len(), 19);
+        assert_eq!(c0c180.compact(Symmetry::Rotational).len(), 1);
+    }
+
+    #[test]
+    fn test_from_plane_angles() {
+        let from_degrees = |angles: &[f64]| {
+            Symmetry::from_plane_angles(&angles.iter().map(|ang| ang.to_radians()).collect::<Vec<f64>>())
+        };
+        assert_eq!(from_degrees(&[0.0]), Symmetry::Rotational);
+        assert_eq!(from_degrees(&[0.0, 45.0, 90.0]), Symmetry::Quadrant);
+        assert_eq!(from_degrees(&[0.0, 90.0, 180.0]), Symmetry::C0C180);
+        assert_eq!(from_degrees(&[90.0, 180.0, 270.0]), Symmetry::C90C270);
+        assert_eq!(from_degrees(&[0.0, 90.0, 180.0, 270.0, 360.0]), Symmetry::None);
+        assert_eq!(from_degrees(&[]), Symmetry::None);
+    }
+
+    /// Averaging the mirrored planes of a noisy web should result in a web that has the requested symmetry.
+    #[test]
+    fn test_symmetrise() {
+        let noisy = web_from_fn(|c, gamma| {
+            100.0 + gamma * (2.0 * c.to_radians()).cos() + (c * 7.0 + gamma * 3.0).sin()
+        });
+        assert_eq!(noisy.detect_symmetry(1E-6), Symmetry::None);
+
+        let symmetric = noisy.symmetrise(Symmetry::Quadrant);
+        assert_eq!(symmetric.n_planes(), 36);
+        assert_eq!(symmetric.detect_symmetry(1E-9), Symmetry::Quadrant);
+        assert!((symmetric.total_intensity() - noisy.total_intensity()).abs() < noisy.total_intensity() * 1E-3);
+
+        let rotational = noisy.symmetrise(Symmetry::Rotational);
+        assert_eq!(rotational.n_planes(), 1);
+    }
+}
@@ -0,0 +1,58 @@
+//! Photometric webs shared by the tests of the crate, built from analytic intensity distributions.
+
+use super::{PhotometricWeb, Plane};
+
+/// Returns `n + 1` angles (in degrees) equally spaced from zero to the given end, with n the number of steps that
+/// are closest to the requested step. The angles are computed as i x end / n, so integer steps are exact.
+fn angles_degrees(end: f64, step: f64) -> Vec<f64> {
+    let n = (end / step).round() as usize;
+    (0..=n).map(|i| i as f64 * end / n as f64).collect()
+}
+
+/// A Type C web of C-planes every `c_step` degrees, with gamma angles every `gamma_step` degrees, with intensities
+/// given by a function of the C and gamma angles (both in radians).
+pub fn planar_web(c_step: f64, gamma_step: f64, func: impl Fn(f64, f64) -> f64) -> PhotometricWeb {
+    let mut c_angles = angles_degrees(360.0, c_step);
+    c_angles.pop();
+    let mut web = PhotometricWeb::new();
+    web.set_planes(
+        c_angles
+            .iter()
+            .map(|c_deg| {
+                let mut plane = Plane::new();
+                plane.set_angle_degrees(*c_deg);
+                plane.set_angles_degrees(&angles_degrees(180.0, gamma_step));
+                plane.set_intensities(
+                    plane
+                        .angles()
+                        .iter()
+                        .map(|gamma| func(plane.angle(), *gamma))
+                        .collect::<Vec<f64>>(),
+                );
+                plane
+            })
+            .collect(),
+    );
+    web
+}
+
+/// A rotationally symmetric web of a single plane, with gamma angles every `gamma_step` degrees, with intensities
+/// given by a function of gamma (in radians).
+pub fn rotational_web(gamma_step: f64, func: impl Fn(f64) -> f64) -> PhotometricWeb {
+    let mut plane = Plane::new();
+    plane.set_angles_degrees(&angles_degrees(180.0, gamma_step));
+    plane.set_intensities(plane.angles().iter().map(|gamma| func(*gamma)).collect::<Vec<f64>>());
+    let mut web = PhotometricWeb::new();
+    web.set_planes(vec![plane]);
+    web
+}
+
+/// An isotropic source of the given intensity, as a single plane with gamma angles every degree.
+pub fn isotropic_web(intensity: f64) -> PhotometricWeb {
+    rotational_web(1.0, |_| intensity)
+}
+
+/// A rotationally symmetric Lambertian (cosine) distribution with the given intensity at the nadir, and no upward light.
+pub fn lambertian_web(intensity: f64) -> PhotometricWeb {
+    rotational_web(1.0, |gamma| intensity * gamma.cos().max(0.0))
+}
@@ -1,6 +1,8 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum IntensityUnits {
     Candela,
+    /// Dimensionless intensities, such as those normalised to a peak or the ratio of two webs.
+    Relative,
 }
 
 impl Default for IntensityUnits {
@@ -0,0 +1,233 @@
+//! Rendering of Cartesian intensity diagrams, with the intensity plotted against gamma for selected C-planes.
+//!
+//! Negative gamma angles are taken in the opposite C-plane, so that a range of -90 to 90 degrees shows the whole
+//! cross-section of a beam through the C-plane and its opposite.
+
+use super::{format_number, nice_step, IntensityScale, SvgDocument, SvgStyle, TextAnchor};
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+
+/// The step (in degrees) at which the curves are sampled.
+const GAMMA_STEP: f64 = 0.5;
+/// The number of divisions of the axes, at most.
+const MAX_DIVISIONS: (usize, usize) = (12, 6);
+/// The margins between the edges of the document and the plot area, as (left, right, top, bottom).
+const MARGINS: (f64, f64, f64, f64) = (60.0, 20.0, 20.0, 40.0);
+/// The height of each entry of the legend.
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+
+/// An intensity curve in a single C-plane, and how it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartesianCurve {
+    /// The C-plane of the curve, in degrees.
+    pub cplane: f64,
+    /// The style of the line of the curve.
+    pub style: SvgStyle,
+    /// The label of the curve in the legend, or `None` to label it with its C-plane.
+    pub label: Option<String>,
+}
+
+impl CartesianCurve {
+    /// Creates a curve in the C-plane (in degrees), drawn in a solid line of the given colour.
+    pub fn new(cplane: f64, colour: &str) -> Self {
+        Self {
+            cplane,
+            style: SvgStyle::stroke(colour, 1.5),
+            label: None,
+        }
+    }
+
+    /// The label of the curve in the legend.
+    pub fn legend_label(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("C{}", format_number(self.cplane, 1)))
+    }
+}
+
+/// The options of a Cartesian intensity diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartesianDiagramOptions {
+    /// The width of the plot, excluding the title and legend.
+    pub width: f64,
+    /// The height of the plot, excluding the title and legend.
+    pub height: f64,
+    /// The curves to draw.
+    pub curves: Vec<CartesianCurve>,
+    /// The scaling of the intensities.
+    pub scale: IntensityScale,
+    /// The range of gamma angles to plot, in degrees.
+    pub gamma_range: (f64, f64),
+    /// The intensity at the top of the axis, or `None` to fit the axis to the curves.
+    pub max_intensity: Option<f64>,
+    /// The title drawn above the diagram.
+    pub title: Option<String>,
+    /// Whether to draw the legend below the diagram.
+    pub legend: bool,
+    /// The colour of the background, or `None` for a transparent background.
+    pub background: Option<String>,
+    /// The style of the lines of the grid.
+    pub grid_style: SvgStyle,
+    /// The style of the labels.
+    pub label_style: SvgStyle,
+}
+
+impl Default for CartesianDiagramOptions {
+    /// The C0 plane in a solid red line and the C90 plane in a dashed blue line, from 0 to 90 degrees, in candela.
+    fn default() -> Self {
+        Self {
+            width: 500.0,
+            height: 320.0,
+            curves: vec![
+                CartesianCurve::new(0.0, "#d62728"),
+                CartesianCurve {
+                    style: SvgStyle::stroke("#1f77b4", 1.5).with_dash_array("6 3"),
+                    ..CartesianCurve::new(90.0, "#1f77b4")
+                },
+            ],
+            scale: IntensityScale::Candela,
+            gamma_range: (0.0, 90.0),
+            max_intensity: None,
+            title: None,
+            legend: true,
+            background: Some("white".to_string()),
+            grid_style: SvgStyle::stroke("#bbbbbb", 0.5),
+            label_style: SvgStyle::text("#444444", 10.0),
+        }
+    }
+}
+
+/// Samples the intensity in a C-plane (in degrees) over a range of gamma angles (in degrees), returning
+/// (gamma, intensity) pairs with gamma in degrees. Negative gamma angles are taken in the opposite C-plane.
+pub fn cartesian_curve(web: &PhotometricWeb, cplane: f64, gamma_range: (f64, f64)) -> Vec<(f64, f64)> {
+    let (lower, upper) = gamma_range;
+    let n = ((upper - lower) / GAMMA_STEP).round().max(0.0) as usize;
+    (0..=n)
+        .map(|i| {
+            let gamma = lower + (upper - lower) * i as f64 / n.max(1) as f64;
+            let cplane = if gamma < 0.0 { cplane + 180.0 } else { cplane };
+            (gamma, web.intensity_at(degrees_to_radians(cplane), degrees_to_radians(gamma.abs())))
+        })
+        .collect()
+}
+
+/// Renders the Cartesian intensity diagram of a Type C web as SVG.
+pub fn cartesian_diagram_svg(web: &PhotometricWeb, options: &CartesianDiagramOptions) -> SvgDocument {
+    let factor = options.scale.factor();
+    let curves: Vec<Vec<(f64, f64)>> = options
+        .curves
+        .iter()
+        .map(|curve| {
+            cartesian_curve(web, curve.cplane, options.gamma_range)
+                .into_iter()
+                .map(|(gamma, intensity)| (gamma, factor * intensity))
+                .collect()
+        })
+        .collect();
+
+    let max_intensity = options.max_intensity.unwrap_or_else(|| {
+        curves
+            .iter()
+            .flat_map(|curve| curve.iter().map(|(_, intensity)| *intensity))
+            .fold(0.0, f64::max)
+    });
+    let y_step = nice_step(max_intensity, MAX_DIVISIONS.1);
+    let y_max = y_step * ((max_intensity / y_step).ceil()).max(1.0);
+    let (gamma_lower, gamma_upper) = options.gamma_range;
+    let x_step = nice_step(gamma_upper - gamma_lower, MAX_DIVISIONS.0);
+
+    let title_height = if options.title.is_some() { 28.0 } else { 0.0 };
+    let legend_height = if options.legend {
+        LEGEND_ROW_HEIGHT * options.curves.len() as f64 + 8.0
+    } else {
+        0.0
+    };
+    let mut doc = SvgDocument::new(options.width, options.height + title_height + legend_height);
+    if let Some(background) = &options.background {
+        doc.rect(0.0, 0.0, doc.width, doc.height, &SvgStyle::fill(background));
+    }
+    if let Some(title) = &options.title {
+        let style = SvgStyle::text("#000000", options.label_style.font_size + 4.0);
+        doc.text(options.width / 2.0, 20.0, title, TextAnchor::Middle, &style);
+    }
+
+    let (left, right) = (MARGINS.0, options.width - MARGINS.1);
+    let (top, bottom) = (title_height + MARGINS.2, title_height + options.height - MARGINS.3);
+    let to_xy = |gamma: f64, intensity: f64| {
+        let x = if gamma_upper > gamma_lower {
+            left + (right - left) * (gamma - gamma_lower) / (gamma_upper - gamma_lower)
+        } else {
+            left
+        };
+        (x, bottom - (bottom - top) * intensity / y_max)
+    };
+
+    let first_tick = (gamma_lower / x_step).ceil() as i64;
+    let last_tick = (gamma_upper / x_step + 1E-9).floor() as i64;
+    for tick in first_tick..=last_tick {
+        let gamma = tick as f64 * x_step;
+        let (x, _) = to_xy(gamma, 0.0);
+        doc.line(x, top, x, bottom, &options.grid_style);
+        doc.text(x, bottom + 14.0, &format_number(gamma, 1), TextAnchor::Middle, &options.label_style);
+    }
+    let n_y = (y_max / y_step).round() as usize;
+    for tick in 0..=n_y {
+        let intensity = tick as f64 * y_step;
+        let (_, y) = to_xy(gamma_lower, intensity);
+        doc.line(left, y, right, y, &options.grid_style);
+        doc.text(left - 4.0, y + 4.0, &format_number(intensity, 2), TextAnchor::End, &options.label_style);
+    }
+    doc.rect(left, top, right - left, bottom - top, &SvgStyle::stroke("#444444", 1.0));
+    doc.text((left + right) / 2.0, bottom + 30.0, "\u{3b3} (\u{b0})", TextAnchor::Middle, &options.label_style);
+    doc.text(4.0, top - 6.0, options.scale.unit_symbol(), TextAnchor::Start, &options.label_style);
+
+    for (curve, points) in options.curves.iter().zip(curves.iter()) {
+        let xy: Vec<(f64, f64)> = points.iter().map(|(gamma, intensity)| to_xy(*gamma, *intensity)).collect();
+        doc.polyline(&xy, &curve.style);
+    }
+
+    if options.legend {
+        let legend_top = title_height + options.height;
+        for (i, curve) in options.curves.iter().enumerate() {
+            let y = legend_top + LEGEND_ROW_HEIGHT * (i as f64 + 0.5);
+            doc.line(left, y, left + 30.0, y, &curve.style);
+            doc.text(left + 38.0, y + 4.0, &curve.legend_label(), TextAnchor::Start, &options.label_style);
+        }
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::planar_web;
+    use std::f64::consts::FRAC_PI_2;
+
+    const SNAPSHOT_DEFAULT: &str = include_str!("snapshots/cartesian_default.svg");
+
+    /// A narrow beam, wider in the C90-C270 planes than in the C0-C180 planes, with planes every 90 degrees and gamma
+    /// every 5 degrees.
+    fn test_web() -> PhotometricWeb {
+        planar_web(90.0, 5.0, |c_angle, gamma| {
+            let sigma = if (c_angle / FRAC_PI_2).round() as i32 % 2 == 0 { 10.0_f64 } else { 20.0 };
+            12000.0 * (-0.5 * (gamma.to_degrees() / sigma).powi(2)).exp()
+        })
+    }
+
+    #[test]
+    fn test_cartesian_curve() {
+        let web = test_web();
+        let curve = cartesian_curve(&web, 0.0, (-10.0, 10.0));
+        assert_eq!(curve.len(), 41);
+        assert_eq!(curve[20], (0.0, 12000.0));
+        assert!((curve[0].1 - curve[40].1).abs() < 1E-9);
+    }
+
+    #[test]
+    fn test_cartesian_diagram_snapshot() {
+        let options = CartesianDiagramOptions {
+            gamma_range: (-60.0, 60.0),
+            ..CartesianDiagramOptions::default()
+        };
+        assert_eq!(cartesian_diagram_svg(&test_web(), &options).to_string(), SNAPSHOT_DEFAULT);
+    }
+}
@@ -0,0 +1,116 @@
+//! Rendering of the cone diagram of a spotlight: the beam drawn to scale, with the beam diameter and the centre and
+//! average illuminances tabulated alongside each throw distance.
+
+use super::{format_number, SvgDocument, SvgStyle, TextAnchor};
+use crate::analysis::ConeDiagram;
+
+/// The width of the document.
+const WIDTH: f64 = 560.0;
+/// The height allowed for each row of the diagram.
+const ROW_HEIGHT: f64 = 50.0;
+/// The vertical position of the apex of the cone.
+const APEX_Y: f64 = 60.0;
+/// The horizontal position of the axis of the cone.
+const AXIS_X: f64 = 140.0;
+/// The largest half-width of the drawn cone.
+const MAX_HALF_WIDTH: f64 = 120.0;
+/// The horizontal positions of the columns of the table.
+const COLUMNS: [f64; 4] = [320.0, 380.0, 450.0, 530.0];
+
+/// Renders a cone diagram as SVG.
+pub fn cone_diagram_svg(diagram: &ConeDiagram) -> SvgDocument {
+    let n_rows = diagram.rows.len();
+    let mut doc = SvgDocument::new(WIDTH, APEX_Y + ROW_HEIGHT * n_rows as f64 + 30.0);
+    let (length, illuminance) = (diagram.units.length_symbol(), diagram.units.illuminance_symbol());
+    let text = SvgStyle::text("#000", 12.0);
+    let label = SvgStyle::text("#444", 11.0);
+
+    doc.text(
+        WIDTH / 2.0,
+        20.0,
+        &format!(
+            "Beam angle {}\u{b0}, axial intensity {} cd",
+            format_number(diagram.beam_angle, 1),
+            format_number(diagram.axial_intensity, 0)
+        ),
+        TextAnchor::Middle,
+        &text,
+    );
+    let headers = [
+        format!("d ({})", length),
+        format!("\u{d8} ({})", length),
+        format!("E ({})", illuminance),
+        format!("Eav ({})", illuminance),
+    ];
+    for (x, header) in COLUMNS.iter().zip(headers.iter()) {
+        doc.text(*x, APEX_Y - 15.0, header, TextAnchor::End, &label);
+    }
+
+    let (max_distance, max_diameter) = diagram
+        .rows
+        .iter()
+        .fold((0.0, 0.0), |(dist, diam): (f64, f64), row| (dist.max(row.distance), diam.max(row.diameter)));
+    if max_distance <= 0.0 {
+        return doc;
+    }
+    let vertical_scale = ROW_HEIGHT * n_rows as f64 / max_distance;
+    let horizontal_scale = if max_diameter > 0.0 {
+        vertical_scale.min(2.0 * MAX_HALF_WIDTH / max_diameter)
+    } else {
+        vertical_scale
+    };
+
+    let bottom = APEX_Y + max_distance * vertical_scale;
+    let half_width = max_diameter * horizontal_scale / 2.0;
+    doc.polygon(
+        &[(AXIS_X, APEX_Y), (AXIS_X + half_width, bottom), (AXIS_X - half_width, bottom)],
+        &SvgStyle::stroke("#c8a000", 1.0).with_fill("#ffe680", 0.6),
+    );
+    doc.line(AXIS_X, APEX_Y, AXIS_X, bottom, &SvgStyle::stroke("#888", 0.5).with_dash_array("4 2"));
+
+    for row in &diagram.rows {
+        let y = APEX_Y + row.distance * vertical_scale;
+        let half_width = row.diameter * horizontal_scale / 2.0;
+        doc.line(AXIS_X - half_width, y, AXIS_X + half_width, y, &SvgStyle::stroke("#000", 1.0));
+        let values = [
+            format_number(row.distance, 1),
+            format_number(row.diameter, 2),
+            format_number(row.centre_illuminance, 0),
+            format_number(row.average_illuminance, 0),
+        ];
+        for (x, value) in COLUMNS.iter().zip(values.iter()) {
+            doc.text(*x, y + 4.0, value, TextAnchor::End, &text);
+        }
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{ConeDiagramRow, ConeUnits};
+
+    #[test]
+    fn test_cone_diagram_svg() {
+        let diagram = ConeDiagram {
+            units: ConeUnits::Metric,
+            beam_angle: 30.0,
+            axial_intensity: 1000.0,
+            beam_flux: 200.0,
+            rows: (1..=2)
+                .map(|dist| ConeDiagramRow {
+                    distance: dist as f64,
+                    diameter: 0.5 * dist as f64,
+                    centre_illuminance: 1000.0 / (dist * dist) as f64,
+                    average_illuminance: 800.0 / (dist * dist) as f64,
+                })
+                .collect(),
+        };
+        let svg = cone_diagram_svg(&diagram).to_string();
+        assert!(svg.contains("Beam angle 30\u{b0}, axial intensity 1000 cd"));
+        assert!(svg.contains("<polygon points=\"140,60 165,160 115,160\""));
+        assert!(svg.contains("<line x1=\"127.5\" y1=\"110\" x2=\"152.5\" y2=\"110\""));
+        assert!(svg.contains(">250</text>"));
+        assert_eq!(svg.matches("<text").count(), 1 + 4 + 2 * 4);
+    }
+}
@@ -0,0 +1,217 @@
+//! Extraction of contour lines from values sampled on a rectangular grid, using marching squares.
+//!
+//! The values are indexed by [row][column], with the rows at the y coordinates and the columns at the x coordinates.
+//! Cells containing values that are not finite, such as samples outside of a projection, are skipped.
+
+use super::format_number;
+use std::collections::HashMap;
+
+/// A contour line at a single level, as a polyline in the coordinates of the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContourLine {
+    /// The value of the contour.
+    pub level: f64,
+    /// The points along the line.
+    pub points: Vec<(f64, f64)>,
+    /// Whether the line is a closed loop, whose last point joins the first.
+    pub closed: bool,
+}
+
+/// An edge of a cell of the grid, identified by its lower corner and whether it runs along x (true) or along y (false).
+type Edge = (usize, usize, bool);
+
+/// Extracts the contour lines at each of the levels from values sampled on a grid.
+/// Lines are returned in order of level, and are joined across cells into continuous polylines.
+pub fn contour_lines(xs: &[f64], ys: &[f64], values: &[Vec<f64>], levels: &[f64]) -> Vec<ContourLine> {
+    levels
+        .iter()
+        .flat_map(|level| contour_level(xs, ys, values, *level))
+        .collect()
+}
+
+/// Writes contour lines as a GeoJSON feature collection of line strings, in the coordinates of the grid.
+/// Each feature has the level of its contour, a label of the level with the given unit, and whether it is closed.
+/// Closed lines repeat their first point at the end, as GeoJSON requires.
+pub fn contours_geojson(contours: &[ContourLine], unit: &str) -> String {
+    let features: Vec<String> = contours
+        .iter()
+        .map(|contour| {
+            let points = contour
+                .points
+                .iter()
+                .chain(contour.points.first().filter(|_| contour.closed))
+                .map(|(x, y)| format!("[{},{}]", format_number(*x, 3), format_number(*y, 3)))
+                .collect::<Vec<String>>()
+                .join(",");
+            let label = format!("{} {}", format_number(contour.level, 2), unit).replace('"', "\\\"");
+            format!(
+                "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[{}]}},\
+                 \"properties\":{{\"level\":{},\"label\":\"{}\",\"closed\":{}}}}}",
+                points, contour.level, label, contour.closed
+            )
+        })
+        .collect();
+    format!("{{\"type\":\"FeatureCollection\",\"features\":[{}]}}", features.join(","))
+}
+
+/// Extracts the contour lines at a single level.
+fn contour_level(xs: &[f64], ys: &[f64], values: &[Vec<f64>], level: f64) -> Vec<ContourLine> {
+    let (n_x, n_y) = (xs.len(), ys.len().min(values.len()));
+    let value = |ix: usize, iy: usize| values[iy].get(ix).copied().unwrap_or(f64::NAN);
+
+    // The point where the contour crosses an edge, linearly interpolated between its corners.
+    let crossing = |(ix, iy, along_x): Edge| {
+        let (ix2, iy2) = if along_x { (ix + 1, iy) } else { (ix, iy + 1) };
+        let (v1, v2) = (value(ix, iy), value(ix2, iy2));
+        let frac = if v2 != v1 { ((level - v1) / (v2 - v1)).clamp(0.0, 1.0) } else { 0.5 };
+        (xs[ix] + frac * (xs[ix2] - xs[ix]), ys[iy] + frac * (ys[iy2] - ys[iy]))
+    };
+
+    let mut segments: Vec<(Edge, Edge)> = Vec::new();
+    for iy in 0..n_y.saturating_sub(1) {
+        for ix in 0..n_x.saturating_sub(1) {
+            let corners = [value(ix, iy), value(ix + 1, iy), value(ix + 1, iy + 1), value(ix, iy + 1)];
+            if corners.iter().any(|val| !val.is_finite()) {
+                continue;
+            }
+            let case = corners
+                .iter()
+                .enumerate()
+                .fold(0, |case, (i, val)| if *val >= level { case | (1 << i) } else { case });
+            let (bottom, right, top, left) = ((ix, iy, true), (ix + 1, iy, false), (ix, iy + 1, true), (ix, iy, false));
+            let centre_above = corners.iter().sum::<f64>() / 4.0 >= level;
+            let cell: &[(Edge, Edge)] = match case {
+                1 | 14 => &[(left, bottom)],
+                2 | 13 => &[(bottom, right)],
+                3 | 12 => &[(left, right)],
+                4 | 11 => &[(right, top)],
+                6 | 9 => &[(bottom, top)],
+                7 | 8 => &[(left, top)],
+                5 if centre_above => &[(left, top), (bottom, right)],
+                5 => &[(left, bottom), (right, top)],
+                10 if centre_above => &[(left, bottom), (right, top)],
+                10 => &[(left, top), (bottom, right)],
+                _ => &[],
+            };
+            segments.extend_from_slice(cell);
+        }
+    }
+
+    join_segments(&segments)
+        .into_iter()
+        .map(|(edges, closed)| ContourLine {
+            level,
+            points: edges.into_iter().map(crossing).collect(),
+            closed,
+        })
+        .collect()
+}
+
+/// Joins segments that share edges into chains of edges, returning each chain and whether it is closed.
+fn join_segments(segments: &[(Edge, Edge)]) -> Vec<(Vec<Edge>, bool)> {
+    let mut by_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (i, (start, end)) in segments.iter().enumerate() {
+        by_edge.entry(*start).or_default().push(i);
+        by_edge.entry(*end).or_default().push(i);
+    }
+    let mut used = vec![false; segments.len()];
+    let next_from = |edge: Edge, used: &mut [bool]| {
+        let i = *by_edge.get(&edge)?.iter().find(|i| !used[**i])?;
+        used[i] = true;
+        let (start, end) = segments[i];
+        Some(if start == edge { end } else { start })
+    };
+
+    let mut chains = Vec::new();
+    for i in 0..segments.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let (start, end) = segments[i];
+        let mut chain = vec![start, end];
+        while let Some(edge) = next_from(*chain.last().unwrap_or(&end), &mut used) {
+            chain.push(edge);
+        }
+        let closed = chain.len() > 2 && chain.first() == chain.last();
+        if !closed {
+            // The line ends at the boundary of the grid or of the finite values, so extend it backwards as well.
+            let mut backwards = Vec::new();
+            while let Some(edge) = next_from(*backwards.last().unwrap_or(&start), &mut used) {
+                backwards.push(edge);
+            }
+            chain = backwards.into_iter().rev().chain(chain).collect();
+        } else {
+            chain.pop();
+        }
+        chains.push((chain, closed));
+    }
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn grid(n: usize, func: impl Fn(f64, f64) -> f64) -> (Vec<f64>, Vec<f64>, Vec<Vec<f64>>) {
+        let coords: Vec<f64> = (0..n).map(|i| -2.0 + 4.0 * i as f64 / (n - 1) as f64).collect();
+        let values = coords.iter().map(|y| coords.iter().map(|x| func(*x, *y)).collect()).collect();
+        (coords.clone(), coords, values)
+    }
+
+    /// The contours of a paraboloid are circles, so each level gives a single closed loop of radius sqrt(level).
+    #[test]
+    fn test_circles() {
+        let (xs, ys, values) = grid(81, |x, y| x * x + y * y);
+        let lines = contour_lines(&xs, &ys, &values, &[0.5, 1.0, 2.0]);
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            assert!(line.closed);
+            for (x, y) in &line.points {
+                assert_abs_diff_eq!(x.hypot(*y), line.level.sqrt(), epsilon = 0.01);
+            }
+        }
+    }
+
+    /// A plane crosses the grid in an open straight line, and the parts of the grid that are not finite are skipped.
+    #[test]
+    fn test_open_lines() {
+        let (xs, ys, values) = grid(11, |x, _| x);
+        let lines = contour_lines(&xs, &ys, &values, &[0.3]);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].closed);
+        assert_eq!(lines[0].points.len(), 11);
+        assert!(lines[0].points.iter().all(|(x, _)| (x - 0.3).abs() < 1E-9));
+
+        let (xs, ys, values) = grid(11, |x, y| if y > 0.5 { f64::NAN } else { x });
+        let lines = contour_lines(&xs, &ys, &values, &[0.3]);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].points.iter().all(|(_, y)| *y <= 0.4 + 1E-9));
+        assert!(contour_lines(&xs, &ys, &values, &[5.0]).is_empty());
+    }
+
+    #[test]
+    fn test_geojson() {
+        let contours = vec![
+            ContourLine {
+                level: 10.0,
+                points: vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+                closed: true,
+            },
+            ContourLine {
+                level: 2.5,
+                points: vec![(0.1234, -1.5), (2.0, 3.0)],
+                closed: false,
+            },
+        ];
+        assert_eq!(
+            contours_geojson(&contours, "lx"),
+            "{\"type\":\"FeatureCollection\",\"features\":[\
+             {\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[0,0],[1,0],[1,1],[0,0]]},\
+             \"properties\":{\"level\":10,\"label\":\"10 lx\",\"closed\":true}},\
+             {\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[0.123,-1.5],[2,3]]},\
+             \"properties\":{\"level\":2.5,\"label\":\"2.5 lx\",\"closed\":false}}]}"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Rendering of isocandela diagrams, with contours of equal intensity drawn over a projection of the sphere of directions.
+//!
+//! The contours are found by marching squares over the web, interpolated on a regular grid in the projection.
+
+use super::{contour_lines, format_number, ContourLine, IntensityScale, SvgDocument, SvgStyle, TextAnchor};
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+
+/// The fractions of the peak intensity at which the contours are drawn by default.
+pub const ISOCANDELA_FRACTIONS: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+/// The angle (in degrees) between the lines of the grid.
+const GRID_STEP: f64 = 30.0;
+/// The margin around the plot, leaving room for the grid labels.
+const MARGIN: f64 = 36.0;
+/// The height of each entry of the legend.
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+
+/// The projection of the sphere of directions onto the plane of the diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsocandelaProjection {
+    /// A rectangular projection, with the C angle (0 to 360 degrees) along x and gamma (0 to 180 degrees) along y.
+    CGamma,
+    /// The Lambert azimuthal equal-area projection centred on the nadir, with the C0 plane to the right and the C90
+    /// plane upwards. A direction at gamma lies at a radius of 2 sin(gamma / 2), so the zenith forms the outer circle.
+    EqualArea,
+}
+
+impl IsocandelaProjection {
+    /// The extents of the projection as (x_min, x_max, y_min, y_max).
+    pub fn extents(&self) -> (f64, f64, f64, f64) {
+        match self {
+            Self::CGamma => (0.0, 360.0, 0.0, 180.0),
+            Self::EqualArea => (-2.0, 2.0, -2.0, 2.0),
+        }
+    }
+
+    /// Projects a direction given as (C, gamma) in degrees onto the plane of the diagram.
+    pub fn project(&self, c_angle: f64, gamma: f64) -> (f64, f64) {
+        match self {
+            Self::CGamma => (c_angle, gamma),
+            Self::EqualArea => {
+                let r = 2.0 * degrees_to_radians(gamma / 2.0).sin();
+                let c = degrees_to_radians(c_angle);
+                (r * c.cos(), r * c.sin())
+            }
+        }
+    }
+
+    /// Returns the direction as (C, gamma) in degrees of a point on the plane of the diagram, or `None` if the point
+    /// is outside of the projection.
+    pub fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        match self {
+            Self::CGamma => ((0.0..=360.0).contains(&x) && (0.0..=180.0).contains(&y)).then_some((x, y)),
+            Self::EqualArea => {
+                let r = x.hypot(y);
+                (r <= 2.0).then(|| {
+                    let c_angle = y.atan2(x).to_degrees().rem_euclid(360.0);
+                    (c_angle, 2.0 * (r / 2.0).asin().to_degrees())
+                })
+            }
+        }
+    }
+}
+
+/// Computes the isocandela contours of a Type C web at the given intensities, in the coordinates of the projection.
+/// The web is sampled on a grid of the given number of points along each axis of the projection.
+pub fn isocandela_contours(
+    web: &PhotometricWeb,
+    projection: IsocandelaProjection,
+    levels: &[f64],
+    resolution: usize,
+) -> Vec<ContourLine> {
+    let (x_min, x_max, y_min, y_max) = projection.extents();
+    let n = resolution.max(2);
+    let axis = |lower: f64, upper: f64| -> Vec<f64> {
+        (0..n)
+            .map(|i| lower + (upper - lower) * i as f64 / (n - 1) as f64)
+            .collect()
+    };
+    let (xs, ys) = (axis(x_min, x_max), axis(y_min, y_max));
+    let values: Vec<Vec<f64>> = ys
+        .iter()
+        .map(|y| {
+            xs.iter()
+                .map(|x| {
+                    projection.unproject(*x, *y).map_or(f64::NAN, |(c_angle, gamma)| {
+                        web.intensity_at(degrees_to_radians(c_angle), degrees_to_radians(gamma))
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    contour_lines(&xs, &ys, &values, levels)
+}
+
+/// The options of an isocandela diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsocandelaDiagramOptions {
+    /// The width of the plot, excluding the title and legend.
+    pub size: f64,
+    /// The projection of the diagram.
+    pub projection: IsocandelaProjection,
+    /// The scaling of the intensities.
+    pub scale: IntensityScale,
+    /// The scaled intensities of the contours, or `None` for the standard fractions of the peak intensity.
+    pub levels: Option<Vec<f64>>,
+    /// The number of points along each axis of the grid the web is sampled on.
+    pub resolution: usize,
+    /// The colours of the contours, repeated if there are more contours than colours.
+    pub colours: Vec<String>,
+    /// Whether to label the contours with their intensities.
+    pub label_contours: bool,
+    /// The title drawn above the diagram.
+    pub title: Option<String>,
+    /// Whether to draw the legend below the diagram.
+    pub legend: bool,
+    /// The colour of the background, or `None` for a transparent background.
+    pub background: Option<String>,
+    /// The style of the lines of the grid.
+    pub grid_style: SvgStyle,
+    /// The style of the labels.
+    pub label_style: SvgStyle,
+}
+
+impl Default for IsocandelaDiagramOptions {
+    fn default() -> Self {
+        Self {
+            size: 400.0,
+            projection: IsocandelaProjection::EqualArea,
+            scale: IntensityScale::Candela,
+            levels: None,
+            resolution: 181,
+            colours: ["#440154", "#3b528b", "#21918c", "#5ec962", "#fde725"]
+                .iter()
+                .map(|colour| colour.to_string())
+                .collect(),
+            label_contours: true,
+            title: None,
+            legend: true,
+            background: Some("white".to_string()),
+            grid_style: SvgStyle::stroke("#bbbbbb", 0.5),
+            label_style: SvgStyle::text("#444444", 10.0),
+        }
+    }
+}
+
+/// Renders the isocandela diagram of a Type C web as SVG.
+pub fn isocandela_diagram_svg(web: &PhotometricWeb, options: &IsocandelaDiagramOptions) -> SvgDocument {
+    let factor = options.scale.factor();
+    let levels: Vec<f64> = options.levels.clone().unwrap_or_else(|| {
+        let peak = factor * web.max_intensity();
+        ISOCANDELA_FRACTIONS.iter().map(|fraction| fraction * peak).collect()
+    });
+    // The contours are found in the units of the web, and labelled in the scaled units.
+    let unscaled: Vec<f64> = levels
+        .iter()
+        .map(|level| if factor > 0.0 { level / factor } else { *level })
+        .collect();
+    let contours = isocandela_contours(web, options.projection, &unscaled, options.resolution);
+
+    let (x_min, x_max, y_min, y_max) = options.projection.extents();
+    let plot_width = options.size - 2.0 * MARGIN;
+    let plot_height = plot_width * (y_max - y_min) / (x_max - x_min);
+    let title_height = if options.title.is_some() { 28.0 } else { 0.0 };
+    let legend_height = if options.legend {
+        LEGEND_ROW_HEIGHT * levels.len() as f64 + 8.0
+    } else {
+        0.0
+    };
+    let plot_bottom = title_height + plot_height + 2.0 * MARGIN;
+    let mut doc = SvgDocument::new(options.size, plot_bottom + legend_height);
+    if let Some(background) = &options.background {
+        doc.rect(0.0, 0.0, doc.width, doc.height, &SvgStyle::fill(background));
+    }
+    if let Some(title) = &options.title {
+        let style = SvgStyle::text("#000000", options.label_style.font_size + 4.0);
+        doc.text(options.size / 2.0, 20.0, title, TextAnchor::Middle, &style);
+    }
+
+    // Gamma increases down the page in the rectangular projection, and the C90 plane points up the page in the
+    // equal-area projection.
+    let (left, top) = (MARGIN, title_height + MARGIN);
+    let to_xy = |x: f64, y: f64| {
+        let sx = left + plot_width * (x - x_min) / (x_max - x_min);
+        let sy = match options.projection {
+            IsocandelaProjection::CGamma => top + plot_height * (y - y_min) / (y_max - y_min),
+            IsocandelaProjection::EqualArea => top + plot_height * (y_max - y) / (y_max - y_min),
+        };
+        (sx, sy)
+    };
+
+    let n_grid = |range: f64| (range / GRID_STEP).round() as usize;
+    match options.projection {
+        IsocandelaProjection::CGamma => {
+            for i in 0..=n_grid(360.0) {
+                let c_angle = i as f64 * GRID_STEP;
+                let ((x1, y1), (x2, y2)) = (to_xy(c_angle, 0.0), to_xy(c_angle, 180.0));
+                doc.line(x1, y1, x2, y2, &options.grid_style);
+                let label = format!("C{}", format_number(c_angle, 0));
+                doc.text(x1, y1 - 6.0, &label, TextAnchor::Middle, &options.label_style);
+            }
+            for i in 0..=n_grid(180.0) {
+                let gamma = i as f64 * GRID_STEP;
+                let ((x1, y1), (x2, y2)) = (to_xy(0.0, gamma), to_xy(360.0, gamma));
+                doc.line(x1, y1, x2, y2, &options.grid_style);
+                let label = format!("{}\u{b0}", format_number(gamma, 0));
+                doc.text(x1 - 4.0, y1 + 4.0, &label, TextAnchor::End, &options.label_style);
+            }
+        }
+        IsocandelaProjection::EqualArea => {
+            let (cx, cy) = to_xy(0.0, 0.0);
+            for i in 1..=n_grid(180.0) {
+                let gamma = i as f64 * GRID_STEP;
+                let (x, _) = to_xy(options.projection.project(0.0, gamma).0, 0.0);
+                doc.circle(cx, cy, x - cx, &options.grid_style);
+                let label = format!("{}\u{b0}", format_number(gamma, 0));
+                doc.text(x + 2.0, cy - 2.0, &label, TextAnchor::Start, &options.label_style);
+            }
+            for i in 0..n_grid(360.0) {
+                let c_angle = i as f64 * GRID_STEP;
+                let (px, py) = options.projection.project(c_angle, 180.0);
+                let (x, y) = to_xy(px, py);
+                doc.line(cx, cy, x, y, &options.grid_style);
+                let (lx, ly) = to_xy(px * 1.08, py * 1.08);
+                let label = format!("C{}", format_number(c_angle, 0));
+                doc.text(lx, ly + 4.0, &label, TextAnchor::Middle, &options.label_style);
+            }
+        }
+    }
+
+    let colour = |i: usize| options.colours.get(i % options.colours.len().max(1)).map_or("black", |col| col);
+    for contour in &contours {
+        let i = unscaled.iter().position(|level| *level == contour.level).unwrap_or(0);
+        let points: Vec<(f64, f64)> = contour.points.iter().map(|(x, y)| to_xy(*x, *y)).collect();
+        let style = SvgStyle::stroke(colour(i), 1.5);
+        if contour.closed {
+            doc.polygon(&points, &style);
+        } else {
+            doc.polyline(&points, &style);
+        }
+        if options.label_contours && !points.is_empty() {
+            let (x, y) = points[points.len() / 2];
+            doc.text(x, y - 2.0, &format_level(levels[i]), TextAnchor::Middle, &options.label_style);
+        }
+    }
+
+    if options.legend {
+        for (i, level) in levels.iter().enumerate() {
+            let y = plot_bottom + LEGEND_ROW_HEIGHT * (i as f64 + 0.5);
+            doc.line(MARGIN, y, MARGIN + 30.0, y, &SvgStyle::stroke(colour(i), 1.5));
+            let label = format!("{} {}", format_level(*level), options.scale.unit_symbol());
+            doc.text(MARGIN + 38.0, y + 4.0, &label, TextAnchor::Start, &options.label_style);
+        }
+    }
+    doc
+}
+
+/// Formats the intensity of a contour, with fewer decimal places for larger intensities.
+fn format_level(level: f64) -> String {
+    format_number(level, if level.abs() >= 100.0 { 0 } else { 1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::rotational_web;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_projection() {
+        let projection = IsocandelaProjection::EqualArea;
+        let (x, y) = projection.project(90.0, 60.0);
+        assert_abs_diff_eq!(x, 0.0, epsilon = 1E-12);
+        assert_abs_diff_eq!(y, 1.0, epsilon = 1E-12);
+        let (c_angle, gamma) = projection.unproject(x, y).unwrap();
+        assert_abs_diff_eq!(c_angle, 90.0, epsilon = 1E-9);
+        assert_abs_diff_eq!(gamma, 60.0, epsilon = 1E-9);
+        assert_eq!(projection.unproject(2.0, 1.0), None);
+        assert_eq!(IsocandelaProjection::CGamma.unproject(90.0, 200.0), None);
+    }
+
+    /// The isocandela contours of a rotationally symmetric web are circles around the nadir in the equal-area projection,
+    /// and straight lines of constant gamma in the rectangular projection.
+    #[test]
+    fn test_rotational_contours() {
+        let web = rotational_web(1.0, |gamma| 1000.0 * gamma.cos().max(0.0));
+        let half_gamma = 60.0;
+        let contours = isocandela_contours(&web, IsocandelaProjection::EqualArea, &[500.0], 101);
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        let radius = 2.0 * degrees_to_radians(half_gamma / 2.0).sin();
+        for (x, y) in &contours[0].points {
+            assert_abs_diff_eq!(x.hypot(*y), radius, epsilon = 0.01);
+        }
+
+        let contours = isocandela_contours(&web, IsocandelaProjection::CGamma, &[500.0], 181);
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        assert!(contours[0].points.iter().all(|(_, gamma)| (gamma - half_gamma).abs() < 0.1));
+    }
+
+    #[test]
+    fn test_isocandela_diagram() {
+        let web = rotational_web(1.0, |gamma| 1000.0 * gamma.cos().max(0.0));
+        let options = IsocandelaDiagramOptions {
+            resolution: 61,
+            ..IsocandelaDiagramOptions::default()
+        };
+        let svg = isocandela_diagram_svg(&web, &options).to_string();
+        assert_eq!(svg.matches("<polygon").count(), ISOCANDELA_FRACTIONS.len());
+        assert!(svg.contains(">500 cd</text>"));
+        assert!(svg.contains(">C90</text>"));
+
+        let options = IsocandelaDiagramOptions {
+            projection: IsocandelaProjection::CGamma,
+            scale: IntensityScale::CandelaPerKilolumen(2000.0),
+            levels: Some(vec![250.0]),
+            resolution: 61,
+            ..IsocandelaDiagramOptions::default()
+        };
+        let svg = isocandela_diagram_svg(&web, &options).to_string();
+        assert_eq!(svg.matches("<polyline").count(), 1);
+        assert!(svg.contains(">250 cd/klm</text>"));
+    }
+}
@@ -0,0 +1,255 @@
+//! Isolux diagrams: contours of equal illuminance over an illuminance grid, drawn to scale in the coordinates of the
+//! grid, and exported as GeoJSON for overlaying on site plans.
+
+use super::{
+    contour_lines, contours_geojson, format_number, nice_step, ContourLine, IntensityScale, SvgDocument, SvgStyle,
+    TextAnchor,
+};
+use crate::calc::IlluminanceGrid;
+
+/// The multiples of each power of ten at which the contours are drawn by default.
+const STANDARD_MULTIPLES: [f64; 3] = [1.0, 2.0, 5.0];
+/// The ratio of the largest to the smallest illuminance of the default contours.
+const STANDARD_RANGE: f64 = 100.0;
+/// The number of divisions of the axes, at most.
+const MAX_DIVISIONS: usize = 8;
+/// The margin around the plot, leaving room for the axis labels.
+const MARGIN: f64 = 40.0;
+/// The height of each entry of the legend.
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+
+/// Returns the standard isolux levels for a grid with the given maximum illuminance: the values of 1, 2 and 5 times a
+/// power of ten below the maximum, down to one hundredth of it.
+pub fn standard_isolux_levels(max: f64) -> Vec<f64> {
+    if max <= 0.0 || !max.is_finite() {
+        return Vec::new();
+    }
+    let lowest = max / STANDARD_RANGE;
+    let first_power = lowest.log10().floor() as i32;
+    let last_power = max.log10().floor() as i32;
+    (first_power..=last_power)
+        .flat_map(|power| STANDARD_MULTIPLES.iter().map(move |multiple| multiple * 10_f64.powi(power)))
+        .filter(|level| *level >= lowest && *level < max)
+        .collect()
+}
+
+/// Extracts the isolux contours of an illuminance grid at the given illuminances, in the coordinates of the grid.
+pub fn isolux_contours(grid: &IlluminanceGrid, levels: &[f64]) -> Vec<ContourLine> {
+    let (n_x, n_y) = (grid.grid.n_x, grid.grid.n_y);
+    let points = grid.grid.points();
+    let xs: Vec<f64> = (0..n_x).map(|ix| points[ix][0].x).collect();
+    let ys: Vec<f64> = (0..n_y).map(|iy| points.first().map_or(0.0, |row| row[iy].y)).collect();
+    // The grid is indexed by [x][y], while the contours are extracted from rows of constant y.
+    let values: Vec<Vec<f64>> = (0..n_y)
+        .map(|iy| (0..n_x).map(|ix| grid.values[ix][iy]).collect())
+        .collect();
+    contour_lines(&xs, &ys, &values, levels)
+}
+
+/// Extracts the isolux contours of an illuminance grid as GeoJSON, in the coordinates of the grid.
+/// The illuminances are scaled as in the diagram, and the levels default to the standard isolux levels.
+pub fn isolux_geojson(grid: &IlluminanceGrid, scale: IntensityScale, levels: Option<&[f64]>) -> String {
+    let (levels, contours) = scaled_contours(grid, scale, levels);
+    let contours: Vec<ContourLine> = contours
+        .into_iter()
+        .map(|(i, contour)| ContourLine {
+            level: levels[i],
+            ..contour
+        })
+        .collect();
+    contours_geojson(&contours, scale.illuminance_symbol())
+}
+
+/// Finds the contours of the grid at the scaled levels, returning the levels and each contour with the index of its level.
+fn scaled_contours(
+    grid: &IlluminanceGrid,
+    scale: IntensityScale,
+    levels: Option<&[f64]>,
+) -> (Vec<f64>, Vec<(usize, ContourLine)>) {
+    let factor = scale.factor();
+    let levels = levels.map_or_else(|| standard_isolux_levels(factor * grid.max()), |levels| levels.to_vec());
+    let contours = levels
+        .iter()
+        .enumerate()
+        .filter(|_| factor > 0.0)
+        .flat_map(|(i, level)| {
+            isolux_contours(grid, &[level / factor])
+                .into_iter()
+                .map(move |contour| (i, contour))
+        })
+        .collect();
+    (levels, contours)
+}
+
+/// The options of an isolux diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsoluxDiagramOptions {
+    /// The width of the document. The height follows from the aspect ratio of the grid.
+    pub size: f64,
+    /// The scaling of the intensities the grid was calculated with, for values per 1000 lm.
+    pub scale: IntensityScale,
+    /// The scaled illuminances of the contours, or `None` for the standard isolux levels.
+    pub levels: Option<Vec<f64>>,
+    /// The colours of the contours, repeated if there are more contours than colours.
+    pub colours: Vec<String>,
+    /// Whether to label the contours with their illuminances.
+    pub label_contours: bool,
+    /// The title drawn above the diagram.
+    pub title: Option<String>,
+    /// Whether to draw the legend below the diagram.
+    pub legend: bool,
+    /// The colour of the background, or `None` for a transparent background.
+    pub background: Option<String>,
+    /// The style of the lines of the grid.
+    pub grid_style: SvgStyle,
+    /// The style of the labels.
+    pub label_style: SvgStyle,
+}
+
+impl Default for IsoluxDiagramOptions {
+    fn default() -> Self {
+        Self {
+            size: 400.0,
+            scale: IntensityScale::Candela,
+            levels: None,
+            colours: ["#440154", "#3b528b", "#21918c", "#5ec962", "#fde725"]
+                .iter()
+                .map(|colour| colour.to_string())
+                .collect(),
+            label_contours: true,
+            title: None,
+            legend: true,
+            background: Some("white".to_string()),
+            grid_style: SvgStyle::stroke("#bbbbbb", 0.5),
+            label_style: SvgStyle::text("#444444", 10.0),
+        }
+    }
+}
+
+/// Renders the isolux diagram of an illuminance grid as SVG, drawn to scale with the axes in metres.
+pub fn isolux_diagram_svg(grid: &IlluminanceGrid, options: &IsoluxDiagramOptions) -> SvgDocument {
+    let (levels, contours) = scaled_contours(grid, options.scale, options.levels.as_deref());
+    let (x_min, y_min) = grid.grid.origin;
+    let (x_max, y_max) = (x_min + grid.grid.length, y_min + grid.grid.width);
+
+    let plot_width = options.size - 2.0 * MARGIN;
+    let plot_height = if grid.grid.length > 0.0 {
+        plot_width * grid.grid.width / grid.grid.length
+    } else {
+        plot_width
+    };
+    let title_height = if options.title.is_some() { 28.0 } else { 0.0 };
+    let legend_height = if options.legend {
+        LEGEND_ROW_HEIGHT * levels.len() as f64 + 8.0
+    } else {
+        0.0
+    };
+    let plot_bottom = title_height + plot_height + 2.0 * MARGIN;
+    let mut doc = SvgDocument::new(options.size, plot_bottom + legend_height);
+    if let Some(background) = &options.background {
+        doc.rect(0.0, 0.0, doc.width, doc.height, &SvgStyle::fill(background));
+    }
+    if let Some(title) = &options.title {
+        let style = SvgStyle::text("#000000", options.label_style.font_size + 4.0);
+        doc.text(options.size / 2.0, 20.0, title, TextAnchor::Middle, &style);
+    }
+
+    // The y-axis of the grid points up the page.
+    let (left, top) = (MARGIN, title_height + MARGIN);
+    let to_xy = |x: f64, y: f64| {
+        let sx = if x_max > x_min { left + plot_width * (x - x_min) / (x_max - x_min) } else { left };
+        let sy = if y_max > y_min { top + plot_height * (y_max - y) / (y_max - y_min) } else { top };
+        (sx, sy)
+    };
+
+    let step = nice_step((x_max - x_min).max(y_max - y_min), MAX_DIVISIONS);
+    let ticks = |lower: f64, upper: f64| {
+        let (first, last) = ((lower / step).ceil() as i64, (upper / step + 1E-9).floor() as i64);
+        (first..=last).map(|tick| tick as f64 * step).collect::<Vec<f64>>()
+    };
+    for x in ticks(x_min, x_max) {
+        let ((x1, y1), (x2, y2)) = (to_xy(x, y_min), to_xy(x, y_max));
+        doc.line(x1, y1, x2, y2, &options.grid_style);
+        doc.text(x1, y1 + 14.0, &format_number(x, 2), TextAnchor::Middle, &options.label_style);
+    }
+    for y in ticks(y_min, y_max) {
+        let ((x1, y1), (x2, y2)) = (to_xy(x_min, y), to_xy(x_max, y));
+        doc.line(x1, y1, x2, y2, &options.grid_style);
+        doc.text(x1 - 4.0, y1 + 4.0, &format_number(y, 2), TextAnchor::End, &options.label_style);
+    }
+    doc.rect(left, top, plot_width, plot_height, &SvgStyle::stroke("#444444", 1.0));
+    doc.text(left + plot_width, top - 6.0, "m", TextAnchor::End, &options.label_style);
+
+    let colour = |i: usize| options.colours.get(i % options.colours.len().max(1)).map_or("black", |col| col);
+    for (i, contour) in &contours {
+        let points: Vec<(f64, f64)> = contour.points.iter().map(|(x, y)| to_xy(*x, *y)).collect();
+        let style = SvgStyle::stroke(colour(*i), 1.5);
+        if contour.closed {
+            doc.polygon(&points, &style);
+        } else {
+            doc.polyline(&points, &style);
+        }
+        if options.label_contours && !points.is_empty() {
+            let (x, y) = points[points.len() / 2];
+            doc.text(x, y - 2.0, &format_number(levels[*i], 2), TextAnchor::Middle, &options.label_style);
+        }
+    }
+
+    if options.legend {
+        for (i, level) in levels.iter().enumerate() {
+            let y = plot_bottom + LEGEND_ROW_HEIGHT * (i as f64 + 0.5);
+            doc.line(MARGIN, y, MARGIN + 30.0, y, &SvgStyle::stroke(colour(i), 1.5));
+            let label = format!("{} {}", format_number(*level, 2), options.scale.illuminance_symbol());
+            doc.text(MARGIN + 38.0, y + 4.0, &label, TextAnchor::Start, &options.label_style);
+        }
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::isotropic_web;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_standard_levels() {
+        assert_eq!(standard_isolux_levels(150.0), vec![2.0, 5.0, 10.0, 20.0, 50.0, 100.0]);
+        assert_eq!(standard_isolux_levels(100.0), vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0]);
+        assert!(standard_isolux_levels(0.0).is_empty());
+    }
+
+    /// An isotropic source of 1000 cd at 2 m gives E = 250 cos^3(gamma) lx, so the 125 lx contour is a circle of
+    /// radius 2 tan(gamma) with cos^3(gamma) = 1 / 2.
+    #[test]
+    fn test_footprint_contours() {
+        let grid = IlluminanceGrid::footprint(&isotropic_web(1000.0), 2.0, 3.0, 61);
+        let contours = isolux_contours(&grid, &[125.0]);
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        let radius = 2.0 * 0.5_f64.powf(1.0 / 3.0).acos().tan();
+        for (x, y) in &contours[0].points {
+            assert_abs_diff_eq!(x.hypot(*y), radius, epsilon = 0.02);
+        }
+
+        // Per 1000 lm of a 2000 lm luminaire, the same contour is at 62.5 lx/klm.
+        let scale = IntensityScale::CandelaPerKilolumen(2000.0);
+        let geojson = isolux_geojson(&grid, scale, Some(&[62.5]));
+        assert!(geojson.starts_with("{\"type\":\"FeatureCollection\""));
+        assert!(geojson.contains("\"level\":62.5,\"label\":\"62.5 lx/klm\",\"closed\":true"));
+        assert_eq!(geojson.matches("\"Feature\"").count(), 1);
+    }
+
+    #[test]
+    fn test_isolux_diagram() {
+        let grid = IlluminanceGrid::footprint(&isotropic_web(1000.0), 2.0, 3.0, 31);
+        let svg = isolux_diagram_svg(&grid, &IsoluxDiagramOptions::default()).to_string();
+        // The levels are 5 to 200 lx, of which those above the 45.8 lx at the middle of each edge are closed, and those
+        // below the 21 lx at the corners lie outside of the grid.
+        assert_eq!(standard_isolux_levels(grid.max()), vec![5.0, 10.0, 20.0, 50.0, 100.0, 200.0]);
+        assert_eq!(svg.matches("<polygon").count(), 3);
+        assert_eq!(svg.matches("<polyline").count(), 0);
+        assert!(svg.contains(">100 lx</text>"));
+        assert!(svg.contains("width=\"400\" height=\"516\""));
+    }
+}
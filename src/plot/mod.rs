@@ -0,0 +1,10 @@
+//! Rendering of photometric diagrams as SVG, with no external dependencies.
+
+pub mod cartesian;
+pub mod cone;
+pub mod contour;
+pub mod isocandela;
+pub mod isolux;
+pub mod polar;
+pub mod svg;
+pub use self::{cartesian::*, cone::*, contour::*, isocandela::*, isolux::*, polar::*, svg::*};
@@ -0,0 +1,288 @@
+//! Rendering of the polar intensity diagram of a luminaire, with the intensity curves through pairs of opposing C-planes.
+//!
+//! The nadir (gamma 0) points down the page and the zenith up. The lower C-plane of each pair is drawn on the right, and
+//! the upper C-plane on the left, following the arrangement of `PhotometricWeb::get_cplane_pair`.
+
+use super::{format_number, nice_step, SvgDocument, SvgStyle, TextAnchor};
+use crate::{photweb::PhotometricWeb, util::geom::degrees_to_radians};
+use std::f64::consts::PI;
+
+/// The number of rings the radial grid is divided into, at most.
+const MAX_RINGS: usize = 5;
+/// The angle (in degrees) between the radial lines of the grid.
+const SPOKE_STEP: f64 = 30.0;
+/// The margin around the plot, leaving room for the angle labels.
+const MARGIN: f64 = 36.0;
+/// The height of each entry of the legend.
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+
+/// The scaling of the intensities of a diagram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntensityScale {
+    /// The intensities of the web, in candela.
+    Candela,
+    /// The intensities per 1000 lumens of the given flux, in cd/klm.
+    CandelaPerKilolumen(f64),
+}
+
+impl IntensityScale {
+    /// The factor applied to the intensities of the web.
+    pub fn factor(&self) -> f64 {
+        match self {
+            Self::Candela => 1.0,
+            Self::CandelaPerKilolumen(flux) if *flux > 0.0 => 1000.0 / flux,
+            Self::CandelaPerKilolumen(_) => 0.0,
+        }
+    }
+
+    /// The symbol of the unit of the scaled intensities.
+    pub fn unit_symbol(&self) -> &'static str {
+        match self {
+            Self::Candela => "cd",
+            Self::CandelaPerKilolumen(_) => "cd/klm",
+        }
+    }
+
+    /// The symbol of the unit of illuminances calculated from the scaled intensities.
+    pub fn illuminance_symbol(&self) -> &'static str {
+        match self {
+            Self::Candela => "lx",
+            Self::CandelaPerKilolumen(_) => "lx/klm",
+        }
+    }
+}
+
+/// An intensity curve through a pair of opposing C-planes, and how it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolarCurve {
+    /// The lower and upper C-planes of the pair, in degrees.
+    pub cplanes: (f64, f64),
+    /// The style of the line of the curve.
+    pub style: SvgStyle,
+    /// The label of the curve in the legend, or `None` to label it with its C-planes.
+    pub label: Option<String>,
+}
+
+impl PolarCurve {
+    /// Creates a curve through the pair of C-planes (in degrees), drawn in a solid line of the given colour.
+    pub fn new(lower: f64, upper: f64, colour: &str) -> Self {
+        Self {
+            cplanes: (lower, upper),
+            style: SvgStyle::stroke(colour, 1.5),
+            label: None,
+        }
+    }
+
+    /// The label of the curve in the legend.
+    pub fn legend_label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| {
+            format!("C{} - C{}", format_number(self.cplanes.0, 1), format_number(self.cplanes.1, 1))
+        })
+    }
+}
+
+/// The options of a polar diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolarDiagramOptions {
+    /// The width and height of the plot area, excluding the title and legend.
+    pub size: f64,
+    /// The curves to draw.
+    pub curves: Vec<PolarCurve>,
+    /// The scaling of the intensities.
+    pub scale: IntensityScale,
+    /// The intensity at the outer ring of the grid, or `None` to fit the grid to the curves.
+    pub max_intensity: Option<f64>,
+    /// The title drawn above the diagram.
+    pub title: Option<String>,
+    /// Whether to draw the legend below the diagram.
+    pub legend: bool,
+    /// The colour of the background, or `None` for a transparent background.
+    pub background: Option<String>,
+    /// The style of the lines of the grid.
+    pub grid_style: SvgStyle,
+    /// The style of the labels.
+    pub label_style: SvgStyle,
+}
+
+impl Default for PolarDiagramOptions {
+    /// The C0-C180 plane pair in a solid red line and the C90-C270 plane pair in a dashed blue line, in candela.
+    fn default() -> Self {
+        Self {
+            size: 400.0,
+            curves: vec![
+                PolarCurve::new(0.0, 180.0, "#d62728"),
+                PolarCurve {
+                    style: SvgStyle::stroke("#1f77b4", 1.5).with_dash_array("6 3"),
+                    ..PolarCurve::new(90.0, 270.0, "#1f77b4")
+                },
+            ],
+            scale: IntensityScale::Candela,
+            max_intensity: None,
+            title: None,
+            legend: true,
+            background: Some("white".to_string()),
+            grid_style: SvgStyle::stroke("#bbbbbb", 0.5),
+            label_style: SvgStyle::text("#444444", 10.0),
+        }
+    }
+}
+
+/// Returns the intensity curve through a pair of C-planes (in degrees) as (angle, intensity) pairs, with the angles of
+/// the upper plane mirrored to 2 pi - gamma, running from pi to 2 pi, followed by those of the lower plane running from
+/// 0 to pi, as `get_cplane_pair`.
+/// Pairs that are not planes of the web are interpolated at the gamma angles of its first plane.
+pub fn cplane_pair_polar(web: &PhotometricWeb, lower: f64, upper: f64) -> Vec<(f64, f64)> {
+    match web.get_cplane_pair(lower, upper) {
+        Some((angles, intensities)) => angles.into_iter().zip(intensities).collect(),
+        None => {
+            let gammas: Vec<f64> = web
+                .planes()
+                .first()
+                .map(|pl| pl.angles().to_vec())
+                .unwrap_or_default();
+            let (lower, upper) = (degrees_to_radians(lower), degrees_to_radians(upper));
+            gammas
+                .iter()
+                .rev()
+                .map(|gamma| (2.0 * PI - gamma, web.intensity_at(upper, *gamma)))
+                .chain(gammas.iter().map(|gamma| (*gamma, web.intensity_at(lower, *gamma))))
+                .collect()
+        }
+    }
+}
+
+/// Renders the polar intensity diagram of a Type C web as SVG.
+pub fn polar_diagram_svg(web: &PhotometricWeb, options: &PolarDiagramOptions) -> SvgDocument {
+    let factor = options.scale.factor();
+    let curves: Vec<Vec<(f64, f64)>> = options
+        .curves
+        .iter()
+        .map(|curve| {
+            cplane_pair_polar(web, curve.cplanes.0, curve.cplanes.1)
+                .into_iter()
+                .map(|(angle, intensity)| (angle, factor * intensity))
+                .collect()
+        })
+        .collect();
+
+    // The grid is fitted to a round step, so that the outer ring is the first multiple of the step above the curves.
+    let max_intensity = options.max_intensity.unwrap_or_else(|| {
+        curves
+            .iter()
+            .flat_map(|curve| curve.iter().map(|(_, intensity)| *intensity))
+            .fold(0.0, f64::max)
+    });
+    let step = nice_step(max_intensity, MAX_RINGS);
+    let n_rings = ((max_intensity / step).ceil() as usize).max(1);
+    let outer = step * n_rings as f64;
+
+    let title_height = if options.title.is_some() { 28.0 } else { 0.0 };
+    let legend_height = if options.legend {
+        LEGEND_ROW_HEIGHT * options.curves.len() as f64 + 8.0
+    } else {
+        0.0
+    };
+    let mut doc = SvgDocument::new(options.size, options.size + title_height + legend_height);
+    if let Some(background) = &options.background {
+        doc.rect(0.0, 0.0, doc.width, doc.height, &SvgStyle::fill(background));
+    }
+    if let Some(title) = &options.title {
+        let style = SvgStyle::text("#000000", options.label_style.font_size + 4.0);
+        doc.text(options.size / 2.0, 20.0, title, TextAnchor::Middle, &style);
+    }
+
+    let (cx, cy) = (options.size / 2.0, title_height + options.size / 2.0);
+    let radius = options.size / 2.0 - MARGIN;
+    let to_xy = |angle: f64, intensity: f64| {
+        let r = if outer > 0.0 { radius * intensity / outer } else { 0.0 };
+        (cx + r * angle.sin(), cy + r * angle.cos())
+    };
+
+    for ring in 1..=n_rings {
+        doc.circle(cx, cy, radius * ring as f64 / n_rings as f64, &options.grid_style);
+    }
+    let n_spokes = (360.0 / SPOKE_STEP).round() as usize;
+    for spoke in 0..n_spokes {
+        let angle = degrees_to_radians(spoke as f64 * SPOKE_STEP);
+        let (x, y) = to_xy(angle, outer);
+        doc.line(cx, cy, x, y, &options.grid_style);
+        let (lx, ly) = (cx + (radius + 14.0) * angle.sin(), cy + (radius + 14.0) * angle.cos());
+        let gamma = spoke as f64 * SPOKE_STEP;
+        let gamma = if gamma > 180.0 { 360.0 - gamma } else { gamma };
+        doc.text(lx, ly + 4.0, &format!("{}\u{b0}", format_number(gamma, 0)), TextAnchor::Middle, &options.label_style);
+    }
+    for ring in 1..=n_rings {
+        let r = radius * ring as f64 / n_rings as f64;
+        let value = format_number(step * ring as f64, 2);
+        doc.text(cx + 3.0, cy - r - 2.0, &value, TextAnchor::Start, &options.label_style);
+    }
+    doc.text(MARGIN / 2.0, title_height + 14.0, options.scale.unit_symbol(), TextAnchor::Start, &options.label_style);
+
+    for (curve, points) in options.curves.iter().zip(curves.iter()) {
+        let xy: Vec<(f64, f64)> = points.iter().map(|(angle, intensity)| to_xy(*angle, *intensity)).collect();
+        doc.polyline(&xy, &curve.style);
+    }
+
+    if options.legend {
+        let top = title_height + options.size;
+        for (i, curve) in options.curves.iter().enumerate() {
+            let y = top + LEGEND_ROW_HEIGHT * (i as f64 + 0.5);
+            doc.line(MARGIN, y, MARGIN + 30.0, y, &curve.style);
+            doc.text(MARGIN + 38.0, y + 4.0, &curve.legend_label(), TextAnchor::Start, &options.label_style);
+        }
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photweb::test_webs::planar_web;
+    use std::f64::consts::FRAC_PI_2;
+
+    const SNAPSHOT_DEFAULT: &str = include_str!("snapshots/polar_default.svg");
+    const SNAPSHOT_STYLED: &str = include_str!("snapshots/polar_styled.svg");
+
+    /// A web with planes every 90 degrees and gamma every 15 degrees, with a wider distribution in C90-C270.
+    fn test_web() -> PhotometricWeb {
+        planar_web(90.0, 15.0, |c_angle, gamma| {
+            let power = if (c_angle / FRAC_PI_2).round() as i32 % 2 == 0 { 4 } else { 1 };
+            850.0 * gamma.cos().max(0.0).powi(power) + 20.0
+        })
+    }
+
+    #[test]
+    fn test_cplane_pair_polar() {
+        let web = test_web();
+        let pair = cplane_pair_polar(&web, 0.0, 180.0);
+        assert_eq!(pair.len(), 26);
+        assert_eq!(pair[0], (PI, 20.0));
+        assert_eq!(pair[12], (2.0 * PI, 870.0));
+        assert_eq!(pair[13], (0.0, 870.0));
+
+        // The C45-C225 pair is not in the web, so is interpolated between the neighbouring planes.
+        let interpolated = cplane_pair_polar(&web, 45.0, 225.0);
+        assert_eq!(interpolated.len(), 26);
+        assert!((interpolated[13].1 - 870.0).abs() < 1E-9);
+    }
+
+    #[test]
+    fn test_polar_diagram_snapshots() {
+        let web = test_web();
+        assert_eq!(polar_diagram_svg(&web, &PolarDiagramOptions::default()).to_string(), SNAPSHOT_DEFAULT);
+
+        let options = PolarDiagramOptions {
+            size: 300.0,
+            curves: vec![PolarCurve {
+                label: Some("Diagonal".to_string()),
+                ..PolarCurve::new(45.0, 225.0, "green")
+            }],
+            scale: IntensityScale::CandelaPerKilolumen(2000.0),
+            max_intensity: Some(600.0),
+            title: Some("Test & luminaire".to_string()),
+            background: None,
+            ..PolarDiagramOptions::default()
+        };
+        assert_eq!(polar_diagram_svg(&web, &options).to_string(), SNAPSHOT_STYLED);
+    }
+}
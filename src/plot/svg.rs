@@ -0,0 +1,321 @@
+//! A minimal SVG document builder, used by the diagram renderers.
+//!
+//! Coordinates are in SVG user units (pixels), with the origin at the top left and y increasing downwards.
+//! Numbers are written with at most two decimal places, so that the output is stable and can be compared in tests.
+
+use crate::err::Error;
+use std::{fmt::Write as _, path::Path};
+
+/// The stroke, fill and font of an SVG element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    /// The colour of the stroke, or `None` for no stroke.
+    pub stroke: Option<String>,
+    /// The width of the stroke.
+    pub stroke_width: f64,
+    /// The dash pattern of the stroke, such as "4 2", or `None` for a solid stroke.
+    pub dash_array: Option<String>,
+    /// The colour of the fill, or `None` for no fill.
+    pub fill: Option<String>,
+    /// The opacity of the fill, from 0 to 1.
+    pub fill_opacity: f64,
+    /// The size of the font of text elements.
+    pub font_size: f64,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            stroke: None,
+            stroke_width: 1.0,
+            dash_array: None,
+            fill: None,
+            fill_opacity: 1.0,
+            font_size: 12.0,
+        }
+    }
+}
+
+impl SvgStyle {
+    /// A style with a solid stroke of the given colour and width, and no fill.
+    pub fn stroke(colour: &str, width: f64) -> Self {
+        Self {
+            stroke: Some(colour.to_string()),
+            stroke_width: width,
+            ..Self::default()
+        }
+    }
+
+    /// A style with a fill of the given colour, and no stroke.
+    pub fn fill(colour: &str) -> Self {
+        Self {
+            fill: Some(colour.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// A style for text of the given colour and font size.
+    pub fn text(colour: &str, font_size: f64) -> Self {
+        Self {
+            fill: Some(colour.to_string()),
+            font_size,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the style with its stroke dashed with the given pattern.
+    pub fn with_dash_array(self, dash_array: &str) -> Self {
+        Self {
+            dash_array: Some(dash_array.to_string()),
+            ..self
+        }
+    }
+
+    /// Returns the style with a fill of the given colour and opacity.
+    pub fn with_fill(self, colour: &str, opacity: f64) -> Self {
+        Self {
+            fill: Some(colour.to_string()),
+            fill_opacity: opacity,
+            ..self
+        }
+    }
+
+    /// Writes the style as SVG presentation attributes.
+    fn attributes(&self) -> String {
+        let mut attrs = format!(
+            "stroke=\"{}\" fill=\"{}\"",
+            self.stroke.as_deref().unwrap_or("none"),
+            self.fill.as_deref().unwrap_or("none")
+        );
+        if self.stroke.is_some() {
+            let _ = write!(attrs, " stroke-width=\"{}\"", format_number(self.stroke_width, 2));
+        }
+        if let Some(dash_array) = &self.dash_array {
+            let _ = write!(attrs, " stroke-dasharray=\"{}\"", dash_array);
+        }
+        if self.fill.is_some() && self.fill_opacity < 1.0 {
+            let _ = write!(attrs, " fill-opacity=\"{}\"", format_number(self.fill_opacity, 2));
+        }
+        attrs
+    }
+}
+
+/// The horizontal alignment of text relative to its anchor point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAnchor {
+    Start,
+    Middle,
+    End,
+}
+
+impl TextAnchor {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Middle => "middle",
+            Self::End => "end",
+        }
+    }
+}
+
+/// An SVG document, built up from simple elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgDocument {
+    /// The width of the document.
+    pub width: f64,
+    /// The height of the document.
+    pub height: f64,
+    /// The font family used for text.
+    pub font_family: String,
+    /// The elements of the document, in drawing order.
+    pub elements: Vec<String>,
+}
+
+impl SvgDocument {
+    /// Creates an empty document of the given size.
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            font_family: "sans-serif".to_string(),
+            elements: Vec::new(),
+        }
+    }
+
+    /// Adds a rectangle with its top left corner at (x, y).
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, style: &SvgStyle) {
+        self.elements.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            coord(x),
+            coord(y),
+            coord(width),
+            coord(height),
+            style.attributes()
+        ));
+    }
+
+    /// Adds a straight line from (x1, y1) to (x2, y2).
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, style: &SvgStyle) {
+        self.elements.push(format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+            coord(x1),
+            coord(y1),
+            coord(x2),
+            coord(y2),
+            style.attributes()
+        ));
+    }
+
+    /// Adds a circle centred on (cx, cy).
+    pub fn circle(&mut self, cx: f64, cy: f64, radius: f64, style: &SvgStyle) {
+        self.elements.push(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            coord(cx),
+            coord(cy),
+            coord(radius),
+            style.attributes()
+        ));
+    }
+
+    /// Adds an open line through the points.
+    pub fn polyline(&mut self, points: &[(f64, f64)], style: &SvgStyle) {
+        self.elements.push(format!(
+            "<polyline points=\"{}\" {}/>",
+            point_list(points),
+            style.attributes()
+        ));
+    }
+
+    /// Adds a closed shape through the points.
+    pub fn polygon(&mut self, points: &[(f64, f64)], style: &SvgStyle) {
+        self.elements.push(format!(
+            "<polygon points=\"{}\" {}/>",
+            point_list(points),
+            style.attributes()
+        ));
+    }
+
+    /// Adds text with its anchor point at (x, y), on the baseline of the text.
+    pub fn text(&mut self, x: f64, y: f64, content: &str, anchor: TextAnchor, style: &SvgStyle) {
+        self.elements.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+            coord(x),
+            coord(y),
+            anchor.as_str(),
+            format_number(style.font_size, 2),
+            style.fill.as_deref().unwrap_or("black"),
+            escape_text(content)
+        ));
+    }
+
+    /// Writes the document to a file.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SvgDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"{}\">",
+            escape_text(&self.font_family),
+            w = coord(self.width),
+            h = coord(self.height),
+        )?;
+        for element in &self.elements {
+            writeln!(f, "  {}", element)?;
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+/// Formats a number with at most the given number of decimal places, without trailing zeros.
+pub fn format_number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value);
+    let trimmed = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Returns a round step (1, 2, 2.5 or 5 times a power of ten) that divides the range into at most the given number of
+/// divisions, for the labels of axes and grids.
+pub fn nice_step(range: f64, divisions: usize) -> f64 {
+    if range <= 0.0 || divisions == 0 {
+        return 1.0;
+    }
+    let rough = range / divisions as f64;
+    let magnitude = 10_f64.powf(rough.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough * (1.0 - 1E-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Formats a coordinate of the document.
+fn coord(value: f64) -> String {
+    format_number(value, 2)
+}
+
+/// Formats a list of points as the value of a points attribute.
+fn point_list(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{},{}", coord(*x), coord(*y)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Escapes the characters of text that have special meanings in XML.
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1.5, 2), "1.5");
+        assert_eq!(format_number(2.0, 2), "2");
+        assert_eq!(format_number(-0.001, 2), "0");
+        assert_eq!(format_number(1234.567, 1), "1234.6");
+        assert_eq!(format_number(10.0, 0), "10");
+    }
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(1000.0, 5), 200.0);
+        assert_eq!(nice_step(1200.0, 5), 250.0);
+        assert_eq!(nice_step(3.0, 5), 1.0);
+        assert_eq!(nice_step(0.07, 4), 0.02);
+    }
+
+    #[test]
+    fn test_document() {
+        let mut doc = SvgDocument::new(100.0, 50.0);
+        doc.line(0.0, 0.0, 10.5, 20.0, &SvgStyle::stroke("#000", 1.0).with_dash_array("4 2"));
+        doc.polygon(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)], &SvgStyle::fill("red"));
+        doc.text(5.0, 5.0, "E < 10 lx", TextAnchor::Middle, &SvgStyle::text("#333", 10.0));
+        assert_eq!(
+            doc.to_string(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\" font-family=\"sans-serif\">\n  \
+             <line x1=\"0\" y1=\"0\" x2=\"10.5\" y2=\"20\" stroke=\"#000\" fill=\"none\" stroke-width=\"1\" stroke-dasharray=\"4 2\"/>\n  \
+             <polygon points=\"0,0 1,0 1,1\" stroke=\"none\" fill=\"red\"/>\n  \
+             <text x=\"5\" y=\"5\" text-anchor=\"middle\" font-size=\"10\" fill=\"#333\">E &lt; 10 lx</text>\n\
+             </svg>\n"
+        );
+    }
+}
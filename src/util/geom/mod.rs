@@ -1,3 +1,5 @@
 pub mod angles;
+pub mod rotation;
+pub mod vector;
 
-pub use self::angles::*;
+pub use self::{angles::*, rotation::*, vector::*};
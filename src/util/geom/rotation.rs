@@ -0,0 +1,121 @@
+use super::Vector3;
+
+/// A rotation in three dimensions, stored as a rotation matrix.
+///
+/// Rotations are defined in the luminaire coordinate system (see `Vector3`):
+/// - Yaw rotates about the vertical (z) axis. Positive yaw increases the C-angle of a direction.
+/// - Pitch rotates about the C90 (y) axis. Positive pitch swings the nadir towards the C0 plane.
+/// - Roll rotates about the C0 (x) axis. Positive roll swings the nadir towards the C90 plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    matrix: [[f64; 3]; 3],
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Rotation {
+    /// A rotation that leaves all vectors unchanged.
+    pub const fn identity() -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    /// A rotation about the vertical (z) axis, in radians.
+    pub fn from_yaw(yaw: f64) -> Self {
+        let (sin, cos) = yaw.sin_cos();
+        Self {
+            matrix: [[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    /// A rotation about the C90 (y) axis, in radians.
+    pub fn from_pitch(pitch: f64) -> Self {
+        let (sin, cos) = pitch.sin_cos();
+        Self {
+            matrix: [[cos, 0.0, -sin], [0.0, 1.0, 0.0], [sin, 0.0, cos]],
+        }
+    }
+
+    /// A rotation about the C0 (x) axis, in radians.
+    pub fn from_roll(roll: f64) -> Self {
+        let (sin, cos) = roll.sin_cos();
+        Self {
+            matrix: [[1.0, 0.0, 0.0], [0.0, cos, -sin], [0.0, sin, cos]],
+        }
+    }
+
+    /// Composes a rotation from yaw, pitch and roll angles (in radians).
+    /// The roll is applied first, followed by the pitch and finally the yaw.
+    pub fn from_yaw_pitch_roll(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Self::from_yaw(yaw)
+            .compose(&Self::from_pitch(pitch))
+            .compose(&Self::from_roll(roll))
+    }
+
+    /// Returns the rotation that applies `other` first, followed by `self`.
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, val) in row.iter_mut().enumerate() {
+                *val = (0..3).map(|k| self.matrix[i][k] * other.matrix[k][j]).sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    /// Returns the inverse of this rotation.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, val) in row.iter_mut().enumerate() {
+                *val = self.matrix[j][i];
+            }
+        }
+        Rotation { matrix }
+    }
+
+    /// Applies the rotation to a vector.
+    pub fn rotate(&self, vec: &Vector3) -> Vector3 {
+        let m = &self.matrix;
+        Vector3::new(
+            m[0][0] * vec.x + m[0][1] * vec.y + m[0][2] * vec.z,
+            m[1][0] * vec.x + m[1][1] * vec.y + m[1][2] * vec.z,
+            m[2][0] * vec.x + m[2][1] * vec.y + m[2][2] * vec.z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rotation, Vector3};
+    use crate::util::geom::degrees_to_radians;
+    use approx::assert_abs_diff_eq;
+
+    /// Check the sign conventions of each of the elementary rotations against the nadir.
+    #[test]
+    fn test_rotation_conventions() {
+        let nadir = Vector3::new(0.0, 0.0, -1.0);
+
+        let rolled = Rotation::from_roll(degrees_to_radians(10.0)).rotate(&nadir);
+        assert!(rolled.y > 0.0);
+
+        let pitched = Rotation::from_pitch(degrees_to_radians(10.0)).rotate(&nadir);
+        assert!(pitched.x > 0.0);
+
+        let yawed = Rotation::from_yaw(degrees_to_radians(90.0)).rotate(&Vector3::new(1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(yawed.y, 1.0, epsilon = 1E-12);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let rot = Rotation::from_yaw_pitch_roll(0.3, -0.2, 0.9);
+        let vec = Vector3::new(0.2, -0.5, 0.7);
+        let back = rot.inverse().rotate(&rot.rotate(&vec));
+        assert_abs_diff_eq!((back - vec).norm(), 0.0, epsilon = 1E-12);
+    }
+}
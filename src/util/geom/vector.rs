@@ -0,0 +1,182 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A simple three dimensional vector, used for representing positions and directions.
+///
+/// Directions relative to a luminaire use the luminaire coordinate system, in which the x-axis points
+/// along the C0 plane, the y-axis points along the C90 plane and the z-axis points upwards, such that
+/// gamma = 0 (the nadir) points along -z.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vector3 {
+    /// Creates a new vector from its components.
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Creates a unit vector pointing in the direction of the given C-plane and gamma angles (in radians).
+    pub fn from_c_gamma(c_angle: f64, gamma: f64) -> Self {
+        Self::new(
+            gamma.sin() * c_angle.cos(),
+            gamma.sin() * c_angle.sin(),
+            -gamma.cos(),
+        )
+    }
+
+    /// Returns the C-plane and gamma angles (in radians) of the direction of this vector.
+    /// The C-plane angle is in the range 0 -> 2 pi, and the gamma angle in the range 0 -> pi.
+    pub fn to_c_gamma(&self) -> (f64, f64) {
+        let unit = self.normalise();
+        let gamma = (-unit.z).clamp(-1.0, 1.0).acos();
+        let c_angle = unit.y.atan2(unit.x).rem_euclid(2.0 * std::f64::consts::PI);
+        (c_angle, gamma)
+    }
+
+    /// Creates a unit vector from Type B angles (in radians): the angle of the B-plane (V), hinged on the horizontal
+    /// C90 axis, and the angle within that plane (H). The direction (0, 0) is the nadir, positive V swings towards C0 and
+    /// positive H towards C90.
+    pub fn from_type_b(v_angle: f64, h_angle: f64) -> Self {
+        Self::new(
+            h_angle.cos() * v_angle.sin(),
+            h_angle.sin(),
+            -h_angle.cos() * v_angle.cos(),
+        )
+    }
+
+    /// Returns the Type B angles (V, H) of the direction of this vector, in radians.
+    /// V is in the range -pi -> pi, and H in the range -pi / 2 -> pi / 2.
+    pub fn to_type_b(&self) -> (f64, f64) {
+        let unit = self.normalise();
+        (unit.x.atan2(-unit.z), unit.y.clamp(-1.0, 1.0).asin())
+    }
+
+    /// Creates a unit vector from Type A angles (in radians): the angle of the A-plane (H), hinged on the horizontal
+    /// C0 axis, and the angle within that plane (V). The direction (0, 0) is the nadir, positive H swings towards C90 and
+    /// positive V towards C0.
+    pub fn from_type_a(h_angle: f64, v_angle: f64) -> Self {
+        Self::new(
+            v_angle.sin(),
+            v_angle.cos() * h_angle.sin(),
+            -v_angle.cos() * h_angle.cos(),
+        )
+    }
+
+    /// Returns the Type A angles (H, V) of the direction of this vector, in radians.
+    /// H is in the range -pi -> pi, and V in the range -pi / 2 -> pi / 2.
+    pub fn to_type_a(&self) -> (f64, f64) {
+        let unit = self.normalise();
+        (unit.y.atan2(-unit.z), unit.x.clamp(-1.0, 1.0).asin())
+    }
+
+    /// The dot product of two vectors.
+    pub fn dot(&self, other: &Vector3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// The cross product of two vectors.
+    pub fn cross(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// The length of the vector.
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns a unit vector in the same direction. A zero vector is returned unchanged.
+    pub fn normalise(&self) -> Vector3 {
+        let norm = self.norm();
+        if norm > 0.0 {
+            *self * (1.0 / norm)
+        } else {
+            *self
+        }
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+    fn add(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+    fn sub(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<f64> for Vector3 {
+    type Output = Vector3;
+    fn mul(self, rhs: f64) -> Vector3 {
+        Vector3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vector3;
+    use crate::util::geom::degrees_to_radians;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_c_gamma_round_trip() {
+        // The nadir points straight down.
+        let nadir = Vector3::from_c_gamma(0.0, 0.0);
+        assert_abs_diff_eq!(nadir.z, -1.0, epsilon = 1E-12);
+
+        // C90, gamma 90 points along the y-axis.
+        let c90 = Vector3::from_c_gamma(degrees_to_radians(90.0), degrees_to_radians(90.0));
+        assert_abs_diff_eq!(c90.y, 1.0, epsilon = 1E-12);
+
+        let (c_angle, gamma) =
+            Vector3::from_c_gamma(degrees_to_radians(250.0), degrees_to_radians(35.0)).to_c_gamma();
+        assert_abs_diff_eq!(c_angle, degrees_to_radians(250.0), epsilon = 1E-12);
+        assert_abs_diff_eq!(gamma, degrees_to_radians(35.0), epsilon = 1E-12);
+    }
+
+    /// Check the Type A and B systems against the standard conversion formulae from the C-gamma system:
+    /// tan(V) = tan(gamma) cos(C) and sin(H) = sin(gamma) sin(C) for Type B, and the transposed relations for Type A.
+    #[test]
+    fn test_type_a_b_angles() {
+        let (c_angle, gamma) = (degrees_to_radians(30.0), degrees_to_radians(40.0));
+        let dir = Vector3::from_c_gamma(c_angle, gamma);
+
+        let (v_angle, h_angle) = dir.to_type_b();
+        assert_abs_diff_eq!(v_angle.tan(), gamma.tan() * c_angle.cos(), epsilon = 1E-12);
+        assert_abs_diff_eq!(h_angle.sin(), gamma.sin() * c_angle.sin(), epsilon = 1E-12);
+        assert_abs_diff_eq!((Vector3::from_type_b(v_angle, h_angle) - dir).norm(), 0.0, epsilon = 1E-12);
+
+        let (h_angle, v_angle) = dir.to_type_a();
+        assert_abs_diff_eq!(h_angle.tan(), gamma.tan() * c_angle.sin(), epsilon = 1E-12);
+        assert_abs_diff_eq!(v_angle.sin(), gamma.sin() * c_angle.cos(), epsilon = 1E-12);
+        assert_abs_diff_eq!((Vector3::from_type_a(h_angle, v_angle) - dir).norm(), 0.0, epsilon = 1E-12);
+    }
+
+    #[test]
+    fn test_products() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.dot(&y), 0.0);
+        assert_eq!(x.cross(&y), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!((x + y).norm(), 2.0_f64.sqrt());
+    }
+}